@@ -0,0 +1,138 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, git::GIT_CMD, ignore::Ignore, vault};
+
+/// How many hex digits of their hash content-addressed assets are named after.
+const HASH_LEN: usize = 16;
+
+/// Returns the hash git would give a blob with the given content.
+fn hash(content: &[u8]) -> Result<String> {
+    let mut child = Command::new(GIT_CMD)
+        .args(["hash-object", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to execute git to hash an asset")?;
+    // The unwrap is fine, since stdin was piped just above.
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(content)
+        .context("failed to pass an asset to git")?;
+    let output = child
+        .wait_with_output()
+        .context("failed to execute git to hash an asset")?;
+    if !output.status.success() {
+        bail!(
+            "failed to hash an asset: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Stores the given content in the assets directory, named after its hash and with the given
+/// extension, e.g. `assets/3b18e512dba79e4c.png`, and returns its path, relative to base-dir. The
+/// same content is only ever stored once.
+pub fn store(args: &cli::Args, content: &[u8], extension: &str) -> Result<PathBuf> {
+    let hash = hash(content)?;
+    let asset = args.assets_dir.join(format!(
+        "{}.{}",
+        &hash[..HASH_LEN.min(hash.len())],
+        extension
+    ));
+    let absolute_asset = args.base_dir.join(&asset);
+    if !absolute_asset.exists() {
+        std::fs::create_dir_all(args.base_dir.join(&args.assets_dir))
+            .context(format!("failed to create {}", args.assets_dir.display()))?;
+        std::fs::write(&absolute_asset, content)
+            .context(format!("failed to write {}", asset.display()))?;
+    }
+    Ok(asset)
+}
+
+/// The extensions of the files that Markdown can embed as images.
+static IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "avif"];
+
+/// Returns whether the given file is an image, going by its extension.
+pub fn is_image(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        IMAGE_EXTENSIONS.contains(&extension.to_string_lossy().to_lowercase().as_str())
+    })
+}
+
+/// Returns the directory, relative to base-dir, that attachments of the given note (also relative
+/// to base-dir) are copied to: the assets-dir, or the note's own, e.g. ideas/app.assets/ for
+/// ideas/app.md.
+pub fn dir(args: &cli::Args, note: &Path, per_note: bool) -> PathBuf {
+    match per_note {
+        true => {
+            let mut name = note.file_stem().unwrap_or_default().to_os_string();
+            name.push(".assets");
+            note.with_file_name(name)
+        }
+        false => args.assets_dir.clone(),
+    }
+}
+
+/// Warns if the given asset (relative to base-dir) is ignored, by .jotignore, the ignore flag or
+/// git, since it is then never synced along with the notes that reference it.
+pub fn warn_if_ignored(args: &cli::Args, asset: &Path) -> Result<()> {
+    let ignored_by_git = Command::new(GIT_CMD)
+        .args(["check-ignore", "--quiet", "--"])
+        .arg(asset)
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if ignored_by_git || Ignore::load(&args.base_dir, &args.ignore)?.is_ignored(asset) {
+        println!(
+            "warning: {} is ignored, so it is not synced along with the notes that reference it",
+            asset.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns a Markdown reference from the given note to the given asset (both relative to
+/// base-dir) with the given label, e.g. `![](../assets/a.png)`. Images are embedded, anything else
+/// is linked to.
+pub fn reference(note: &Path, asset: &Path, label: &str, is_image: bool) -> String {
+    let target = vault::relative_to(note.parent().unwrap_or_else(|| Path::new("")), asset)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let target = match target.contains([' ', '(', ')']) {
+        true => format!("<{}>", target),
+        false => target,
+    };
+    format!("{}[{}]({})", if is_image { "!" } else { "" }, label, target)
+}
+
+#[test]
+fn asset_references() {
+    assert_eq!(
+        reference(
+            Path::new("a/b/note.md"),
+            Path::new("a/assets/x.png"),
+            "",
+            true
+        ),
+        "![](../assets/x.png)"
+    );
+    assert_eq!(
+        reference(
+            Path::new("note.md"),
+            Path::new("assets/my file.pdf"),
+            "my file.pdf",
+            false
+        ),
+        "[my file.pdf](<assets/my file.pdf>)"
+    );
+}
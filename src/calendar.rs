@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    cli,
+    date::Date,
+    periodic,
+    style::{Styler, BOLD, DIM, GREEN, YELLOW},
+};
+
+/// The names of the months, for calendars' headers.
+static MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Parses a month, e.g. `2024-05`, or `5` for May of the given date's year, into its first day.
+pub fn parse_month(month: &str, today: Date) -> Result<Date> {
+    let (year, month_number) = match month.split_once('-') {
+        Some((year, month)) => (year.parse::<i64>().ok(), month.parse::<u32>().ok()),
+        None => (Some(today.year), month.parse::<u32>().ok()),
+    };
+    match (year, month_number) {
+        (Some(year), Some(month @ 1..=12)) => Ok(Date {
+            year,
+            month,
+            day: 1,
+        }),
+        _ => bail!(
+            "{} is not a month, e.g. 2024-05, or 5 for May this year",
+            month
+        ),
+    }
+}
+
+/// Returns the number of days in the month the given date is in.
+pub fn days_in_month(date: Date) -> u32 {
+    periodic::shift(cli::Period::Month, date, 1)
+        .add_days(-1)
+        .day
+}
+
+/// Parses the output of git log with `--pretty=format:%x00%at --name-only` into how many commits
+/// touched files the given function accepts on each local date, in the timezone the given offset
+/// (in seconds) from UTC.
+pub fn commit_counts(
+    log: &str,
+    utc_offset: i64,
+    accept: impl Fn(&str) -> bool,
+) -> BTreeMap<Date, usize> {
+    let mut counts = BTreeMap::new();
+    let mut commit: Option<(Date, bool)> = None;
+    for line in log.lines().chain(["\0"]) {
+        if let Some(timestamp) = line.strip_prefix('\0') {
+            if let Some((date, true)) = commit {
+                *counts.entry(date).or_insert(0) += 1;
+            }
+            commit = timestamp.trim().parse::<i64>().ok().map(|timestamp| {
+                let date = Date::from_days((timestamp + utc_offset).div_euclid(86_400));
+                (date, false)
+            });
+            continue;
+        }
+        if let Some((_, accepted)) = commit.as_mut().filter(|_| !line.is_empty()) {
+            *accepted |= accept(line);
+        }
+    }
+    counts
+}
+
+/// Draws the month the given date is in like cal does, with weeks starting on Mondays. Days with a
+/// journal entry are marked with `*` (or, in color, green), others on which notes were edited
+/// with `+` (or yellow), and today is in bold.
+pub fn render(
+    month: Date,
+    journaled: &BTreeSet<u32>,
+    edited: &BTreeSet<u32>,
+    today: Option<u32>,
+    styler: Styler,
+) -> String {
+    let header = format!("{} {}", MONTH_NAMES[month.month as usize - 1], month.year);
+    let mut rendered = format!(
+        "{}\n Mo  Tu  We  Th  Fr  Sa  Su\n",
+        format!("{:^28}", header).trim_end()
+    );
+
+    let first = Date { day: 1, ..month };
+    let mut line = "    ".repeat(first.weekday() as usize - 1);
+    for day in 1..=days_in_month(month) {
+        let (marker, codes) = match (journaled.contains(&day), edited.contains(&day)) {
+            (true, _) => ("*", vec![GREEN]),
+            (false, true) => ("+", vec![YELLOW]),
+            (false, false) => (" ", vec![]),
+        };
+        let mut codes = codes;
+        if today == Some(day) {
+            codes.push(BOLD);
+        }
+        let number = format!("{:>2}", day);
+        let cell = match (styler.is_enabled(), codes.is_empty()) {
+            (true, false) => format!(" {} ", styler.paint(&number, &codes)),
+            _ => format!(" {}{}", number, marker),
+        };
+        line.push_str(&cell);
+        if (first.weekday() - 1 + i64::from(day)) % 7 == 0 {
+            rendered.push_str(line.trim_end());
+            rendered.push('\n');
+            line.clear();
+        }
+    }
+    if !line.is_empty() {
+        rendered.push_str(line.trim_end());
+        rendered.push('\n');
+    }
+
+    let legend = match styler.is_enabled() {
+        true => format!(
+            "{} journal entry  {} notes edited",
+            styler.paint("■", &[GREEN]),
+            styler.paint("■", &[YELLOW])
+        ),
+        false => "* journal entry  + notes edited".to_string(),
+    };
+    rendered.push_str(&format!("\n{}\n", legend));
+    rendered
+}
+
+/// The shades of heatmap cells, from days without any activity to the busiest days.
+static SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// Draws a GitHub-style heatmap of the given counts per day, over the given number of weeks up to
+/// and including the given date: a column per week, with a row per day of the week, from Monday
+/// to Sunday. Each day is shaded by how its count compares to the busiest day's.
+pub fn heatmap(end: Date, weeks: usize, counts: &BTreeMap<Date, usize>, styler: Styler) -> String {
+    let start = periodic::start(cli::Period::Week, end).add_days(-7 * (weeks as i64 - 1));
+    let max = counts.range(start..=end).map(|(_, count)| *count).max();
+
+    // Months are labelled above the week their first day is in, as long as there is room.
+    let mut labels = String::from("    ");
+    for week in 0..weeks {
+        let monday = start.add_days(7 * week as i64);
+        let first_of_month = (0..7)
+            .map(|day| monday.add_days(day))
+            .find(|date| date.day == 1 && *date <= end);
+        let column = 4 + week * 2;
+        if let Some(first) = first_of_month.filter(|_| labels.chars().count() <= column) {
+            labels.push_str(&" ".repeat(column - labels.chars().count()));
+            labels.push_str(&MONTH_NAMES[first.month as usize - 1][..3]);
+            labels.push(' ');
+        }
+    }
+    let mut rendered = format!("{}\n", labels.trim_end());
+
+    for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", ""].iter().enumerate() {
+        let mut line = format!("{:<4}", label);
+        for week in 0..weeks {
+            let date = start.add_days(7 * week as i64 + weekday as i64);
+            if date > end {
+                break;
+            }
+            let count = counts.get(&date).copied().unwrap_or(0);
+            let shade = match max {
+                Some(max) if count > 0 => (count * 4).div_ceil(max).clamp(1, 4),
+                _ => 0,
+            };
+            let cell = match shade {
+                0 => styler.paint(SHADES[0], &[DIM]),
+                _ => styler.paint(SHADES[shade], &[GREEN]),
+            };
+            line.push_str(&cell);
+            line.push(' ');
+        }
+        rendered.push_str(line.trim_end());
+        rendered.push('\n');
+    }
+
+    let legend = SHADES
+        .iter()
+        .enumerate()
+        .map(|(shade, cell)| match shade {
+            0 => styler.paint(cell, &[DIM]),
+            _ => styler.paint(cell, &[GREEN]),
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    rendered.push_str(&format!("\nLess {} More\n", legend));
+    rendered
+}
+
+#[test]
+fn render_calendar() {
+    let may = parse_month("2024-05", Date::from_days(0)).unwrap();
+    let rendered = render(
+        may,
+        &BTreeSet::from([1, 13]),
+        &BTreeSet::from([2, 13, 31]),
+        Some(31),
+        Styler::new(cli::ColorMode::Never, true),
+    );
+    assert_eq!(
+        rendered,
+        "          May 2024\n Mo  Tu  We  Th  Fr  Sa  Su\n          1*  2+  3   4   5\n\
+         \x20 6   7   8   9  10  11  12\n 13* 14  15  16  17  18  19\n\
+         \x2020  21  22  23  24  25  26\n 27  28  29  30  31+\n\n\
+         * journal entry  + notes edited\n"
+    );
+    assert_eq!(parse_month("2", may).unwrap(), Date { month: 2, ..may });
+    assert!(parse_month("13", may).is_err());
+
+    let log = "\x00300\na.md\nc.md\n\n\x00200\na.md\n\n\x0086400\nimage.png\n\x0090000\nb.md\n";
+    assert_eq!(
+        commit_counts(log, 0, |path| path.ends_with(".md")),
+        BTreeMap::from([(Date::from_days(0), 2), (Date::from_days(1), 1)])
+    );
+
+    let end = parse_month("2024-05", may).unwrap().add_days(7);
+    let counts = BTreeMap::from([
+        (end.add_days(-9), 1),
+        (end.add_days(-7), 4),
+        (end.add_days(-1), 2),
+    ]);
+    assert_eq!(
+        heatmap(end, 2, &counts, Styler::new(cli::ColorMode::Never, true)),
+        "    May\nMon ░ ·\n    · ▒\nWed █ ·\n    ·\nFri ·\n    ·\n    ·\n\nLess · ░ ▒ ▓ █ More\n"
+    );
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 /// Write notes.
 ///
@@ -64,9 +64,9 @@ pub struct Args {
     pub finder: String,
 
     /// Specifies a command invocation that, given a path (relative to base-dir) as a positional
-    /// argument, prints a listing to stdout.
+    /// argument, prints a listing to stdout. Without one, jot lists notes as a tree itself.
     #[clap(short, long, value_parser)]
-    pub lister: String,
+    pub lister: Option<String>,
 
     /// Editing should finish with a sync automatically. Default: true.
     #[clap(default_value_t = true, short, long, value_parser)]
@@ -87,31 +87,568 @@ pub struct Args {
     #[clap(default_value_t = true, short, long, value_parser)]
     pub quiet_on_ctrl_c: bool,
 
+    /// Kill the finder if it has not exited after this many seconds.
+    #[clap(long, value_parser)]
+    pub finder_timeout_secs: Option<u64>,
+
+    /// Kill the lister if it has not exited after this many seconds.
+    #[clap(long, value_parser)]
+    pub lister_timeout_secs: Option<u64>,
+
+    /// Kill git operations, e.g. a push hanging on the network, if they have not finished after
+    /// this many seconds. The native git backend instead gives up on remotes that stop responding
+    /// for this long. Commits are exempt, since they may wait on $EDITOR for a message.
+    #[clap(long, value_parser)]
+    pub git_timeout_secs: Option<u64>,
+
+    /// Do not report the progress of syncs, i.e. which step they are at and how long each took,
+    /// on stderr.
+    #[clap(long, value_parser)]
+    pub quiet: bool,
+
+    /// Do not page the output of list, cat, log and diff through $PAGER (or less, if it is not
+    /// set), even on a terminal.
+    #[clap(long, value_parser)]
+    pub no_pager: bool,
+
+    /// When to color output, e.g. listings, diffs, search matches and errors. Auto colors output
+    /// to terminals, unless $NO_COLOR is set.
+    #[clap(default_value_t = ColorMode::Auto, long, value_enum)]
+    pub color: ColorMode,
+
+    /// Specifies a command invocation to run before opening a note in $EDITOR. If it fails, the
+    /// note is not opened. Like all hooks, it runs from base-dir, with JOT_HOOK set to the hook's
+    /// name and JOT_BASE_DIR to base-dir. Edit hooks also get the path of the note in JOT_NOTE.
+    #[clap(long, value_parser)]
+    pub pre_edit_hook: Option<String>,
+
+    /// Specifies a command invocation to run after $EDITOR exits, e.g. a formatter. Like all post
+    /// hooks, it runs whether or not what it follows succeeded, with JOT_EXIT_STATUS set to 0 if
+    /// it did, and 1 otherwise.
+    #[clap(long, value_parser)]
+    pub post_edit_hook: Option<String>,
+
+    /// Specifies a command invocation to run before syncing. If it fails, nothing is synced.
+    #[clap(long, value_parser)]
+    pub pre_sync_hook: Option<String>,
+
+    /// Specifies a command invocation to run after syncing, e.g. a backup script or a
+    /// notification.
+    #[clap(long, value_parser)]
+    pub post_sync_hook: Option<String>,
+
     /// Specifies the name of the remote to push/pull to/from.
     #[clap(default_value = "origin", short = 'r', long, value_parser)]
     pub git_remote_name: String,
 
+    /// The name of another remote to push to after git-remote-name, e.g. a self-hosted mirror.
+    /// Nothing is ever pulled from it. A sync only fails to push if it cannot push to any remote
+    /// at all. May be specified multiple times.
+    #[clap(long, value_parser)]
+    pub git_mirror_remote: Vec<String>,
+
+    /// Commit to a branch of this device's own, devices/<DEVICE_BRANCH> (e.g. devices/laptop),
+    /// rather than to git-upstream-branch directly. Syncing merges upstream and every other
+    /// device's branch into it, pushes it, and then fast-forwards git-upstream-branch to it. Since
+    /// no other device pushes to it, its pushes are never rejected, even while other devices sync
+    /// at the same time. This requires the merge pull strategy.
+    #[clap(long, value_parser)]
+    pub device_branch: Option<String>,
+
     /// Specifies the name of the remote branch to push/pull to/from.
     #[clap(default_value = "main", short = 'u', long, value_parser)]
     pub git_upstream_branch: String,
 
+    /// The name to author (and commit) jot's commits as, instead of git's user.name, e.g. to tell
+    /// which device made each change, like `bob@laptop`. Commits that jot rewrites, e.g. when
+    /// compacting history, keep their original authors.
+    #[clap(long, value_parser)]
+    pub git_author_name: Option<String>,
+
+    /// The email to author (and commit) jot's commits as, instead of git's user.email.
+    #[clap(long, value_parser)]
+    pub git_author_email: Option<String>,
+
     /// Prompt for a custom git commit message when syncing. This will default to whatever behavior
     /// your git config suggests for a bare `git commit`.
     #[clap(default_value_t = false, short = 'm', long, value_parser)]
     pub git_custom_commit_msg: bool,
+
+    /// The directory, relative to base-dir, that the templates notes can be created from are
+    /// kept in, e.g. templates/meeting.md for jot new --template meeting.
+    #[clap(default_value = "templates", long, value_parser)]
+    pub templates_dir: std::path::PathBuf,
+
+    /// Specifies a command invocation that prints the text on the system clipboard, for jot clip.
+    /// Defaults to pbpaste on macOS, wl-paste on Wayland and xclip elsewhere.
+    #[clap(long, value_parser)]
+    pub clipboard_cmd: Option<String>,
+
+    /// Specifies a command invocation that prints the image on the system clipboard as a PNG, for
+    /// jot paste-image. Defaults to pngpaste on macOS, wl-paste on Wayland and xclip elsewhere.
+    #[clap(long, value_parser)]
+    pub clipboard_image_cmd: Option<String>,
+
+    /// The directory, relative to base-dir, that images and other attachments are stored in.
+    #[clap(default_value = "assets", long, value_parser)]
+    pub assets_dir: std::path::PathBuf,
+
+    /// The path, relative to base-dir, of the note jot quick and jot clip append to. It is created
+    /// if it does not exist yet.
+    #[clap(default_value = "inbox.md", long, value_parser)]
+    pub inbox: std::path::PathBuf,
+
+    /// The path, relative to base-dir, of the daily notes jot today and co. open. `%Y`, `%m` and
+    /// `%d` are replaced with the year, month and day of the note's date.
+    #[clap(default_value = "journal/%Y/%m/%d.md", long, value_parser)]
+    pub daily_path: String,
+
+    /// The name of the template to create daily notes from. Without this, they are created like
+    /// jot new creates notes without one.
+    #[clap(long, value_parser)]
+    pub daily_template: Option<String>,
+
+    /// The path, relative to base-dir, of the weekly notes jot week opens. `%G` and `%V` are
+    /// replaced with the ISO year and week of the note, and `%Y`, `%m` and `%d` with the date of
+    /// its Monday.
+    #[clap(default_value = "journal/%G/W%V.md", long, value_parser)]
+    pub weekly_path: String,
+
+    /// The name of the template to create weekly notes from, whose `{{date}}` is the date of
+    /// their Monday.
+    #[clap(long, value_parser)]
+    pub weekly_template: Option<String>,
+
+    /// The path, relative to base-dir, of the monthly notes jot month opens. `%Y` and `%m` are
+    /// replaced with the year and month of the note.
+    #[clap(default_value = "journal/%Y/%m.md", long, value_parser)]
+    pub monthly_path: String,
+
+    /// The name of the template to create monthly notes from, whose `{{date}}` is the first of
+    /// their month.
+    #[clap(long, value_parser)]
+    pub monthly_template: Option<String>,
+
+    /// Copy the unfinished tasks, i.e. `- [ ]` items, of the previous period's note into new
+    /// daily, weekly and monthly notes, e.g. last week's into this week's.
+    #[clap(long, value_parser)]
+    pub carry_over_tasks: bool,
+
+    /// Do not create the missing directories of the notes jot creates, e.g. projects/acme/ for jot
+    /// new projects/acme/kickoff.md, and fail instead.
+    #[clap(long, value_parser)]
+    pub no_create_dirs: bool,
+
+    /// The separator between the words of the file names jot derives from notes' titles, e.g. for
+    /// jot new --title.
+    #[clap(default_value = "-", long, value_parser)]
+    pub slug_separator: String,
+
+    /// Keep the case of titles in the file names jot derives from them, rather than lowercasing
+    /// them.
+    #[clap(long, value_parser)]
+    pub slug_keep_case: bool,
+
+    /// Keep the punctuation of titles in the file names jot derives from them, except for what is
+    /// unsafe in file names, e.g. `:`.
+    #[clap(long, value_parser)]
+    pub slug_keep_punctuation: bool,
+
+    /// Prepend YAML frontmatter with when it was created, its title (derived from its file name)
+    /// and the default-tags to the notes jot creates. Fields the note's template already sets are
+    /// left as they are.
+    #[clap(long, value_parser)]
+    pub frontmatter: bool,
+
+    /// A tag for the frontmatter of the notes jot creates with the frontmatter flag. May be
+    /// specified multiple times.
+    #[clap(long, value_parser)]
+    pub default_tag: Vec<String>,
+
+    /// The template for the messages of the commits jot creates when syncing, unless a message is
+    /// given. Placeholders are `{timestamp}` (RFC3339), `{hostname}`, `{files_changed}` (the
+    /// number of changed files), `{first_changed_note}` and `{summary}`, which summarizes the
+    /// changes, e.g. `edit projects/foo.md (+42 -3); add images/diagram.png`.
+    #[clap(default_value = "{timestamp}", long, value_parser)]
+    pub commit_template: String,
+
+    /// Sign the commits jot creates when syncing, including merges, as `git commit -S` would. This
+    /// honors the signing setup in your git config, e.g. user.signingkey and gpg.format (for
+    /// GPG or SSH signatures). The native git backend defers signed commits to the git binary.
+    #[clap(long, value_parser)]
+    pub sign_commits: bool,
+
+    /// The key to sign commits with, overriding user.signingkey. Implies sign-commits.
+    #[clap(long, value_parser)]
+    pub signing_key: Option<String>,
+
+    /// Skip the git hooks of base-dir when syncing, as git commit --no-verify and git push
+    /// --no-verify do, e.g. when a pre-commit hook is broken.
+    #[clap(long, value_parser)]
+    pub no_verify: bool,
+
+    /// Specifies how git operations are performed when syncing. `native` performs them in-process
+    /// via libgit2, and is only available if jot was built with the native-git feature. It
+    /// authenticates with SSH remotes via ssh-agent or the default keys in ~/.ssh, and with HTTPS
+    /// remotes via the access token in $JOT_GIT_TOKEN or git's credential helpers.
+    /// `subprocess` executes the git binary. `auto` uses `native` if it is available, and falls
+    /// back to `subprocess` otherwise.
+    #[clap(default_value_t = GitBackend::Auto, long, value_enum)]
+    pub git_backend: GitBackend,
+
+    /// Specifies what base-dir is synced with. `git` commits changes and pulls and pushes them
+    /// from and to git-remote-name. `rsync` mirrors base-dir to and from sync-target with rsync,
+    /// which must then be installed, without keeping any history: whichever copy of a file was
+    /// modified last wins, and deletions are not synced. `s3` and `webdav` sync the same way with
+    /// S3-compatible or WebDAV storage at sync-target via curl, which must then be installed. s3
+    /// authenticates with $AWS_ACCESS_KEY_ID, $AWS_SECRET_ACCESS_KEY and $AWS_REGION, and webdav
+    /// with $JOT_SYNC_USER and $JOT_SYNC_PASSWORD or ~/.netrc. `syncthing` leaves syncing to
+    /// Syncthing, and only merges the conflict copies (`*.sync-conflict-*`) it keeps of notes
+    /// edited on two devices at once, leaving those that conflict for jot conflicts. base-dir need
+    /// not be a git repository with backends other than git, though commands that work with
+    /// history, like log or undo, need it to be.
+    #[clap(default_value_t = SyncBackend::Git, long, value_enum)]
+    pub sync_backend: SyncBackend,
+
+    /// Where sync backends other than git sync base-dir with, e.g. `user@host:notes/` for rsync or
+    /// `https://bucket.s3.amazonaws.com/notes/` for s3.
+    #[clap(long, value_parser)]
+    pub sync_target: Option<String>,
+
+    /// When to show a desktop notification of how a sync went, via notify-send on Linux or
+    /// osascript on macOS, e.g. `jot --notify failures sync &`. Defaults to failures for the syncs
+    /// of jot daemon and sync --watch, and to never for everything else.
+    #[clap(long, value_enum)]
+    pub notify: Option<Notify>,
+
+    /// A URL to POST a JSON payload to after each sync that changed anything, e.g. to rebuild a
+    /// static site from the vault. The payload lists the files that changed and the resulting
+    /// commit and its message, e.g. `{"base_dir": "...", "commit": "...", "message": "...",
+    /// "files": ["projects/foo.md"]}`. Only syncs with the git sync-backend post to it, via curl,
+    /// which must then be installed.
+    #[clap(long, value_parser)]
+    pub webhook_url: Option<String>,
+
+    /// A gitignore-style pattern of paths to never stage when syncing, e.g. `*.swp`. These are
+    /// applied after any patterns in the .jotignore file at the root of base-dir. May be specified
+    /// multiple times.
+    #[clap(long, value_parser)]
+    pub ignore: Vec<String>,
+
+    /// While a note is open in $EDITOR, commit it every this many minutes if it changed, so that a
+    /// crash never loses more than that much writing. Once the editor exits, these
+    /// work-in-progress commits are squashed into the commit of the sync that follows.
+    #[clap(long, value_parser)]
+    pub wip_commit_mins: Option<u64>,
+
+    /// How many seconds to wait for another jot process (e.g. a background sync) that is running
+    /// git operations in base-dir to finish, before giving up.
+    #[clap(default_value_t = 10, long, value_parser)]
+    pub lock_wait: u64,
+
+    /// How many times to attempt each pull and push when syncing, for as long as they fail
+    /// because a remote could not be reached, e.g. on flaky Wi-Fi. Attempts are spaced out
+    /// exponentially, starting at a second apart. Commits are never retried.
+    #[clap(default_value_t = 3, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub sync_attempts: u32,
+
+    /// When syncing a shallow clone whose history does not go back far enough to integrate
+    /// upstream changes, how many commits at a time to fetch more of it by.
+    #[clap(default_value_t = 100, long, value_parser)]
+    pub deepen_by: u32,
+
+    /// Store attachments larger than this many KiB with Git LFS, which must then be installed.
+    /// Whenever syncing would commit such a file, every file with its extension is tracked with
+    /// Git LFS from then on, via .gitattributes. Notes are never stored with Git LFS.
+    #[clap(long, value_parser)]
+    pub lfs_threshold_kib: Option<u64>,
+
+    /// Tag the vault once per this period while syncing, e.g. daily/2024-05-01 for days, and push
+    /// the tags along with the vault. The tag marks the vault as of the period's first sync. Diff
+    /// and restore can then refer to these snapshots.
+    #[clap(long, value_enum)]
+    pub snapshot_tags: Option<Period>,
+
+    /// Specifies how upstream changes are integrated when pulling. Regardless of strategy, any
+    /// uncommitted local changes are stashed before pulling, and restored afterwards.
+    #[clap(default_value_t = PullStrategy::Merge, long, value_enum)]
+    pub pull_strategy: PullStrategy,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Creates a new note at the specified path and opens it in $EDITOR. If a file exists at the
     /// path already, this command behaves similarly to Edit if its dispatched program had returned
-    /// the given path.
-    New {
-        /// The path at which to create the new note. This path may be absolute, or, if relative,
-        /// must be relative to base-dir. This path, regardless of absoluteness, must reside
-        /// beneath base-dir.
+    /// the given path, unless unique is set.
+    New(NewArgs),
+    /// Appends a timestamped bullet with the given text to the inbox note and syncs, without
+    /// opening $EDITOR or dispatching to the finder, e.g. jot quick call dentist tomorrow.
+    Quick {
+        /// The text to capture. Multiple arguments are joined with spaces, so it need not be
+        /// quoted.
+        #[clap(value_parser, required = true)]
+        text: Vec<String>,
+
+        /// The commit message to use for the sync that follows capturing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Appends the text on the system clipboard (see clipboard-cmd) to the given note, a note
+    /// chosen with the finder, or else the inbox note, under a heading with the current time, and
+    /// syncs.
+    Clip {
+        /// The path of the note to append to. This path may be absolute, or, if relative, must be
+        /// relative to base-dir.
+        #[clap(value_parser, conflicts_with = "find")]
+        path: Option<std::path::PathBuf>,
+
+        /// Choose the note to append to with the finder.
+        #[clap(short, long, value_parser)]
+        find: bool,
+
+        /// The URL that the clipboard's text is from, which is noted along with it.
+        #[clap(short, long, value_parser)]
+        source: Option<String>,
+
+        /// The commit message to use for the sync that follows appending.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Saves the image on the system clipboard (see clipboard-image-cmd) in the assets-dir, named
+    /// after its hash, and embeds it at the end of the given note, e.g. for screenshots. Syncs
+    /// afterwards.
+    PasteImage {
+        /// The path of the note to embed the image in. This path may be absolute, or, if relative,
+        /// must be relative to base-dir.
+        #[clap(value_parser)]
+        path: std::path::PathBuf,
+
+        /// Only print the Markdown that embeds the image in the note, e.g. to paste it in $EDITOR,
+        /// rather than appending it to the note and syncing.
+        #[clap(short, long, value_parser)]
+        print: bool,
+
+        /// The commit message to use for the sync that follows embedding the image.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Copies the given file into the assets-dir, or the note's own assets directory, and links to
+    /// it at the end of the given note, or embeds it if it is an image. Syncs afterwards, so that
+    /// the attachment is never left uncommitted.
+    Attach {
+        /// The path of the file to attach.
+        #[clap(value_parser)]
+        file: std::path::PathBuf,
+
+        /// The path of the note to attach the file to. This path may be absolute, or, if relative,
+        /// must be relative to base-dir.
+        #[clap(value_parser)]
+        path: std::path::PathBuf,
+
+        /// Copy the file into the note's own assets directory, i.e. one named after the note
+        /// next to it, e.g. ideas/app.assets/ for ideas/app.md, rather than the assets-dir.
+        #[clap(long, value_parser)]
+        per_note: bool,
+
+        /// Only print the Markdown that links to the attachment, e.g. to paste it in $EDITOR,
+        /// rather than appending it to the note and syncing.
+        #[clap(short, long, value_parser)]
+        print: bool,
+
+        /// The commit message to use for the sync that follows attaching the file.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Fetches the web page at the given URL, and saves its readable content, i.e. its title,
+    /// byline and main content, as Markdown in a new note named after its title (see the slug
+    /// flags), with the URL in its frontmatter. Syncs afterwards. This requires curl to be
+    /// installed.
+    Web {
+        /// The URL of the page to capture.
+        #[clap(value_parser)]
+        url: String,
+
+        /// The directory to create the note in. This path may be absolute, or, if relative, must be
+        /// relative to base-dir. Defaults to base-dir.
+        #[clap(value_parser)]
+        path: Option<std::path::PathBuf>,
+
+        /// The commit message to use for the sync that follows capturing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Walks the inbox note's top-level list items and sections one by one, and asks whether to
+    /// move each to another note, chosen with the finder, to delete it, or to leave it in the
+    /// inbox. Syncs afterwards.
+    Refile {
+        /// The commit message to use for the sync that follows refiling.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Appends the given text, or stdin without it, to the given note and syncs, without opening
+    /// $EDITOR, e.g. for logging to a running note from scripts and cron jobs. The note is created
+    /// if it does not exist yet.
+    Append {
+        /// The path of the note to append to. This path may be absolute, or, if relative, must be
+        /// relative to base-dir.
         #[clap(value_parser)]
         path: std::path::PathBuf,
+
+        /// The text to append. Multiple arguments are joined with spaces. Without any, stdin is
+        /// appended instead.
+        #[clap(value_parser)]
+        text: Vec<String>,
+
+        /// Append under a heading with today's date, e.g. `## 2024-05-01`, which is added unless
+        /// it is the note's last heading already.
+        #[clap(short, long, value_parser)]
+        dated: bool,
+
+        /// The commit message to use for the sync that follows appending.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Copies the given note to a new one and opens the copy in $EDITOR, e.g. to start from last
+    /// week's meeting notes without setting up a template.
+    Cp {
+        /// The path of the note to copy. This path may be absolute, or, if relative, must be
+        /// relative to base-dir.
+        #[clap(value_parser)]
+        source: std::path::PathBuf,
+
+        /// The path of the copy, which must not exist yet. This path may be absolute, or, if
+        /// relative, must be relative to base-dir.
+        #[clap(value_parser)]
+        dest: std::path::PathBuf,
+
+        /// Strip the dates from the copy's frontmatter (created, date, updated and modified) and
+        /// set them to now instead, as if the copy had just been written.
+        #[clap(short, long, value_parser)]
+        fresh_dates: bool,
+
+        /// The commit message to use for the sync that follows editing the copy.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Merges the given notes into one, in which each is a section headed by its title, with its
+    /// own headings a level lower, and syncs. Links to the notes anywhere in the vault are
+    /// rewritten to point at their sections of the merged note, and the notes are deleted. The
+    /// merged note keeps the frontmatter of the first note, if any.
+    Merge {
+        /// The paths of the notes to merge, in the order to merge them in. These paths may be
+        /// absolute, or, if relative, must be relative to base-dir.
+        #[clap(value_parser, required = true, min_values = 2)]
+        notes: Vec<std::path::PathBuf>,
+
+        /// The path of the merged note, which must either not exist yet, or be one of the notes to
+        /// merge. This path may be absolute, or, if relative, must be relative to base-dir.
+        #[clap(long, value_parser)]
+        into: std::path::PathBuf,
+
+        /// The commit message to use for the sync that follows merging.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Splits the given note into one note per top-level section, in a directory named after the
+    /// note, and syncs. The note is left with what came before its first section and links to the
+    /// new notes, and links to its sections anywhere in the vault are rewritten to point at their
+    /// new notes.
+    Split {
+        /// The path of the note to split. This path may be absolute, or, if relative, must be
+        /// relative to base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+
+        /// The commit message to use for the sync that follows splitting.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Opens today's daily note (see daily-path) in $EDITOR, creating it from the daily-template
+    /// if it does not exist yet.
+    Today {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+
+        /// Afterwards, print the current and longest streaks of days with a daily note, like
+        /// streak.
+        #[clap(short, long, value_parser)]
+        streak: bool,
+    },
+    /// Opens yesterday's daily note in $EDITOR, like today.
+    Yesterday {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Opens tomorrow's daily note in $EDITOR, like today.
+    Tomorrow {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Opens this week's note (see weekly-path) in $EDITOR, creating it from the weekly-template
+    /// if it does not exist yet. Weeks are ISO weeks, i.e. start on Mondays.
+    Week {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Opens this month's note (see monthly-path) in $EDITOR, creating it from the
+    /// monthly-template if it does not exist yet.
+    Month {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Print a calendar of a month, with the days that have a daily note (see daily-path), and
+    /// those on which any notes were synced, according to git, highlighted.
+    Cal {
+        /// The month to print, e.g. `2024-05`, or `5` for May this year. Defaults to this month.
+        #[clap(value_parser)]
+        month: Option<String>,
+
+        /// Afterwards, prompt for a day of the month, and open its daily note in $EDITOR,
+        /// creating it from the daily-template if it does not exist yet, like today.
+        #[clap(short, long, value_parser)]
+        interactive: bool,
+
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Print the current and longest streaks of consecutive days with a daily note (see
+    /// daily-path). Daily notes that have since been deleted or moved count too, as long as git
+    /// remembers them. A streak that ended yesterday is still current, since today's note may be
+    /// yet to come.
+    Streak,
+    /// Print statistics about the notes in base-dir: how many there are, how many words they
+    /// have, in total and per top-level directory, how many attachments there are and how much
+    /// space they take up, on how many days notes were edited, how many notes were created each
+    /// month, and which notes are the largest and the oldest. Dates are according to git.
+    Stats {
+        /// Also print a GitHub-style heatmap of the days on which notes were edited, with a column
+        /// per week and a row per day of the week, shaded by how many syncs touched notes.
+        #[clap(long, value_parser)]
+        heatmap: bool,
+
+        /// Print the statistics as a JSON object instead, with sizes in bytes.
+        #[clap(long, value_parser, conflicts_with = "heatmap")]
+        json: bool,
+
+        /// The number of weeks, up to and including this one, to count edits in, and to draw in
+        /// the heatmap.
+        #[clap(default_value_t = 53, short, long, value_parser = clap::value_parser!(u64).range(1..))]
+        weeks: u64,
+    },
+    /// Print what jot knows about a note: how many words it has and roughly how long it takes to
+    /// read, when it was created and last modified according to git, its tags, how many notes
+    /// link to it and it links to, and the attachments it references.
+    Info {
+        /// The note to describe. This path may be absolute, or, if relative, must be relative to
+        /// base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
     },
     /// Dispatch to a program that outputs a filepath to open in $EDITOR. Edit mode need not be
     /// explicitly called. Calling jot without any subcommand defaults to edit mode. Note that the
@@ -119,24 +656,727 @@ pub enum Command {
     /// $EDITOR will be called nevertheless on the path. Most editors will open a blank page, and
     /// then create the file on save. This makes Edit roughly equivalent to New, the primary
     /// difference being that New creates the file prior to opening it in $EDITOR.
-    Edit,
-    /// Dispatch to a program (e.g. tree) that outputs a listing of all notes.
-    List {
-        /// The path representing the subtree from which to begin the listing. This is optional and
-        /// if omitted, runs the invocation from base-dir. This path may be absolute, or, if relative,
-        /// must be relative to base-dir. This path, regardless of absoluteness, must reside
-        /// beneath base-dir. Note that this is effectively setting the working directory for the
-        /// invocation, it does not get passed to the invocation.
+    Edit {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Pin a note, so that it comes first in listings (see list) and the terminal UI. Pins are
+    /// kept in a .jotpins file at the root of base-dir, so they are synced along with the notes.
+    Pin {
+        /// The note to pin. This path may be absolute, or, if relative, must be relative to
+        /// base-dir.
         #[clap(value_parser)]
-        subpath: Option<std::path::PathBuf>,
+        note: std::path::PathBuf,
+
+        /// The commit message to use for the sync that follows pinning.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Unpin a note pinned with pin.
+    Unpin {
+        /// The note to unpin. This path may be absolute, or, if relative, must be relative to
+        /// base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+
+        /// The commit message to use for the sync that follows unpinning.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
     },
+    /// Reopen the note that jot last opened in $EDITOR, whichever command opened it, without
+    /// going through the finder. Which note that was is kept in the .git directory, so it is not
+    /// synced.
+    Last {
+        /// The commit message to use for the sync that follows editing.
+        #[clap(short, long, value_parser)]
+        message: Option<String>,
+    },
+    /// Dispatch to a program (e.g. tree) that outputs a listing of all notes, i.e. the lister.
+    /// Without one, or with depth, dirs-only or all, jot draws the tree itself, directories
+    /// first, with each note's title next to its name, below the pinned notes (see pin). With
+    /// json, sorting or filters, jot lists the notes itself, pinned notes first, along with what
+    /// it knows about them.
+    List(ListArgs),
     /// 'Synchronize' the notes. This is really just an attempt to git pull, git add -A, git
     /// commit, then finally, git push. If an error (namely a merge conflict) occurs, an error is
     /// propagated to stderr. If you want to be prompted for a custom commit message, specify the
     /// git-custom-commit-msg flag, otherwise, jot will set the message to the current local system
-    /// time in RFC3339 format.
+    /// time in RFC3339 format, unless a message is given via the message flag. Files matching a
+    /// pattern in base-dir's .jotignore file, or given via the ignore flag, are never staged.
     #[clap(name = "sync")]
-    Synch,
+    Synch(SyncArgs),
+    /// Print every link to the given note from the other notes in base-dir. Both Markdown links
+    /// and wiki-links are considered. Each link is printed as `path:line: text`, similar to grep.
+    Backlinks {
+        /// The note to find backlinks to. This path may be absolute, or, if relative, must be
+        /// relative to base-dir. This path, regardless of absoluteness, must reside beneath
+        /// base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+
+        /// Print this many lines of context around each link.
+        #[clap(default_value_t = 0, short = 'C', long, value_parser)]
+        context: usize,
+    },
+    /// Print the graph of links between the notes in base-dir, e.g. for visualization with
+    /// Graphviz. Only links from one note to another existing note are included.
+    Graph {
+        /// The format to print the graph in.
+        #[clap(default_value_t = GraphFormat::Dot, short, long, value_enum)]
+        format: GraphFormat,
+    },
+    /// Print the notes that do not link to any other note and are not linked to from any other
+    /// note.
+    Orphans {
+        /// Do not report orphans beneath this directory, e.g. for a journal that is intentionally
+        /// unlinked. This path may be absolute, or, if relative, must be relative to base-dir.
+        /// May be specified multiple times.
+        #[clap(short = 'x', long, value_parser)]
+        exclude: Vec<std::path::PathBuf>,
+
+        /// Terminate each path with a NUL instead of a newline, e.g. for xargs -0 or fzf
+        /// --read0.
+        #[clap(short = '0', long, value_parser)]
+        print0: bool,
+    },
+    /// Check that the links and image references in notes point at files that exist beneath
+    /// base-dir. Each broken link is printed as `path:line: target`, and jot exits unsuccessfully
+    /// if any are found.
+    CheckLinks {
+        /// Also check external http(s) links, by sending a HEAD request to each of them. This
+        /// requires curl to be installed.
+        #[clap(short, long, value_parser)]
+        external: bool,
+
+        /// The maximum number of external links to check concurrently.
+        #[clap(default_value_t = 8, short, long, value_parser)]
+        jobs: usize,
+
+        /// The number of seconds to wait for each external link to respond.
+        #[clap(default_value_t = 10, short, long, value_parser)]
+        timeout: u64,
+    },
+    /// Print the most recently changed notes, most recent first, one path (relative to base-dir)
+    /// per line. This is derived from the git history of base-dir, so it is suitable for feeding
+    /// into a finder invocation.
+    Recent {
+        /// The number of notes to print.
+        #[clap(default_value_t = 10, short = 'n', long, value_parser)]
+        count: usize,
+
+        /// Terminate each path with a NUL instead of a newline, e.g. for xargs -0 or fzf
+        /// --read0.
+        #[clap(short = '0', long, value_parser)]
+        print0: bool,
+    },
+    /// Print the history of a note, or of all of base-dir, most recent first. Each commit is
+    /// printed with its hash, which other commands accept as a revision, when it was made, and its
+    /// message.
+    Log {
+        /// The note whose history to print. This path may be absolute, or, if relative, must be
+        /// relative to base-dir. If omitted, prints the history of all of base-dir.
+        #[clap(value_parser)]
+        note: Option<std::path::PathBuf>,
+
+        /// The number of commits to print.
+        #[clap(default_value_t = 20, short = 'n', long, value_parser)]
+        count: usize,
+    },
+    /// Show the changes to a note, or to all of base-dir, since the last sync, word by word. This
+    /// includes changes that are not committed yet.
+    Diff {
+        /// The note whose changes to show. This path may be absolute, or, if relative, must be
+        /// relative to base-dir. If omitted, shows the changes to all of base-dir.
+        #[clap(value_parser)]
+        note: Option<std::path::PathBuf>,
+
+        /// Show the changes since the given revision (e.g. a hash printed by jot log), or since
+        /// the given date, in any format git understands (e.g. `3 days ago` or `2024-01-31`),
+        /// instead of since the last sync.
+        #[clap(short, long, value_parser)]
+        since: Option<String>,
+
+        /// Show the changes since the given snapshot (see the snapshot-tags flag), either by its
+        /// name, e.g. `daily/2024-05-01`, or as one of `daily`, `weekly` and `monthly` for the
+        /// latest such snapshot.
+        #[clap(long, value_parser, conflicts_with = "since")]
+        since_snapshot: Option<String>,
+    },
+    /// Restore a note to how it was at a previous revision or date, e.g. to recover accidentally
+    /// deleted paragraphs, or the note itself. The restored note is then synced.
+    Restore {
+        /// The note to restore. This path may be absolute, or, if relative, must be relative to
+        /// base-dir. The note need not exist anymore.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+
+        /// The revision (e.g. a hash printed by jot log) or date, in any format git understands
+        /// (e.g. `3 days ago` or `2024-01-31`), to restore the note from. For dates, this is the
+        /// note as it was in the last commit before then.
+        #[clap(short, long, value_parser, required_unless_present = "snapshot")]
+        at: Option<String>,
+
+        /// Restore the note from the given snapshot (see the snapshot-tags flag) instead, either by
+        /// its name, e.g. `daily/2024-05-01`, or as one of `daily`, `weekly` and `monthly` for the
+        /// latest such snapshot.
+        #[clap(long, value_parser, conflicts_with = "at")]
+        snapshot: Option<String>,
+
+        /// Instead of overwriting the note, write the restored version next to it, with a
+        /// `.restored` suffix (e.g. note.md.restored). This is not synced.
+        #[clap(short, long, value_parser)]
+        copy: bool,
+    },
+    /// Undo the last commit, e.g. after syncing something by accident, as long as jot made it. If
+    /// the commit was already pushed, to any remote or branch, it is reverted by a new commit,
+    /// which you are offered to push. Otherwise, it is dropped, and its changes can be recovered
+    /// with jot restore.
+    Undo,
+    /// Print each line of a note along with the commit that last changed it: its hash, when it was
+    /// made, and by whom.
+    Blame {
+        /// The note to blame. This path may be absolute, or, if relative, must be relative to
+        /// base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+    },
+    /// Print a note, e.g. for a quick read without opening an editor.
+    Cat {
+        /// The note to print. This path may be absolute, or, if relative, must be relative to
+        /// base-dir.
+        #[clap(value_parser)]
+        note: std::path::PathBuf,
+
+        /// Format the note for the terminal: style its headings, emphasis and lists, and highlight
+        /// the code in its code blocks, instead of printing it as is.
+        #[clap(short, long, value_parser)]
+        render: bool,
+    },
+    /// Browse notes in a terminal UI: directories and tags on the left, the notes in the selected
+    /// one in the middle, and a preview of the selected note on the right. Notes can be opened in
+    /// $EDITOR, created, renamed (which updates the links to them) and deleted from it, and each
+    /// of these is synced. This requires jot to be built with the tui feature.
+    Tui,
+    /// Create a new vault in base-dir (or the given path): a git repository on the branch named by
+    /// the git-upstream-branch flag, with a starter .gitignore and a .gitattributes that makes
+    /// notes use jot's merge driver, in an initial commit.
+    Init {
+        /// Where to create the vault, if not base-dir.
+        #[clap(value_parser)]
+        path: Option<std::path::PathBuf>,
+
+        /// The URL of the remote to sync with, which is added under the name given by the
+        /// git-remote-name flag. The initial commit is pushed to it, so it must exist already,
+        /// and be empty.
+        #[clap(long, value_parser)]
+        remote: Option<String>,
+
+        /// Encrypt everything in the vault but .gitattributes and .gitignore with git-crypt,
+        /// which must be installed. Other clones of the vault need its key, which git-crypt
+        /// export-key exports, to be unlocked. Syncing refuses to stage anything while the vault
+        /// is locked, so that nothing is ever committed unencrypted.
+        #[clap(long, value_parser)]
+        encrypted: bool,
+    },
+    /// Clone a vault into base-dir (or the given path), e.g. when setting jot up on a new machine.
+    /// The remote and its branch are named after the git-remote-name and git-upstream-branch
+    /// flags, and jot's merge drivers are registered if the vault uses them. For vaults with a lot
+    /// of history, the clone can be made shallow, blob-less or both. Syncing fetches whatever more
+    /// history it needs later on.
+    Clone {
+        /// The URL of the remote to clone, e.g. git@github.com:me/notes.git.
+        #[clap(value_parser)]
+        remote: String,
+
+        /// Where to clone the vault to, if not base-dir.
+        #[clap(value_parser)]
+        path: Option<std::path::PathBuf>,
+
+        /// Only clone the last commit, instead of the entire history. Same as depth 1.
+        #[clap(short, long, value_parser, conflicts_with = "depth")]
+        shallow: bool,
+
+        /// Only clone the last this many commits, instead of the entire history.
+        #[clap(short, long, value_parser)]
+        depth: Option<u32>,
+
+        /// Only clone the contents of files as of the commits that are checked out, and fetch the
+        /// rest whenever they are needed, e.g. to show the history of a note. This requires the
+        /// subprocess git backend.
+        #[clap(long, value_parser)]
+        blobless: bool,
+    },
+    /// Write the vault's entire history to a single git bundle file, e.g. to keep a copy offline
+    /// that does not depend on the remote. Restore it with jot backup restore.
+    #[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Backup {
+        #[clap(subcommand)]
+        action: Option<BackupAction>,
+
+        /// The bundle file to write, e.g. notes.bundle.
+        #[clap(value_parser, required = true)]
+        file: Option<std::path::PathBuf>,
+    },
+    /// Run git with the given arguments from base-dir, e.g. `jot git log --stat`. Its exit code
+    /// is passed through.
+    #[clap(
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        disable_help_flag = true
+    )]
+    Git {
+        /// The arguments to pass to git.
+        #[clap(value_parser, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Squash commits older than a number of days into one rollup commit per period (e.g. per
+    /// month), keeping recent history as it is, apart from merges becoming regular commits. This
+    /// rewrites history, so without either the force-push or branch flag, this only prints what
+    /// would be squashed. Either way, the original history is kept at refs/jot/before-compact.
+    Compact {
+        /// Keep commits from the last this many days as they are.
+        #[clap(default_value_t = 90, short, long, value_parser)]
+        keep_days: u64,
+
+        /// The period whose commits are squashed into a single rollup commit.
+        #[clap(default_value_t = Period::Month, short, long, value_enum)]
+        period: Period,
+
+        /// Replace the current branch with the compacted history, and force-push it. Every other
+        /// clone of base-dir must then be reset to the new history (or cloned anew) before its
+        /// next sync, or the next sync will bring the old history back.
+        #[clap(long, value_parser, conflicts_with = "branch")]
+        force_push: bool,
+
+        /// Create a new branch with the given name for the compacted history, leaving the current
+        /// one as it is.
+        #[clap(short, long, value_parser)]
+        branch: Option<String>,
+    },
+    /// Keep running, watching base-dir for changes and syncing them once they settle, as well as
+    /// periodically, so that upstream changes arrive without any local ones. While a daemon runs,
+    /// editing notes only commits them, leaving the pulling and pushing to the daemon, so that
+    /// editing never waits on the network. jot status shows how the daemon's last sync went.
+    Daemon {
+        #[clap(subcommand)]
+        action: Option<DaemonAction>,
+
+        /// How many seconds changes must be left alone for before they are synced.
+        #[clap(default_value_t = 5, short, long, value_parser)]
+        debounce_secs: u64,
+
+        /// How many minutes to sync after, even without local changes.
+        #[clap(default_value_t = 10, short, long, value_parser)]
+        interval_mins: u64,
+    },
+    /// Shrink base-dir's git repository, e.g. after attachments were replaced or deleted many
+    /// times over. This expires old reflog entries, prunes what is no longer referenced, and
+    /// repacks everything else, then reports how much space was reclaimed.
+    Gc {
+        /// Repack far more thoroughly, which takes much longer, but can reclaim more space.
+        #[clap(short, long, value_parser)]
+        aggressive: bool,
+    },
+    /// List the files with unresolved conflicts, e.g. after a sync failed because of a merge
+    /// conflict, and open each of them in $EDITOR. Once the conflicts are resolved, run this again
+    /// with the continue flag to finish the sync. With the syncthing sync-backend, this instead
+    /// merges Syncthing's conflict copies into the notes they are copies of, leaving any
+    /// conflicts between them in the notes.
+    Conflicts {
+        /// Only list the files with conflicts, without opening them.
+        #[clap(short, long, value_parser, conflicts_with = "interactive")]
+        list: bool,
+
+        /// With list, print just the paths of the files with conflicts, each terminated with a
+        /// NUL instead of a newline, e.g. for xargs -0 or fzf --read0.
+        #[clap(short = '0', long, value_parser, requires = "list")]
+        print0: bool,
+
+        /// Instead of opening each file in $EDITOR, show the local and remote sides of its
+        /// conflicts next to each other, and choose between keeping either of them or editing the
+        /// file by hand.
+        #[clap(short, long, value_parser)]
+        interactive: bool,
+
+        /// Finish the sync that was interrupted by conflicts, i.e. conclude the merge (or rebase),
+        /// commit any other local changes and push. This fails if conflict markers remain.
+        #[clap(short = 'c', long = "continue", value_parser)]
+        finish: bool,
+    },
+    /// Merge three versions of a Markdown note one paragraph or list item at a time, rather than
+    /// line by line. This is meant to be used as a git merge driver (see jot doctor), which is
+    /// only honored by the subprocess git backend, and writes the result to the ours file. Exits
+    /// unsuccessfully if conflicts remain.
+    MergeFile {
+        /// The common ancestor's version of the note.
+        #[clap(value_parser)]
+        base: std::path::PathBuf,
+
+        /// Our version of the note, which is overwritten with the merge result.
+        #[clap(value_parser)]
+        ours: std::path::PathBuf,
+
+        /// Their version of the note.
+        #[clap(value_parser)]
+        theirs: std::path::PathBuf,
+
+        /// Keep both sides of conflicting changes instead of leaving conflict markers. This suits
+        /// append-only notes like journals, and is what the jot-union merge driver does, e.g. via
+        /// a `journal/** merge=jot-union` line in .gitattributes.
+        #[clap(short, long, value_parser)]
+        union: bool,
+    },
+    /// Check base-dir for the states that keep jot from syncing, e.g. a merge that was never
+    /// finished, a detached HEAD, leftover conflict markers or a stale index.lock, and suggest how
+    /// to fix each of them. jot exits unsuccessfully if any are found.
+    Fsck,
+    /// Check base-dir's setup for things jot can improve, and offer to fix them. Currently, this
+    /// checks that jot's Markdown-aware merge driver is registered, both in the git config and in
+    /// .gitattributes.
+    Doctor,
+    /// Show the state of base-dir: whether it is ahead of or behind the remote branch, whether a
+    /// sync was interrupted (e.g. by conflicts), and which files have local changes.
+    Status {
+        /// Fetch from the remote first, so that ahead/behind is up to date rather than as of the
+        /// last sync.
+        #[clap(long, value_parser)]
+        fetch: bool,
+    },
+    /// Open a random note in $EDITOR, e.g. for resurfacing old notes.
+    Random {
+        /// The subtree from which to pick a note. This is optional and if omitted, picks from all
+        /// of base-dir. This path may be absolute, or, if relative, must be relative to base-dir.
+        /// This path, regardless of absoluteness, must reside beneath base-dir.
+        #[clap(value_parser)]
+        subpath: Option<std::path::PathBuf>,
+
+        /// Only pick from notes with this tag, either in their frontmatter or inline as `#tag`.
+        #[clap(short, long, value_parser)]
+        tag: Option<String>,
+
+        /// Print the path of the picked note (relative to base-dir) instead of opening it.
+        #[clap(short, long, value_parser)]
+        print: bool,
+
+        /// Terminate the printed path with a NUL instead of a newline, e.g. for xargs -0.
+        #[clap(short = '0', long, value_parser, requires = "print")]
+        print0: bool,
+    },
+    /// Find notes with duplicate contents. Each pair of duplicates is printed as `a == b`, or, for
+    /// near-duplicates, as `a ~= b (similarity)`.
+    Dedupe {
+        /// Also find near-duplicates, i.e. pairs of notes that are at least this similar, where 1
+        /// means identical and 0 means nothing in common, e.g. 0.8.
+        #[clap(short, long, value_parser)]
+        fuzzy: Option<f64>,
+
+        /// For each pair of duplicates, prompt whether to delete one of the notes or merge one
+        /// into the other. If anything changed, this finishes with a sync.
+        #[clap(short, long, value_parser)]
+        interactive: bool,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct NewArgs {
+    /// The path at which to create the new note. This path may be absolute, or, if relative,
+    /// must be relative to base-dir. This path, regardless of absoluteness, must reside
+    /// beneath base-dir. With zettel, title or from-ics, this is the directory to create the
+    /// note in instead, which defaults to base-dir.
+    #[clap(value_parser, required_unless_present_any = ["zettel", "title", "from-ics"])]
+    pub path: Option<std::path::PathBuf>,
+
+    /// Create a note with the given title, named after it as the slug flags say, e.g.
+    /// `quarterly-planning-q3.md` for `Quarterly Planning: Q3`. Like with zettel, the path is
+    /// the directory to create the note in.
+    #[clap(long, value_parser, conflicts_with = "zettel")]
+    pub title: Option<String>,
+
+    /// Create a Zettelkasten note with the given title, named after a unique, timestamp-based
+    /// ID and the title's slug, e.g. `202405021130-some-title.md`. The ID is put in the note's
+    /// frontmatter too, and links to it, e.g. `[[202405021130]]`, resolve to the note.
+    #[clap(short, long, value_parser)]
+    pub zettel: Option<String>,
+
+    /// The commit message to use for the sync that follows editing.
+    #[clap(short, long, value_parser)]
+    pub message: Option<String>,
+
+    /// The name of the template to create the note from, e.g. `meeting` for
+    /// templates/meeting.md (see templates-dir). Notes that exist already are left as they
+    /// are. Without this, notes are created from the default template of the directory they
+    /// are in, if it or a directory above it names one in a .jot-template file, e.g.
+    /// meetings/.jot-template containing `meeting`. Templates may contain `{{date}}`,
+    /// `{{time}}`, `{{title}}` (derived from the note's file name) and `{{author}}` variables,
+    /// as well as `{{prompt:<question>}}` variables, e.g. `{{prompt:Project name}}`, whose
+    /// values jot asks for. A `{{cursor}}` marks where $EDITOR's cursor should start, for the
+    /// editors jot knows how to tell, e.g. vim, nano, emacs, helix and VS Code.
+    #[clap(short, long, value_parser)]
+    pub template: Option<String>,
+
+    /// If a file exists at the path already, create the note next to it instead, with a
+    /// numeric suffix after the slug-separator, e.g. `idea-2.md` for `idea.md`, rather than
+    /// opening the existing one.
+    #[clap(short, long, value_parser)]
+    pub unique: bool,
+
+    /// Write stdin to the new note and sync it, without opening it in $EDITOR, e.g. for
+    /// `some-command | jot new output.md --from-stdin`. With a template, stdin fills its
+    /// `{{stdin}}` variables, or is appended to it if there are none.
+    #[clap(long, value_parser)]
+    pub from_stdin: bool,
+
+    /// Only create the note, without opening it in $EDITOR or syncing, e.g. for scripts that
+    /// just need it to exist and be committed by the next sync. Its path is printed.
+    #[clap(long, value_parser)]
+    pub no_open: bool,
+
+    /// With no-open, terminate each printed path with a NUL instead of a newline, e.g. for xargs
+    /// -0 or fzf --read0.
+    #[clap(short = '0', long, value_parser, requires = "no-open")]
+    pub print0: bool,
+
+    /// Create a note for each event in the given iCalendar file, or stdin for `-`, e.g. a
+    /// calendar export, and sync them, without opening them in $EDITOR. Each is named after the
+    /// event's date and title, e.g. `2024-05-01-weekly-sync.md`, and created from the template,
+    /// whose `{{date}}`, `{{time}}` and `{{title}}` variables are the event's, and whose
+    /// `{{end}}`, `{{location}}` and `{{attendees}}` variables are filled in too. Notes that
+    /// exist already are left as they are.
+    #[clap(long, value_parser, conflicts_with_all = &["zettel", "title", "from-stdin"])]
+    pub from_ics: Option<std::path::PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ListArgs {
+    /// The path representing the subtree from which to begin the listing. This is optional and
+    /// if omitted, runs the invocation from base-dir. This path may be absolute, or, if relative,
+    /// must be relative to base-dir. This path, regardless of absoluteness, must reside
+    /// beneath base-dir. Note that this is effectively setting the working directory for the
+    /// invocation, it does not get passed to the invocation.
+    #[clap(value_parser)]
+    pub subpath: Option<std::path::PathBuf>,
+
+    /// Only descend this many directories deep, e.g. 1 for just the top-level ones. When jot lists
+    /// the notes itself, e.g. with json, this leaves out those more than this many directories
+    /// beneath subpath, e.g. with 1, those in its subdirectories.
+    #[clap(short = 'L', long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub depth: Option<u64>,
+
+    /// Only list directories, not the files in them, like tree -d.
+    #[clap(short, long, value_parser)]
+    pub dirs_only: bool,
+
+    /// Also list hidden files and directories, except for .git.
+    #[clap(short, long, value_parser)]
+    pub all: bool,
+
+    /// Print the notes as a JSON array of objects with their path (relative to base-dir),
+    /// title (from their frontmatter or first heading), size in bytes, number of words, tags,
+    /// whether they are pinned, and when they were created and last modified, as seconds since
+    /// the Unix epoch, according to git.
+    #[clap(long, value_parser, conflicts_with_all = &["dirs-only", "all"])]
+    pub json: bool,
+
+    /// Sort the notes by this, and print them (unless as JSON) relative to base-dir, one per line,
+    /// in a column next to their titles. Times are according to git.
+    #[clap(short, long, value_enum, conflicts_with_all = &["dirs-only", "all"])]
+    pub sort: Option<SortKey>,
+
+    /// Reverse the order the notes are sorted in.
+    #[clap(short, long, value_parser, requires = "sort")]
+    pub reverse: bool,
+
+    /// Only list the notes with this tag, in their frontmatter or inline. If given more than
+    /// once, only those with all of the tags are listed.
+    #[clap(short, long, value_parser, conflicts_with_all = &["dirs-only", "all"])]
+    pub tag: Vec<String>,
+
+    /// Only list the files with this extension, which may be any file's, e.g. pdf, not just a
+    /// note's. If given more than once, those with any of the extensions are listed.
+    #[clap(short, long, value_parser, conflicts_with_all = &["dirs-only", "all"])]
+    pub ext: Vec<String>,
+
+    /// Only list the notes modified within this long ago, according to git, e.g. 1w or 3days.
+    #[clap(
+        long,
+        value_parser = humantime::parse_duration,
+        conflicts_with_all = &["dirs-only", "all"]
+    )]
+    pub modified_since: Option<std::time::Duration>,
+
+    /// Leave out the notes' titles, and print just their paths, e.g. for feeding into a finder.
+    #[clap(long, value_parser)]
+    pub no_titles: bool,
+
+    /// Also print when each note was last committed, by whom, and with what message, in columns
+    /// after its title, or, in JSON, as a last_commit object with the commit's hash, timestamp,
+    /// author and message. Notes that were never committed have none.
+    #[clap(long, value_parser, conflicts_with_all = &["dirs-only", "all"])]
+    pub last_commit: bool,
+
+    /// Print just the notes' paths, relative to base-dir, each terminated with a NUL instead of a
+    /// newline, e.g. for xargs -0 or fzf --read0.
+    #[clap(
+        short = '0',
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "last-commit", "dirs-only", "all"]
+    )]
+    pub print0: bool,
+}
+
+impl ListArgs {
+    /// Returns whether jot lists the notes itself, rather than as a tree or with the lister.
+    pub fn is_flat(&self) -> bool {
+        self.json
+            || self.print0
+            || self.last_commit
+            || self.sort.is_some()
+            || !self.tag.is_empty()
+            || !self.ext.is_empty()
+            || self.modified_since.is_some()
+    }
+}
+
+#[derive(clap::Args, Debug, Default)]
+pub struct SyncArgs {
+    /// The commit message to use. This takes precedence over the git-custom-commit-msg flag.
+    #[clap(short, long, value_parser)]
+    pub message: Option<String>,
+
+    /// Print what a sync would do, i.e. the remote and branch it would pull from and push to, the
+    /// files it would stage and the commit message it would use, without doing any of it.
+    #[clap(short = 'n', long, value_parser)]
+    pub dry_run: bool,
+
+    /// Only pull upstream changes, without committing or pushing anything.
+    #[clap(long, value_parser, conflicts_with = "push")]
+    pub pull: bool,
+
+    /// Only push already committed changes, without pulling or committing anything.
+    #[clap(long, value_parser)]
+    pub push: bool,
+
+    /// Push the commits that earlier syncs could not, e.g. because there was no network, pulling
+    /// first so that they can be pushed. Nothing new is committed.
+    #[clap(long, value_parser, conflicts_with_all = &["pull", "push"])]
+    pub flush: bool,
+
+    /// Only commit local changes, without pulling or pushing anything. The next sync pushes them.
+    #[clap(long, value_parser, conflicts_with_all = &["pull", "push", "flush"])]
+    pub local: bool,
+
+    /// Keep running, and sync every this many minutes, as well as whenever base-dir changes, e.g.
+    /// inside tmux on a server. Ctrl-C stops it once the sync in progress, if any, is over. Unlike
+    /// jot daemon, this does not keep editing from syncing on its own.
+    #[clap(
+        short,
+        long,
+        value_name = "INTERVAL_MINS",
+        value_parser,
+        conflicts_with_all = &["message", "dry-run", "pull", "push", "flush", "local"]
+    )]
+    pub watch: Option<u64>,
+
+    /// Whether the sync runs in the background, i.e. for the daemon or sync --watch, where nobody
+    /// sees its output.
+    #[clap(skip)]
+    pub background: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PullStrategy {
+    /// Merge upstream changes, creating a merge commit if local and upstream have diverged.
+    Merge,
+    /// Rebase local commits on top of upstream changes.
+    Rebase,
+    /// Only fast-forward to upstream changes, and fail if local and upstream have diverged.
+    FfOnly,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Notify {
+    /// Never notify.
+    Never,
+    /// Only notify of syncs that failed.
+    Failures,
+    /// Notify of every sync.
+    Always,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SyncBackend {
+    Git,
+    Rsync,
+    S3,
+    Webdav,
+    Syncthing,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GitBackend {
+    Auto,
+    Native,
+    Subprocess,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DaemonAction {
+    /// Install the daemon as a service that runs whenever you are logged in, with the flags given
+    /// to this command, e.g. `jot -b ~/notes -f fzf -l ls daemon -i 5 install`. This writes a
+    /// systemd user unit on Linux, or a launchd agent on macOS, and starts it.
+    Install,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupAction {
+    /// Recreate a vault from a bundle written by jot backup. Like jot clone, jot's merge drivers
+    /// are registered if the vault uses them. The restored vault has no remote, add one with jot
+    /// git remote add to sync it.
+    Restore {
+        /// The bundle file to restore.
+        #[clap(value_parser)]
+        file: std::path::PathBuf,
+
+        /// Where to restore the vault to. This must not exist yet, or be empty.
+        #[clap(value_parser)]
+        path: std::path::PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SortKey {
+    /// When the notes were last modified, newest first.
+    Mtime,
+    /// When the notes were created, newest first.
+    Created,
+    /// The notes' titles, or their paths for those without one.
+    Title,
+    /// The notes' sizes, largest first.
+    Size,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GraphFormat {
+    /// Graphviz's DOT language.
+    Dot,
+    /// A JSON object with `nodes` and `edges` arrays.
+    Json,
 }
 
 // Proactively check for bad configurations.
@@ -0,0 +1,91 @@
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli,
+    cmd::{exec_cmd, get_env_var, SHELL_ENV_VARNAME},
+};
+
+/// Returns the command that prints the text on the system clipboard: the clipboard-cmd
+/// invocation if given, or else the platform's own, i.e. pbpaste on macOS, wl-paste on Wayland and
+/// xclip on X11.
+fn paste_cmd(args: &cli::Args) -> Result<Command> {
+    if let Some(invocation) = &args.clipboard_cmd {
+        let mut shell_exec = Command::new(get_env_var(SHELL_ENV_VARNAME)?);
+        shell_exec.arg(&args.shell_cmd_flag).arg(invocation);
+        return Ok(shell_exec);
+    }
+
+    Ok(if cfg!(target_os = "macos") {
+        Command::new("pbpaste")
+    } else if cfg!(target_os = "windows") {
+        let mut powershell = Command::new("powershell");
+        powershell.args(["-NoProfile", "-Command", "Get-Clipboard"]);
+        powershell
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut wl_paste = Command::new("wl-paste");
+        wl_paste.arg("--no-newline");
+        wl_paste
+    } else {
+        let mut xclip = Command::new("xclip");
+        xclip.args(["-selection", "clipboard", "-out"]);
+        xclip
+    })
+}
+
+/// Returns the command that prints the image on the system clipboard as a PNG: the
+/// clipboard-image-cmd invocation if given, or else the platform's own, i.e. pngpaste on macOS,
+/// wl-paste on Wayland and xclip on X11.
+fn paste_image_cmd(args: &cli::Args) -> Result<Command> {
+    if let Some(invocation) = &args.clipboard_image_cmd {
+        let mut shell_exec = Command::new(get_env_var(SHELL_ENV_VARNAME)?);
+        shell_exec.arg(&args.shell_cmd_flag).arg(invocation);
+        return Ok(shell_exec);
+    }
+
+    Ok(if cfg!(target_os = "macos") {
+        let mut pngpaste = Command::new("pngpaste");
+        pngpaste.arg("-");
+        pngpaste
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut wl_paste = Command::new("wl-paste");
+        wl_paste.args(["--type", "image/png"]);
+        wl_paste
+    } else {
+        let mut xclip = Command::new("xclip");
+        xclip.args(["-selection", "clipboard", "-target", "image/png", "-out"]);
+        xclip
+    })
+}
+
+/// Returns the image on the system clipboard, as a PNG.
+pub fn read_image(args: &cli::Args) -> Result<Vec<u8>> {
+    let mut paste_exec = paste_image_cmd(args)?;
+    // Images are binary, so they cannot go through exec_cmd, which reads stdout as text.
+    let output = paste_exec
+        .output()
+        .context("failed to execute the command that reads the clipboard's image")?;
+    if !output.status.success() || output.stdout.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.trim() {
+            "" => bail!("there is no image on the clipboard"),
+            stderr => bail!("there is no image on the clipboard: {}", stderr),
+        }
+    }
+    Ok(output.stdout)
+}
+
+/// Returns the text on the system clipboard.
+pub fn read(args: &cli::Args) -> Result<String> {
+    let (text, _) = exec_cmd(
+        "reading the clipboard",
+        paste_cmd(args)?,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    if text.trim().is_empty() {
+        bail!("there is no text on the clipboard");
+    }
+    Ok(text)
+}
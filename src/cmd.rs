@@ -1,21 +1,39 @@
 use std::{
     borrow::Cow,
+    collections::{hash_map::RandomState, BTreeSet, HashMap},
     env::var,
+    fs::File,
+    hash::{BuildHasher, Hasher},
+    io::{Read, Write},
+    os::{fd::AsRawFd, unix::process::CommandExt},
     path::Path,
-    process::{Command, Stdio},
-    time::SystemTime,
+    process::{Child, Command, Output, Stdio},
+    sync::{mpsc, Mutex},
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
-use humantime::format_rfc3339_seconds;
 
-use crate::cli;
+#[cfg(feature = "tui")]
+use crate::tui;
+use crate::{
+    assets, calendar, cli, clipboard, compact, conflict, crypt, daemon, date, dedupe, editor,
+    frontmatter,
+    git::{self, ChangeKind, Operation, GIT_CMD},
+    graph::Graph,
+    history, hooks, html, ics,
+    ignore::Ignore,
+    json, last, lfs, links, listing, lock, merge, notify, outline, output, periodic, pins, refile,
+    render, service, snapshot, stats, streak,
+    style::{self, Styler},
+    sync, syncthing, template, tree, undo, vault, webhook, wip, zettel,
+};
 
-static SHELL_ENV_VARNAME: &str = "SHELL";
+pub static SHELL_ENV_VARNAME: &str = "SHELL";
 
 static CTRL_C_EXIT_CODE: i32 = 130;
 
-fn get_env_var(varname: &str) -> Result<String> {
+pub fn get_env_var(varname: &str) -> Result<String> {
     var(varname).context(format!("failed to find ${} in environment", varname))
 }
 
@@ -32,11 +50,137 @@ fn format_output(output: &str) -> String {
         .join("\n")
 }
 
-fn exec_cmd(
+/// The error of a command that was killed for running longer than its timeout.
+#[derive(Debug)]
+pub struct Stalled(String);
+
+impl std::fmt::Display for Stalled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Stalled {}
+
+/// How often a command with a timeout is checked on.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The controlling terminal, while it is handed over to a command's process group. It is handed
+/// back to jot when dropped.
+struct Terminal(File);
+
+impl Terminal {
+    /// Hands the controlling terminal over to the given process group, so that the command in it
+    /// can prompt on it, e.g. fzf or ssh asking for a passphrase, rather than being stopped.
+    /// Returns None if jot has no controlling terminal, or is not in the foreground of it.
+    fn hand_over(pgid: libc::pid_t) -> Result<Option<Terminal>> {
+        let Ok(tty) = File::options().read(true).write(true).open("/dev/tty") else {
+            return Ok(None);
+        };
+        // This is sound, since the terminal is open for as long as it is used.
+        if unsafe { libc::tcgetpgrp(tty.as_raw_fd()) != libc::getpgrp() } {
+            return Ok(None);
+        }
+        let terminal = Terminal(tty);
+        terminal.set_foreground(pgid)?;
+        // The command may have tried to use the terminal before it was handed over, in which case
+        // it was stopped, and must be continued.
+        unsafe { libc::kill(-pgid, libc::SIGCONT) };
+        Ok(Some(terminal))
+    }
+
+    fn set_foreground(&self, pgid: libc::pid_t) -> std::io::Result<()> {
+        // A process that is not in the foreground is stopped for changing it, unless it ignores
+        // SIGTTOU, which jot is not once the terminal is handed over.
+        let result = unsafe {
+            let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+            let result = libc::tcsetpgrp(self.0.as_raw_fd(), pgid);
+            libc::signal(libc::SIGTTOU, previous);
+            result
+        };
+        match result {
+            -1 => Err(std::io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.set_foreground(unsafe { libc::getpgrp() });
+    }
+}
+
+/// Kills the given command, which leads its own process group, along with everything in the group,
+/// and waits for it to exit.
+fn kill_group(child: &mut Child) -> std::io::Result<()> {
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Waits for the given, already spawned, command to exit, and returns its output, or None if it
+/// did not exit within the given timeout, in which case it is killed, along with everything in
+/// its process group.
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Result<Option<Output>> {
+    // The pipes are drained as the command runs, so that it never blocks on writing to them. The
+    // threads draining them are left behind if the command times out, since whatever it started
+    // may have left its process group, and so keep the pipes open even after it is killed.
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Vec<u8>> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut output = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut output);
+            }
+            let _ = sender.send(output);
+        });
+        receiver
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // The command leads its own process group (see exec_cmd_with_timeout), so that this
+            // also kills whatever it started, e.g. the programs in a finder's pipeline, or the ssh
+            // under a git push.
+            kill_group(&mut child)?;
+            return Ok(None);
+        }
+        std::thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout.recv().unwrap_or_default(),
+        stderr: stderr.recv().unwrap_or_default(),
+    }))
+}
+
+pub fn exec_cmd(
+    label: &str,
+    cmd: Command,
+    captured_stderr: bool,
+    quiet_on_ctrl_c: bool,
+) -> Result<(String, Option<i32>)> {
+    exec_cmd_with_timeout(label, cmd, captured_stderr, quiet_on_ctrl_c, None)
+}
+
+/// Like exec_cmd, but kills the command if it runs for longer than the given timeout, if any, e.g.
+/// a push that hangs on the network. Its stdout is always captured. So are its stdin and stderr
+/// if captured_stderr is set, and they are inherited otherwise, e.g. for finders like fzf.
+pub fn exec_cmd_with_timeout(
     label: &str,
     mut cmd: Command,
     captured_stderr: bool,
     quiet_on_ctrl_c: bool,
+    timeout: Option<Duration>,
 ) -> Result<(String, Option<i32>)> {
     let program = cmd.get_program();
     let joined_args_str = cmd
@@ -45,9 +189,42 @@ fn exec_cmd(
         .collect::<Vec<Cow<'_, str>>>()
         .join(" ");
     let invocation = format!("{} {}", program.to_string_lossy(), joined_args_str);
-    let exec = cmd
-        .output()
-        .context(format!("failed to execute {}: `{}`", label, invocation,))?;
+    let exec = match timeout {
+        None => cmd
+            .output()
+            .context(format!("failed to execute {}: `{}`", label, invocation,))?,
+        Some(timeout) => {
+            let (stdin, stderr) = match captured_stderr {
+                true => (Stdio::null(), Stdio::piped()),
+                false => (Stdio::inherit(), Stdio::inherit()),
+            };
+            let mut child = cmd
+                .stdin(stdin)
+                .stdout(Stdio::piped())
+                .stderr(stderr)
+                .process_group(0)
+                .spawn()
+                .context(format!("failed to execute {}: `{}`", label, invocation,))?;
+            let terminal = match Terminal::hand_over(child.id() as libc::pid_t) {
+                Ok(terminal) => terminal,
+                Err(err) => {
+                    let _ = kill_group(&mut child);
+                    return Err(err.context(format!("failed to hand the terminal to {}", label)));
+                }
+            };
+            let output = wait_with_timeout(child, timeout);
+            drop(terminal);
+            match output.context(format!("failed to execute {}: `{}`", label, invocation,))? {
+                Some(output) => output,
+                None => bail!(Stalled(format!(
+                    "{} (`{}`) stalled, and was killed after {}s",
+                    label,
+                    invocation,
+                    timeout.as_secs()
+                ))),
+            }
+        }
+    };
 
     let stdout_output = std::str::from_utf8(exec.stdout.as_ref())?;
     let stderr_output = if captured_stderr {
@@ -83,12 +260,60 @@ fn exec_cmd(
     Ok((trimmed_stdout, exit_code))
 }
 
-fn open_editor_at_path(filepath: &std::path::Path, args: &cli::Args) -> Result<()> {
+/// Executes git with the given arguments, returning its (trimmed) stdout.
+fn exec_git(label: &str, git_args: &[&str], args: &cli::Args) -> Result<String> {
+    let mut git_exec = Command::new(GIT_CMD);
+    git_exec.args(git_args);
+    let (stdout, _) = exec_cmd_with_timeout(
+        label,
+        git_exec,
+        true,
+        args.quiet_on_ctrl_c,
+        args.git_timeout_secs.map(Duration::from_secs),
+    )?;
+    Ok(stdout)
+}
+
+/// Prints the given prompt and reads a single line of input from stdin, returning it trimmed.
+pub fn prompt(question: &str) -> Result<String> {
+    print!("{}", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    let read = std::io::stdin()
+        .read_line(&mut answer)
+        .context("failed to read answer from stdin")?;
+    if read == 0 {
+        // Stdin is closed, so nothing ended the line the prompt is on.
+        println!();
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// Asks the given question until it is given one of the given answers, and returns that answer,
+/// or None if it is given none at all, i.e. an empty one, or stdin is closed, e.g. because jot is
+/// run from a script.
+pub fn prompt_choice(question: &str, answers: &[&str]) -> Result<Option<String>> {
+    loop {
+        let answer = prompt(question)?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        if answers.contains(&answer.as_str()) {
+            return Ok(Some(answer));
+        }
+    }
+}
+
+fn open_editor(
+    filepath: &std::path::Path,
+    args: &cli::Args,
+    cursor: Option<editor::Cursor>,
+) -> Result<()> {
     static EDITOR_ENV_VARNAME: &str = "EDITOR";
     let editor = get_env_var(EDITOR_ENV_VARNAME)?;
-    let mut editor_exec = Command::new(editor);
+    let mut editor_exec = Command::new(&editor);
     editor_exec
-        .arg(filepath)
+        .args(editor::args(&editor, filepath, cursor))
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit());
     exec_cmd(
@@ -98,7 +323,59 @@ fn open_editor_at_path(filepath: &std::path::Path, args: &cli::Args) -> Result<(
         args.quiet_on_ctrl_c,
     )?;
 
-    sync(args)
+    Ok(())
+}
+
+fn open_editor_at_path(
+    filepath: &std::path::Path,
+    args: &cli::Args,
+    message: Option<&str>,
+) -> Result<()> {
+    open_editor_at_cursor(filepath, args, None, message)
+}
+
+/// Opens the given note in $EDITOR with its cursor at the given position, if any, and syncs the
+/// edits afterwards.
+fn open_editor_at_cursor(
+    filepath: &std::path::Path,
+    args: &cli::Args,
+    cursor: Option<editor::Cursor>,
+    message: Option<&str>,
+) -> Result<()> {
+    // Notes outside of base-dir cannot be reopened later, but are still fine to edit now.
+    if let Ok(note) = vault_relative_path(args, &filepath.to_path_buf()) {
+        last::record(&note)?;
+    }
+    hooks::around(
+        args,
+        (hooks::Hook::PreEdit, hooks::Hook::PostEdit),
+        Some(filepath),
+        || match args.wip_commit_mins {
+            Some(mins) => wip::commit_while(args, filepath, Duration::from_secs(mins * 60), || {
+                open_editor(filepath, args, cursor)
+            }),
+            None => open_editor(filepath, args, cursor),
+        },
+    )?;
+
+    sync_edits(args, message)
+}
+
+/// Syncs the edits just made, with the given commit message, if any.
+fn sync_edits(args: &cli::Args, message: Option<&str>) -> Result<()> {
+    // A running daemon pulls and pushes in the background, so there is no need to wait on it.
+    let daemon_running = lock::Lock::is_daemon_running()?;
+    if daemon_running {
+        println!("leaving pulling and pushing to the jot daemon");
+    }
+    sync(
+        args,
+        &cli::SyncArgs {
+            message: message.map(str::to_string),
+            local: daemon_running,
+            ..Default::default()
+        },
+    )
 }
 
 fn relative_path_to_absolute(
@@ -123,144 +400,3141 @@ fn relative_path_to_absolute(
     Ok(absolute_filepath)
 }
 
-pub fn new(args: &cli::Args, filepath: &std::path::PathBuf) -> Result<()> {
+fn vault_relative_path(
+    args: &cli::Args,
+    filepath: &std::path::PathBuf,
+) -> Result<std::path::PathBuf> {
     let absolute_filepath = relative_path_to_absolute(args, filepath)?;
+    // This unwrap is fine, relative_path_to_absolute() guarantees we are beneath base_dir.
+    let relative_filepath = absolute_filepath.strip_prefix(&args.base_dir).unwrap();
+    vault::normalize(relative_filepath).context(format!(
+        "given path must be below base_dir; {} is not",
+        filepath.display()
+    ))
+}
 
-    // First, create the given file:
-    if !absolute_filepath.exists() {
-        std::fs::File::create(absolute_filepath)
-            .context(format!("failed to create a file at {}", filepath.display()))?;
+fn read_note(args: &cli::Args, note: &Path) -> Result<String> {
+    std::fs::read_to_string(args.base_dir.join(note))
+        .context(format!("failed to read note at {}", note.display()))
+}
+
+/// Creates the note with the given title at the given path, which must not exist yet, from the
+/// given template if any, or else the default template of the directory it is in. Templates'
+/// `{{date}}` is the given date, and the given variables fill theirs. The `stdin` variable, if
+/// any, is the note's content without a template. The given frontmatter fields are always
+/// injected into the note. Returns where its template's `{{cursor}}` was, if anywhere.
+fn create_note(
+    args: &cli::Args,
+    filepath: &std::path::PathBuf,
+    title: &str,
+    template: Option<&str>,
+    date: date::Date,
+    mut fields: Vec<(&str, frontmatter::Value)>,
+    variables: &[(&str, &str)],
+) -> Result<Option<editor::Cursor>> {
+    let relative_filepath = vault_relative_path(args, filepath)?;
+    let template = match template {
+        Some(name) => Some(name.to_string()),
+        None => template::default_for(args, &relative_filepath)?,
+    };
+    let mut content = match &template {
+        Some(name) => template::instantiate(args, name, title, date, variables)?,
+        None => variables
+            .iter()
+            .find(|(name, _)| *name == "stdin")
+            .map(|(_, stdin)| stdin.to_string())
+            .unwrap_or_default(),
+    };
+    if args.frontmatter {
+        fields.extend([
+            (
+                "created",
+                frontmatter::Value::Scalar(format!(
+                    "{}T{}",
+                    date::Date::today()?,
+                    date::local_time()?
+                )),
+            ),
+            ("title", frontmatter::Value::Scalar(title.to_string())),
+            ("tags", frontmatter::Value::List(args.default_tag.clone())),
+        ]);
+    }
+    if !fields.is_empty() {
+        content = frontmatter::inject(&content, &fields);
     }
+    let (content, cursor) = template::take_cursor(&content);
+    write_note(args, filepath, &content)?;
+    Ok(cursor)
+}
 
-    // Then, open it in $EDITOR:
-    open_editor_at_path(filepath, args)?;
+/// Writes the given content to the note at the given path, creating the directories it is in
+/// unless no-create-dirs is set.
+fn write_note(args: &cli::Args, filepath: &std::path::PathBuf, content: &str) -> Result<()> {
+    let absolute_filepath = relative_path_to_absolute(args, filepath)?;
+    // This also makes sure that the path does not escape base-dir with `..`, before any
+    // directories are created for it.
+    vault_relative_path(args, filepath)?;
+    if let Some(parent) = absolute_filepath.parent().filter(|parent| !parent.exists()) {
+        if args.no_create_dirs {
+            bail!(
+                "{} does not exist, and no-create-dirs is set",
+                parent.display()
+            );
+        }
+        std::fs::create_dir_all(parent)
+            .context(format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(absolute_filepath, content)
+        .context(format!("failed to create a file at {}", filepath.display()))
+}
 
-    Ok(())
+/// Returns the given path if nothing exists at it yet, or else the first of it with a numeric
+/// suffix, e.g. `idea-2.md` for `idea.md`, that nothing exists at.
+fn unique_path(args: &cli::Args, filepath: &Path) -> Result<std::path::PathBuf> {
+    let stem = filepath.file_stem().unwrap_or_default().to_string_lossy();
+    let mut candidate = filepath.to_path_buf();
+    let mut suffix = 2;
+    while relative_path_to_absolute(args, &candidate)?.exists() {
+        let mut file_name = format!("{}{}{}", stem, args.slug_separator, suffix);
+        if let Some(extension) = filepath.extension() {
+            file_name.push('.');
+            file_name.push_str(&extension.to_string_lossy());
+        }
+        candidate.set_file_name(file_name);
+        suffix += 1;
+    }
+    Ok(candidate)
 }
 
-fn exec_custom_invocation_cmd(mut cmd: Command, args: &cli::Args) -> Result<(String, bool)> {
-    if !args.capture_std {
-        // Allow stderr/stdin to pass through for applications like fzf.
-        cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+/// A new note, as described by the arguments of jot new.
+struct NewNote {
+    filepath: std::path::PathBuf,
+    title: String,
+    /// The frontmatter fields the note needs, i.e. the IDs of Zettelkasten notes.
+    fields: Vec<(&'static str, frontmatter::Value)>,
+}
+
+/// Returns the new note the given arguments describe, i.e. the one at the given path, or one named
+/// after the given title in the given directory.
+fn new_note(args: &cli::Args, new_args: &cli::NewArgs) -> Result<NewNote> {
+    let (title, zettel) = match (&new_args.zettel, &new_args.title) {
+        (Some(title), _) => (title, true),
+        (None, Some(title)) => (title, false),
+        // The unwrap is fine, since clap requires the path without zettel or title.
+        (None, None) => {
+            let filepath = new_args.path.clone().unwrap();
+            return Ok(NewNote {
+                title: template::title(&filepath),
+                filepath,
+                fields: Vec::new(),
+            });
+        }
+    };
+
+    let slug = template::slug(args, title);
+    let mut fields = Vec::new();
+    let file_name = if zettel {
+        let id = zettel::new_id(&vault::notes(&args.base_dir)?)?;
+        fields.push(("id", frontmatter::Value::Scalar(id.clone())));
+        match slug.is_empty() {
+            true => format!("{}.md", id),
+            false => format!("{}{}{}.md", id, args.slug_separator, slug),
+        }
+    } else if slug.is_empty() {
+        bail!(
+            "there is nothing in the title {:?} to name a note after",
+            title
+        );
+    } else {
+        format!("{}.md", slug)
+    };
+    let dir = new_args.path.clone().unwrap_or_default();
+    Ok(NewNote {
+        filepath: dir.join(file_name),
+        title: title.clone(),
+        fields,
+    })
+}
+
+/// Creates a note for each event in the given iCalendar file, or stdin for `-`, in the directory
+/// new-args' path names, and syncs them.
+fn new_from_ics(args: &cli::Args, new_args: &cli::NewArgs, file: &Path) -> Result<()> {
+    let ics = match file.as_os_str() == "-" {
+        true => {
+            let mut stdin = String::new();
+            std::io::stdin()
+                .read_to_string(&mut stdin)
+                .context("failed to read stdin")?;
+            stdin
+        }
+        false => {
+            std::fs::read_to_string(file).context(format!("failed to read {}", file.display()))?
+        }
+    };
+    let events = ics::events(&ics, date::local_offset()?);
+    if events.is_empty() {
+        bail!("there are no events in {}", file.display());
     }
 
-    let (finder_stdout, exit_code) =
-        exec_cmd("finder", cmd, args.capture_std, args.quiet_on_ctrl_c)?;
+    let dir = new_args.path.clone().unwrap_or_default();
+    let mut created = 0;
+    for event in events {
+        let date = match &event.start {
+            Some(start) => start.date,
+            None => date::Date::today()?,
+        };
+        let title = match event.summary.trim() {
+            "" => "Meeting",
+            summary => summary,
+        };
+        let slug = template::slug(args, title);
+        let mut filepath = dir.join(match slug.is_empty() {
+            true => format!("{}.md", date),
+            false => format!("{}{}{}.md", date, args.slug_separator, slug),
+        });
+        if relative_path_to_absolute(args, &filepath)?.exists() {
+            if !new_args.unique {
+                println!(
+                    "warning: {} exists already, so it is left as it is",
+                    filepath.display()
+                );
+                continue;
+            }
+            filepath = unique_path(args, &filepath)?;
+        }
 
-    // If asked to be quiet on CTRL+C, then exec_cmd() will not have returned error. However, if
-    // so, we don't want to make use of whatever stdout may have returned, since the finder program
-    // was terminated prematurely (presumably). If so, return true as our boolean half of the
-    // tuple, to indicate an early return from the caller.
-    Ok((
-        finder_stdout,
-        args.quiet_on_ctrl_c && exit_code == Some(CTRL_C_EXIT_CODE),
-    ))
+        let time_of_day = |time: &Option<ics::Time>| {
+            time.as_ref()
+                .and_then(|time| time.time.clone())
+                .unwrap_or_default()
+        };
+        let (start, end) = (time_of_day(&event.start), time_of_day(&event.end));
+        let attendees = event.attendees.join(", ");
+        let location = event.location.clone().unwrap_or_default();
+        let mut variables = vec![
+            ("time", start.as_str()),
+            ("end", end.as_str()),
+            ("attendees", attendees.as_str()),
+            ("location", location.as_str()),
+        ];
+
+        // Without a template, the note lists the event's details under its title.
+        let template = match &new_args.template {
+            Some(name) => Some(name.clone()),
+            None => template::default_for(args, &vault_relative_path(args, &filepath)?)?,
+        };
+        let mut details = format!("# {}\n\n- Time: {}", title, date);
+        if !start.is_empty() {
+            details.push_str(&format!(" {}", start));
+        }
+        match &event.end {
+            Some(end_time) if end_time.date != date && !end.is_empty() => {
+                details.push_str(&format!(" – {} {}", end_time.date, end))
+            }
+            Some(_) if !end.is_empty() => details.push_str(&format!("–{}", end)),
+            _ => {}
+        }
+        details.push('\n');
+        if !location.is_empty() {
+            details.push_str(&format!("- Location: {}\n", location));
+        }
+        if !attendees.is_empty() {
+            details.push_str(&format!("- Attendees: {}\n", attendees));
+        }
+        if template.is_none() {
+            variables.push(("stdin", details.as_str()));
+        }
+
+        create_note(
+            args,
+            &filepath,
+            title,
+            template.as_deref(),
+            date,
+            Vec::new(),
+            &variables,
+        )?;
+        print!("{}", output::paths([&filepath], new_args.print0));
+        created += 1;
+    }
+
+    if created == 0 || new_args.no_open {
+        return Ok(());
+    }
+    sync_edits(args, new_args.message.as_deref())
 }
 
-pub fn edit(args: &cli::Args) -> Result<()> {
-    // First, we should execute the finder invocation and get a chosen filepath.
-    let shell = get_env_var(SHELL_ENV_VARNAME)?;
-    let mut finder_cmd = Command::new(shell);
-    finder_cmd.arg(&args.shell_cmd_flag).arg(&args.finder);
+pub fn new(args: &cli::Args, new_args: &cli::NewArgs) -> Result<()> {
+    if let Some(file) = &new_args.from_ics {
+        return new_from_ics(args, new_args, file);
+    }
+    let note = new_note(args, new_args)?;
+    let filepath = match new_args.unique {
+        true => unique_path(args, &note.filepath)?,
+        false => note.filepath,
+    };
+    let template = new_args.template.as_deref();
+    let exists = relative_path_to_absolute(args, &filepath)?.exists();
+    let stdin = match new_args.from_stdin {
+        true if exists => bail!(
+            "{} exists already, so there is nothing to write stdin to",
+            filepath.display()
+        ),
+        true => {
+            let mut stdin = String::new();
+            std::io::stdin()
+                .read_to_string(&mut stdin)
+                .context("failed to read stdin")?;
+            Some(stdin)
+        }
+        false => None,
+    };
 
-    if !args.capture_std {
-        // Allow stderr/stdin to pass through for applications like fzf.
-        finder_cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+    // First, create the given file if it does not exist yet:
+    let mut cursor = None;
+    if !exists {
+        cursor = create_note(
+            args,
+            &filepath,
+            &note.title,
+            template,
+            date::Date::today()?,
+            note.fields,
+            stdin.as_deref().map(|stdin| ("stdin", stdin)).as_slice(),
+        )?;
+    } else if template.is_some() {
+        println!(
+            "warning: {} exists already, so it is opened as it is, without the template",
+            filepath.display()
+        );
     }
 
-    let (finder_stdout, should_exit_early) = exec_custom_invocation_cmd(finder_cmd, args)?;
-    if should_exit_early {
+    if new_args.no_open {
+        print!("{}", output::paths([&filepath], new_args.print0));
         return Ok(());
     }
+    if stdin.is_some() {
+        return sync_edits(args, new_args.message.as_deref());
+    }
 
-    let filepath = Path::new(&finder_stdout);
+    // Then, open it in $EDITOR:
+    open_editor_at_cursor(&filepath, args, cursor, new_args.message.as_deref())
+}
 
-    // Then, open the editor at that path.
-    open_editor_at_path(filepath, args)?;
+/// Appends the given text to the given note, creating it if need be. With a heading, the text goes
+/// under it, and the heading is only added if it is not the note's last heading already, so that
+/// repeated appends end up under one heading.
+fn append_to_note(
+    args: &cli::Args,
+    filepath: &std::path::PathBuf,
+    heading: Option<&str>,
+    text: &str,
+) -> Result<()> {
+    if !relative_path_to_absolute(args, filepath)?.exists() {
+        create_note(
+            args,
+            filepath,
+            &template::title(filepath),
+            None,
+            date::Date::today()?,
+            Vec::new(),
+            &[],
+        )?;
+    }
 
-    Ok(())
+    let mut content = read_note(args, filepath)?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if let Some(heading) = heading {
+        let last_heading = content.lines().rev().find(|line| line.starts_with('#'));
+        if last_heading != Some(heading) {
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(heading);
+            content.push_str("\n\n");
+        }
+    }
+    content.push_str(text);
+    if !text.ends_with('\n') {
+        content.push('\n');
+    }
+    std::fs::write(relative_path_to_absolute(args, filepath)?, content)
+        .context(format!("failed to write {}", filepath.display()))
 }
 
-pub fn list(args: &cli::Args, subpath: Option<std::path::PathBuf>) -> Result<()> {
-    // First, change working directory into the given list_path.
-    // Note that this could possibly be a no-op if none was specified.
-    let listing_path = subpath.map_or(Ok(args.base_dir.clone()), |path| {
-        relative_path_to_absolute(args, &path)
-    })?;
-    std::env::set_current_dir(&listing_path).context(format!(
-        "failed to change jot's working directory to {} for listing",
-        listing_path.display(),
-    ))?;
+/// Appends a bullet with the given text and the current time to the inbox note, creating it if
+/// need be, and syncs.
+pub fn quick(args: &cli::Args, text: &str, message: Option<&str>) -> Result<()> {
+    let bullet = format!(
+        "- {} {} {}",
+        date::Date::today()?,
+        date::local_time()?,
+        text.trim()
+    );
+    append_to_note(args, &args.inbox, None, &bullet)?;
+    println!("captured to {}", args.inbox.display());
 
-    let shell = get_env_var(SHELL_ENV_VARNAME)?;
-    let mut lister_cmd = Command::new(shell);
-    lister_cmd.arg(&args.shell_cmd_flag).arg(&args.lister);
+    sync_edits(args, message)
+}
 
-    if !args.capture_std {
-        // Allow stderr/stdin to pass through for applications like fzf.
-        lister_cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
-    }
+/// Appends the text on the clipboard, and the URL it is from, if given, to the given note, the one
+/// chosen with the finder, or else the inbox note, under a heading with the current time, and
+/// syncs.
+pub fn clip(
+    args: &cli::Args,
+    filepath: Option<&std::path::PathBuf>,
+    find_note: bool,
+    source: Option<&str>,
+    message: Option<&str>,
+) -> Result<()> {
+    let text = clipboard::read(args)?;
+    let filepath = match (filepath, find_note) {
+        (Some(filepath), _) => filepath.clone(),
+        (None, true) => match find(args)? {
+            Some(filepath) => filepath,
+            None => return Ok(()),
+        },
+        (None, false) => args.inbox.clone(),
+    };
 
-    let (lister_stdout, should_exit_early) = exec_custom_invocation_cmd(lister_cmd, args)?;
-    if should_exit_early {
+    let heading = format!(
+        "## Clipped {} {}",
+        date::Date::today()?,
+        date::local_time()?
+    );
+    let text = match source {
+        Some(source) => format!("Source: <{}>\n\n{}", source, text),
+        None => text,
+    };
+    append_to_note(args, &filepath, Some(&heading), &text)?;
+    println!("clipped to {}", filepath.display());
+
+    sync_edits(args, message)
+}
+
+/// Saves the image on the clipboard as an asset, and embeds it at the end of the given note and
+/// syncs, or only prints the Markdown that embeds it.
+pub fn paste_image(
+    args: &cli::Args,
+    filepath: &std::path::PathBuf,
+    print: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let note = vault_relative_path(args, filepath)?;
+    let image = clipboard::read_image(args)?;
+    let asset = assets::store(args, &image, "png")?;
+    assets::warn_if_ignored(args, &asset)?;
+    let reference = assets::reference(&note, &asset, "", true);
+    if print {
+        println!("{}", reference);
         return Ok(());
     }
 
-    println!("{}", lister_stdout);
+    append_to_note(args, filepath, None, &reference)?;
+    println!("embedded {} in {}", asset.display(), filepath.display());
+    sync_edits(args, message)
+}
 
-    // Before we can return, we need to reset the current working directory. Technically, since jot
-    // is only ran for a single command at a time, this is actually not necessary, so really, we're
-    // just being polite. I don't think there really is a reason to care, it just bothers me.
-    std::env::set_current_dir(&args.base_dir).context(format!(
-        "failed to change jot's working directory to {} for listing",
-        args.base_dir.display(),
+/// Copies the given file into the given note's assets directory, and links to it at the end of
+/// the note and syncs, or only prints the Markdown that links to it.
+pub fn attach(
+    args: &cli::Args,
+    file: &Path,
+    filepath: &std::path::PathBuf,
+    per_note: bool,
+    print: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let note = vault_relative_path(args, filepath)?;
+    let Some(file_name) = file.file_name() else {
+        bail!("{} is not a file", file.display());
+    };
+    let dir = assets::dir(args, &note, per_note);
+    let asset = unique_path(args, &dir.join(file_name))?;
+    std::fs::create_dir_all(args.base_dir.join(&dir))
+        .context(format!("failed to create {}", dir.display()))?;
+    std::fs::copy(file, args.base_dir.join(&asset)).context(format!(
+        "failed to copy {} to {}",
+        file.display(),
+        asset.display()
     ))?;
+    assets::warn_if_ignored(args, &asset)?;
 
-    Ok(())
+    let reference = assets::reference(
+        &note,
+        &asset,
+        &file_name.to_string_lossy(),
+        assets::is_image(&asset),
+    );
+    if print {
+        println!("{}", reference);
+        return Ok(());
+    }
+    append_to_note(args, filepath, None, &reference)?;
+    println!("attached {} to {}", asset.display(), filepath.display());
+    sync_edits(args, message)
 }
 
-pub fn sync(args: &cli::Args) -> Result<()> {
-    static GIT_CMD: &str = "git";
+/// Captures the readable content of the web page at the given URL in a new note in the given
+/// directory (or base-dir), named after the page's title, and syncs.
+pub fn web(args: &cli::Args, url: &str, dir: Option<&Path>, message: Option<&str>) -> Result<()> {
+    let page = html::readable(&html::fetch(args, url)?, url);
+    let title = page.title.unwrap_or_else(|| url.to_string());
+    let slug = match template::slug(args, &title) {
+        slug if slug.is_empty() => "web".to_string(),
+        slug => slug,
+    };
+    let filepath = unique_path(
+        args,
+        &dir.unwrap_or_else(|| Path::new(""))
+            .join(format!("{}.md", slug)),
+    )?;
 
-    // TODO: We should only run the following chain of git commands if there are new changes.
+    // Most pages have their title as a heading already.
+    let title_heading = format!("# {}", title);
+    let (heading, body) = match page.body.starts_with("# ") {
+        true => page.body.split_once('\n').unwrap_or((&page.body, "")),
+        false => (title_heading.as_str(), page.body.as_str()),
+    };
+    let mut fields = vec![("source", frontmatter::Value::Scalar(url.to_string()))];
+    let mut content = format!("{}\n\n", heading);
+    if let Some(byline) = &page.byline {
+        content.push_str(&format!("By {}\n\n", byline));
+        fields.push(("author", frontmatter::Value::Scalar(byline.clone())));
+    }
+    content.push_str(body.trim_start());
+    content.push('\n');
+    create_note(
+        args,
+        &filepath,
+        &title,
+        None,
+        date::Date::today()?,
+        fields,
+        &[("stdin", &content)],
+    )?;
+    println!("captured {} to {}", url, filepath.display());
 
-    // First, git pull to fetch and merge upstream changes.
-    // If we encounter an issue, namely a merge conflict, this will propagate an error and we will
-    // abort on trying to merge our recent changes.
-    let mut git_pull_exec = Command::new(GIT_CMD);
-    git_pull_exec
-        .arg("pull")
-        .arg(&args.git_remote_name)
-        .arg(&args.git_upstream_branch);
-    exec_cmd("pulling", git_pull_exec, true, args.quiet_on_ctrl_c)
-        .context("failed to pull upstream changes, please fix the issue and run jot sync")?;
-
-    // Second, if we get here, git pull worked. In that case, let's stage our local changes:
-    let mut git_pull_exec = Command::new(GIT_CMD);
-    git_pull_exec.arg("add").arg("-A");
-    exec_cmd("staging", git_pull_exec, true, args.quiet_on_ctrl_c)?;
-
-    // Third, commit these staged changes:
-    let mut git_commit_exec = Command::new(GIT_CMD);
-    git_commit_exec.arg("commit");
-    if args.git_custom_commit_msg {
-        git_commit_exec
-            .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit());
-    } else {
-        git_commit_exec
-            .arg("-m")
-            .arg(format!("{}", format_rfc3339_seconds(SystemTime::now())));
+    sync_edits(args, message)
+}
+
+/// Appends the given text, or else stdin, to the given note, under a heading with today's date if
+/// asked to be, creating the note if need be, and syncs.
+pub fn append(
+    args: &cli::Args,
+    filepath: &std::path::PathBuf,
+    text: Option<&str>,
+    dated: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let text = match text {
+        Some(text) => text.to_string(),
+        None => {
+            let mut stdin = String::new();
+            std::io::stdin()
+                .read_to_string(&mut stdin)
+                .context("failed to read stdin")?;
+            stdin
+        }
+    };
+    if text.trim().is_empty() {
+        bail!("there is nothing to append to {}", filepath.display());
+    }
+    let heading = match dated {
+        true => Some(format!("## {}", date::Date::today()?)),
+        false => None,
+    };
+    append_to_note(args, filepath, heading.as_deref(), &text)?;
+
+    sync_edits(args, message)
+}
+
+/// The frontmatter fields that hold dates, which copies of notes get fresh values for.
+const DATE_FIELDS: [&str; 4] = ["created", "date", "updated", "modified"];
+
+pub fn cp(
+    args: &cli::Args,
+    source: &std::path::PathBuf,
+    dest: &std::path::PathBuf,
+    fresh_dates: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let source = vault_relative_path(args, source)?;
+    let dest = vault_relative_path(args, dest)?;
+    if !args.base_dir.join(&source).is_file() {
+        bail!("there is no note at {}", source.display());
+    }
+    if args.base_dir.join(&dest).exists() {
+        bail!("{} exists already", dest.display());
+    }
+
+    let mut content = read_note(args, &source)?;
+    if fresh_dates {
+        let (frontmatter, _) = frontmatter::split(&content);
+        if let Some(frontmatter) = frontmatter {
+            let today = date::Date::today()?;
+            let now = format!("{}T{}", today, date::local_time()?);
+            let fields: Vec<(&str, frontmatter::Value)> = DATE_FIELDS
+                .into_iter()
+                .filter(|field| frontmatter.get(field).is_some())
+                .map(|field| {
+                    let value = match field {
+                        "date" => today.to_string(),
+                        _ => now.clone(),
+                    };
+                    (field, frontmatter::Value::Scalar(value))
+                })
+                .collect();
+            content = frontmatter::remove(&content, &DATE_FIELDS);
+            if !fields.is_empty() {
+                content = frontmatter::inject(&content, &fields);
+            }
+        }
+    }
+    write_note(args, &dest, &content)?;
+
+    open_editor_at_path(&dest, args, message)
+}
+
+pub fn merge(
+    args: &cli::Args,
+    notes: &[std::path::PathBuf],
+    into: &std::path::PathBuf,
+    message: Option<&str>,
+) -> Result<()> {
+    let into = vault_relative_path(args, into)?;
+    let mut sources: Vec<std::path::PathBuf> = Vec::new();
+    for note in notes {
+        let note = vault_relative_path(args, note)?;
+        if !args.base_dir.join(&note).is_file() {
+            bail!("there is no note at {}", note.display());
+        }
+        if !sources.contains(&note) {
+            sources.push(note);
+        }
+    }
+    if sources.len() < 2 {
+        bail!("there must be at least two notes to merge");
+    }
+    if args.base_dir.join(&into).exists() && !sources.contains(&into) {
+        bail!(
+            "{} exists already, list it among the notes to merge it too",
+            into.display()
+        );
+    }
+
+    let mut contents = Vec::new();
+    let mut titles = Vec::new();
+    for source in &sources {
+        let content = read_note(args, source)?;
+        let (_, body) = frontmatter::split(&content);
+        let title = match outline::split_title(body) {
+            (Some(title), _) => title.to_string(),
+            (None, _) => template::title(source),
+        };
+        titles.push(title);
+        contents.push(content);
+    }
+
+    let mut notes = vault::notes(&args.base_dir)?;
+    if !notes.contains(&into) {
+        notes.push(into.clone());
+    }
+    let resolver = links::Resolver::new(&notes);
+    // Links to the merged notes point at their sections instead, unless they point at a heading
+    // in them already, which the merged note still has.
+    let moved = |note: &Path, heading: Option<&str>| {
+        let idx = sources.iter().position(|source| source == note)?;
+        let heading = heading.map(str::to_string).or(Some(titles[idx].clone()));
+        Some((into.clone(), heading))
+    };
+
+    let mut merged = String::new();
+    let mut parts = Vec::new();
+    for (idx, (source, content)) in sources.iter().zip(&contents).enumerate() {
+        let content = resolver.relink(content, source, &into, moved);
+        let (frontmatter, body) = frontmatter::split(&content);
+        if idx == 0 && frontmatter.is_some() {
+            merged.push_str(&content[..content.len() - body.len()]);
+        }
+        parts.push(outline::split_title(body).1.to_string());
+    }
+    let parts: Vec<(&str, &str)> = titles
+        .iter()
+        .map(String::as_str)
+        .zip(parts.iter().map(String::as_str))
+        .collect();
+    merged.push_str(&outline::merge(&template::title(&into), &parts));
+    write_note(args, &into, &merged)?;
+
+    for note in vault::notes(&args.base_dir)? {
+        if sources.contains(&note) {
+            continue;
+        }
+        let content = read_note(args, &note)?;
+        let relinked = resolver.relink(&content, &note, &note, moved);
+        if relinked != content {
+            std::fs::write(args.base_dir.join(&note), relinked)
+                .context(format!("failed to write {}", note.display()))?;
+        }
+    }
+    for source in sources.iter().filter(|source| **source != into) {
+        std::fs::remove_file(args.base_dir.join(source))
+            .context(format!("failed to delete note at {}", source.display()))?;
+    }
+
+    sync_edits(args, message)
+}
+
+pub fn split(args: &cli::Args, note: &std::path::PathBuf, message: Option<&str>) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+    if !args.base_dir.join(&note).is_file() {
+        bail!("there is no note at {}", note.display());
+    }
+    let content = read_note(args, &note)?;
+    let (preamble, sections) = outline::split(&content);
+    if sections.is_empty() {
+        bail!("{} has no headings to split it by", note.display());
+    }
+
+    let dir = note.with_extension("");
+    let mut pieces: Vec<std::path::PathBuf> = Vec::new();
+    for section in &sections {
+        // A heading with nothing to slugify, e.g. `## ???`, would otherwise make for a hidden note.
+        let stem = match template::slug(args, &section.heading) {
+            stem if stem.is_empty() => "section".to_string(),
+            stem => stem,
+        };
+        let mut piece = dir.join(format!("{}.md", stem));
+        let mut suffix = 2;
+        while pieces.contains(&piece) {
+            piece = dir.join(format!("{}{}{}.md", stem, args.slug_separator, suffix));
+            suffix += 1;
+        }
+        if args.base_dir.join(&piece).exists() {
+            bail!("{} exists already", piece.display());
+        }
+        pieces.push(piece);
+    }
+
+    let mut notes = vault::notes(&args.base_dir)?;
+    notes.extend(pieces.iter().cloned());
+    let resolver = links::Resolver::new(&notes);
+    // Only links to the sections' headings move with them. Links to the note itself keep
+    // pointing at it, where they find links to the sections.
+    let moved = |target: &Path, heading: Option<&str>| {
+        let heading = links::anchor(heading?);
+        let idx = sections
+            .iter()
+            .position(|section| links::anchor(&section.heading) == heading)?;
+        (target == note).then(|| (pieces[idx].clone(), None))
+    };
+
+    for (section, piece) in sections.iter().zip(&pieces) {
+        let content = resolver.relink(&section.content, &note, piece, moved);
+        write_note(args, piece, &format!("{}\n", content.trim_end()))?;
+    }
+
+    let mut stub = resolver.relink(preamble, &note, &note, moved);
+    if !stub.trim().is_empty() {
+        stub = format!("{}\n\n", stub.trim_end());
+    }
+    for (section, piece) in sections.iter().zip(&pieces) {
+        stub.push_str(&format!(
+            "- {}\n",
+            assets::reference(&note, piece, &section.heading, false)
+        ));
+    }
+    std::fs::write(args.base_dir.join(&note), stub)
+        .context(format!("failed to write {}", note.display()))?;
+
+    for other in vault::notes(&args.base_dir)? {
+        if other == note || pieces.contains(&other) {
+            continue;
+        }
+        let content = read_note(args, &other)?;
+        let relinked = resolver.relink(&content, &other, &other, moved);
+        if relinked != content {
+            std::fs::write(args.base_dir.join(&other), relinked)
+                .context(format!("failed to write {}", other.display()))?;
+        }
+    }
+
+    sync_edits(args, message)
+}
+
+/// Opens the note of the period the given number of periods from the current one, e.g. yesterday's
+/// daily note, creating it from the period's template if it does not exist yet.
+pub fn periodic(
+    args: &cli::Args,
+    period: cli::Period,
+    periods_from_now: i64,
+    message: Option<&str>,
+) -> Result<()> {
+    let date = periodic::shift(period, date::Date::today()?, periods_from_now);
+    open_periodic(args, period, date, message)
+}
+
+/// Opens the note of the given period the given date is in in $EDITOR, creating it from the
+/// period's template if it does not exist yet, along with the previous note's unfinished tasks if
+/// asked to carry them over, and syncs.
+fn open_periodic(
+    args: &cli::Args,
+    period: cli::Period,
+    date: date::Date,
+    message: Option<&str>,
+) -> Result<()> {
+    let filepath = periodic::path(args, period, date)?;
+    let absolute_filepath = relative_path_to_absolute(args, &filepath)?;
+    let mut cursor = None;
+    if !absolute_filepath.exists() {
+        cursor = create_note(
+            args,
+            &filepath,
+            &template::title(&filepath),
+            periodic::template(args, period),
+            date,
+            Vec::new(),
+            &[],
+        )?;
+
+        if args.carry_over_tasks {
+            let previous = periodic::path(args, period, periodic::shift(period, date, -1))?;
+            if args.base_dir.join(&previous).exists() {
+                let previous_content = read_note(args, &previous)?;
+                let tasks = periodic::unfinished_tasks(&previous_content);
+                if !tasks.is_empty() {
+                    let mut content = read_note(args, &filepath)?;
+                    if !content.is_empty() && !content.ends_with('\n') {
+                        content.push('\n');
+                    }
+                    for task in tasks {
+                        content.push_str(task);
+                        content.push('\n');
+                    }
+                    std::fs::write(&absolute_filepath, content)
+                        .context(format!("failed to write {}", filepath.display()))?;
+                }
+            }
+        }
+    }
+    open_editor_at_cursor(&filepath, args, cursor, message)
+}
+
+pub fn cal(
+    args: &cli::Args,
+    month: Option<&str>,
+    interactive: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let today = date::Date::today()?;
+    let month = match month {
+        Some(month) => calendar::parse_month(month, today)?,
+        None => periodic::start(cli::Period::Month, today),
+    };
+    let days = 1..=calendar::days_in_month(month);
+
+    let mut journaled = BTreeSet::new();
+    for day in days.clone() {
+        let note = periodic::path(args, cli::Period::Day, date::Date { day, ..month })?;
+        if args.base_dir.join(note).exists() {
+            journaled.insert(day);
+        }
+    }
+    let since = format!("--since={} 00:00", month.add_days(-1));
+    let log = exec_git(
+        "reading history",
+        &[
+            "log",
+            "--relative",
+            "--name-only",
+            listing::TIMES_LOG_FORMAT,
+            &since,
+        ],
+        args,
+    )?;
+    let edited = calendar::commit_counts(&log, date::local_offset()?, |path| {
+        vault::is_note(Path::new(path))
+    })
+    .into_keys()
+    .filter(|date| date.year == month.year && date.month == month.month)
+    .map(|date| date.day)
+    .collect();
+
+    let today = Some(today.day).filter(|_| periodic::start(cli::Period::Month, today) == month);
+    print!(
+        "{}",
+        calendar::render(month, &journaled, &edited, today, Styler::stdout(args))
+    );
+    if !interactive {
+        return Ok(());
+    }
+
+    let answer = prompt(&format!(
+        "\nday to open ({}-{}): ",
+        days.start(),
+        days.end()
+    ))?;
+    if answer.is_empty() {
+        return Ok(());
+    }
+    match answer.parse::<u32>() {
+        Ok(day) if days.contains(&day) => {
+            open_periodic(args, cli::Period::Day, date::Date { day, ..month }, message)
+        }
+        _ => bail!(
+            "{} is not a day in {}-{:02}",
+            answer,
+            month.year,
+            month.month
+        ),
+    }
+}
+
+/// Returns the dates that have a daily note, or, according to git, once had one.
+fn journal_dates(args: &cli::Args) -> Result<BTreeSet<date::Date>> {
+    let mut paths = vault::notes(&args.base_dir)?;
+    if git::is_repository()? {
+        let log = exec_git(
+            "reading the journal's history",
+            &[
+                "-c",
+                "core.quotepath=off",
+                "log",
+                "--relative",
+                "--name-only",
+                "--pretty=format:",
+            ],
+            args,
+        )?;
+        paths.extend(log.lines().filter(|line| !line.is_empty()).map(Into::into));
+    }
+    Ok(paths
+        .iter()
+        .filter_map(|path| date::Date::parse(&args.daily_path, &path.to_string_lossy()))
+        .collect())
+}
+
+pub fn streak(args: &cli::Args) -> Result<()> {
+    let dates = journal_dates(args)?;
+    print!("{}", streak::render(&dates, date::Date::today()?));
+    Ok(())
+}
+
+pub fn stats(args: &cli::Args, heatmap: bool, json: bool, weeks: usize) -> Result<()> {
+    let today = date::Date::today()?;
+    let start = periodic::start(cli::Period::Week, today).add_days(-7 * (weeks as i64 - 1));
+    let utc_offset = date::local_offset()?;
+
+    let since = format!("--since={} 00:00", start.add_days(-1));
+    let log = exec_git(
+        "reading history",
+        &[
+            "log",
+            "--relative",
+            "--name-only",
+            listing::TIMES_LOG_FORMAT,
+            &since,
+        ],
+        args,
+    )?;
+    let mut counts =
+        calendar::commit_counts(&log, utc_offset, |path| vault::is_note(Path::new(path)));
+    counts.retain(|date, _| *date >= start);
+
+    let notes = listing::notes(&args.base_dir, None, &[], &git_times(args)?)?;
+    let mut stats = stats::Stats::gather(&args.base_dir, &notes, utc_offset)?;
+    stats.edit_days = Some((counts.len(), weeks));
+    if json {
+        println!("{}", stats.to_json());
+        return Ok(());
+    }
+
+    if heatmap {
+        println!(
+            "{}",
+            calendar::heatmap(today, weeks, &counts, Styler::stdout(args))
+        );
+    }
+    print!("{}", stats.render());
+    Ok(())
+}
+
+pub fn info(args: &cli::Args, note: &std::path::PathBuf) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+    if !args.base_dir.join(&note).is_file() || !vault::is_note(&note) {
+        bail!("there is no note at {}", note.display());
+    }
+    let listed = listing::notes(&args.base_dir, Some(&note), &[], &git_times(args)?)?
+        .into_iter()
+        .find(|listed| listed.path == note)
+        .context(format!("failed to read note at {}", note.display()))?;
+    let graph = Graph::build(&args.base_dir)?;
+    let info = stats::NoteInfo::gather(&args.base_dir, listed, &graph, date::local_offset()?)?;
+    print!("{}", info.render());
+    Ok(())
+}
+
+fn exec_custom_invocation_cmd(
+    label: &str,
+    mut cmd: Command,
+    args: &cli::Args,
+    timeout_secs: Option<u64>,
+) -> Result<(String, bool)> {
+    if !args.capture_std {
+        // Allow stderr/stdin to pass through for applications like fzf.
+        cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+    }
+
+    let (finder_stdout, exit_code) = exec_cmd_with_timeout(
+        label,
+        cmd,
+        args.capture_std,
+        args.quiet_on_ctrl_c,
+        timeout_secs.map(Duration::from_secs),
+    )?;
+
+    // If asked to be quiet on CTRL+C, then exec_cmd() will not have returned error. However, if
+    // so, we don't want to make use of whatever stdout may have returned, since the finder program
+    // was terminated prematurely (presumably). If so, return true as our boolean half of the
+    // tuple, to indicate an early return from the caller.
+    Ok((
+        finder_stdout,
+        args.quiet_on_ctrl_c && exit_code == Some(CTRL_C_EXIT_CODE),
+    ))
+}
+
+/// Executes the finder invocation, and returns the filepath it chose, or None if jot should exit
+/// early instead, e.g. because the finder was exited with Ctrl-C.
+fn find(args: &cli::Args) -> Result<Option<std::path::PathBuf>> {
+    let shell = get_env_var(SHELL_ENV_VARNAME)?;
+    let mut finder_cmd = Command::new(shell);
+    finder_cmd.arg(&args.shell_cmd_flag).arg(&args.finder);
+
+    if !args.capture_std {
+        // Allow stderr/stdin to pass through for applications like fzf.
+        finder_cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+    }
+
+    let (finder_stdout, should_exit_early) =
+        exec_custom_invocation_cmd("finder", finder_cmd, args, args.finder_timeout_secs)?;
+    Ok((!should_exit_early).then(|| std::path::PathBuf::from(finder_stdout)))
+}
+
+pub fn edit(args: &cli::Args, message: Option<&str>) -> Result<()> {
+    // First, we should execute the finder invocation and get a chosen filepath.
+    let Some(filepath) = find(args)? else {
+        return Ok(());
+    };
+
+    // Then, open the editor at that path.
+    open_editor_at_path(&filepath, args, message)?;
+
+    Ok(())
+}
+
+/// Pins or unpins the given note, and syncs.
+pub fn pin(
+    args: &cli::Args,
+    note: &std::path::PathBuf,
+    pin: bool,
+    message: Option<&str>,
+) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+    let mut pinned = pins::load(&args.base_dir)?;
+    match (pin, pinned.contains(&note)) {
+        (true, true) => bail!("{} is already pinned", note.display()),
+        (true, false) => {
+            if !args.base_dir.join(&note).is_file() {
+                bail!("there is no note at {}", note.display());
+            }
+            pinned.push(note);
+        }
+        (false, true) => pinned.retain(|pin| *pin != note),
+        (false, false) => bail!("{} is not pinned", note.display()),
+    }
+    pins::save(&args.base_dir, &pinned)?;
+    sync_edits(args, message)
+}
+
+pub fn last(args: &cli::Args, message: Option<&str>) -> Result<()> {
+    let Some(note) = last::read()? else {
+        bail!("jot has not opened any notes yet");
+    };
+    open_editor_at_path(&note, args, message)
+}
+
+/// Walks the inbox note's items, i.e. its top-level list items and sections, asking for each
+/// whether to move it to a note chosen with the finder, delete it, or leave it, and syncs.
+pub fn refile(args: &cli::Args, message: Option<&str>) -> Result<()> {
+    if !relative_path_to_absolute(args, &args.inbox)?.exists() {
+        println!("there is no inbox at {}", args.inbox.display());
+        return Ok(());
+    }
+    let content = read_note(args, &args.inbox)?;
+    let items = refile::items(&content);
+    if items.is_empty() {
+        println!("{} is empty", args.inbox.display());
+        return Ok(());
+    }
+
+    let mut removed: Vec<std::ops::Range<usize>> = Vec::new();
+    for (idx, item) in items.iter().enumerate() {
+        println!("\n=== {}/{}", idx + 1, items.len());
+        print!("{}", &content[item.clone()]);
+        let answer = prompt_choice(
+            "[r] refile, [d] delete, [s] skip, [q] quit: ",
+            &["r", "d", "s", "q"],
+        )?;
+        match answer.as_deref() {
+            Some("r") => {
+                let Some(target) = find(args)? else {
+                    break;
+                };
+                append_to_note(args, &target, None, &content[item.clone()])?;
+                println!("refiled to {}", target.display());
+                removed.push(item.clone());
+            }
+            Some("d") => removed.push(item.clone()),
+            Some("s") => {}
+            _ => break,
+        }
+    }
+    if removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut remaining = String::new();
+    let mut offset = 0;
+    for range in &removed {
+        remaining.push_str(&content[offset..range.start]);
+        offset = range.end;
+    }
+    remaining.push_str(&content[offset..]);
+    std::fs::write(relative_path_to_absolute(args, &args.inbox)?, remaining)
+        .context(format!("failed to write {}", args.inbox.display()))?;
+
+    sync_edits(args, message)
+}
+
+/// Returns when each file in base-dir was first and last committed, if it is a git repository.
+fn git_last_commits(args: &cli::Args) -> Result<HashMap<std::path::PathBuf, listing::LastCommit>> {
+    if !git::is_repository()? {
+        return Ok(HashMap::new());
+    }
+    let log = exec_git(
+        "reading who last changed notes",
+        &[
+            "-c",
+            "core.quotepath=off",
+            "log",
+            "--relative",
+            "--name-only",
+            listing::LAST_COMMIT_LOG_FORMAT,
+        ],
+        args,
+    )?;
+    Ok(listing::parse_last_commits(&log))
+}
+
+fn git_times(args: &cli::Args) -> Result<HashMap<std::path::PathBuf, listing::Times>> {
+    if !git::is_repository()? {
+        return Ok(HashMap::new());
+    }
+    let log = exec_git(
+        "reading when notes were changed",
+        &[
+            "-c",
+            "core.quotepath=off",
+            "log",
+            "--relative",
+            "--name-only",
+            listing::TIMES_LOG_FORMAT,
+        ],
+        args,
+    )?;
+    Ok(listing::parse_times(&log))
+}
+
+/// Lists the pinned notes beneath the given directory, with their titles if asked for, to go
+/// above a tree listing of it. This is empty if there are none.
+fn pinned_listing(args: &cli::Args, dir: &Path, titles: bool) -> Result<String> {
+    let mut rows = Vec::new();
+    for pin in pins::load(&args.base_dir)? {
+        let path = args.base_dir.join(&pin);
+        if !path.starts_with(dir) || !path.is_file() {
+            continue;
+        }
+        let title = match titles {
+            true => read_note(args, &pin)
+                .ok()
+                .and_then(|content| outline::title(&content)),
+            false => None,
+        };
+        rows.push(vec![
+            format!("  {}", pin.display()),
+            title.unwrap_or_default(),
+        ]);
+    }
+    match rows.is_empty() {
+        true => Ok(String::new()),
+        false => Ok(format!("Pinned\n{}\n", output::columns(&rows))),
+    }
+}
+
+pub fn list(args: &cli::Args, list_args: &cli::ListArgs) -> Result<()> {
+    let subpath = list_args.subpath.as_ref();
+    // First, change working directory into the given list_path.
+    // Note that this could possibly be a no-op if none was specified.
+    let listing_path = subpath.map_or(Ok(args.base_dir.clone()), |path| {
+        relative_path_to_absolute(args, path)
+    })?;
+
+    if list_args.is_flat() {
+        let subpath = subpath
+            .map(|subpath| vault_relative_path(args, subpath))
+            .transpose()?;
+        let mut notes = listing::notes(
+            &args.base_dir,
+            subpath.as_deref(),
+            &list_args.ext,
+            &git_times(args)?,
+        )?;
+        let modified_since = list_args
+            .modified_since
+            .map(|duration| history::now().saturating_sub(duration.as_secs()));
+        let depth = |note: &listing::Note| {
+            match &subpath {
+                Some(subpath) => note.path.strip_prefix(subpath).unwrap_or(&note.path),
+                None => &note.path,
+            }
+            .components()
+            .count() as u64
+        };
+        notes.retain(|note| {
+            list_args
+                .tag
+                .iter()
+                .all(|tag| note.tags.iter().any(|t| t == tag.trim_start_matches('#')))
+                && modified_since.is_none_or(|since| note.modified.is_some_and(|m| m >= since))
+                && list_args.depth.is_none_or(|max| depth(note) <= max)
+        });
+        if let Some(key) = list_args.sort {
+            listing::sort(&mut notes, key, list_args.reverse);
+        }
+        listing::pinned_first(&mut notes);
+        if list_args.last_commit {
+            let mut last_commits = git_last_commits(args)?;
+            for note in notes.iter_mut() {
+                note.last_commit = last_commits.remove(&note.path);
+            }
+        }
+        let listing = match (list_args.json, list_args.print0) {
+            (true, _) => {
+                let notes = notes.iter().map(listing::Note::to_json).collect();
+                format!("{}\n", json::Value::Array(notes))
+            }
+            (false, true) => output::paths(notes.iter().map(|note| &note.path), true),
+            (false, false) => {
+                // Only the last commits' dates need the timezone, which takes asking date for.
+                let utc_offset = match list_args.last_commit {
+                    true => date::local_offset()?,
+                    false => 0,
+                };
+                output::columns(
+                    &notes
+                        .iter()
+                        .map(|note| {
+                            let mut row = vec![note.path.display().to_string()];
+                            if !list_args.no_titles {
+                                row.push(note.title.clone().unwrap_or_default());
+                            }
+                            if let Some(commit) = &note.last_commit {
+                                let days =
+                                    (commit.timestamp as i64 + utc_offset).div_euclid(86_400);
+                                row.extend([
+                                    date::Date::from_days(days).to_string(),
+                                    commit.author.clone(),
+                                    commit.subject.clone(),
+                                ]);
+                            }
+                            row
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            }
+        };
+        return output::page(args, &listing);
+    }
+
+    let lister = match &args.lister {
+        Some(lister) if list_args.depth.is_none() && !list_args.dirs_only && !list_args.all => {
+            lister
+        }
+        _ => {
+            let depth = list_args.depth.map(|depth| depth as usize);
+            let titles = !list_args.no_titles && !list_args.dirs_only;
+            let mut entries = tree::read(&listing_path, depth, list_args.all, titles)?;
+            let mut listing = String::new();
+            match list_args.dirs_only {
+                true => tree::retain_dirs(&mut entries),
+                false => listing = pinned_listing(args, &listing_path, titles)?,
+            }
+            let root = subpath.map_or(".".to_string(), |path| path.display().to_string());
+            listing.push_str(&tree::render(&root, &entries, Styler::stdout(args)));
+            return output::page(args, &format!("{}\n", listing));
+        }
+    };
+    std::env::set_current_dir(&listing_path).context(format!(
+        "failed to change jot's working directory to {} for listing",
+        listing_path.display(),
+    ))?;
+
+    let shell = get_env_var(SHELL_ENV_VARNAME)?;
+    let mut lister_cmd = Command::new(shell);
+    lister_cmd.arg(&args.shell_cmd_flag).arg(lister);
+
+    if !args.capture_std {
+        // Allow stderr/stdin to pass through for applications like fzf.
+        lister_cmd.stdin(Stdio::inherit()).stderr(Stdio::inherit());
+    }
+
+    let (lister_stdout, should_exit_early) =
+        exec_custom_invocation_cmd("lister", lister_cmd, args, args.lister_timeout_secs)?;
+    if should_exit_early {
+        return Ok(());
+    }
+
+    output::page(args, &format!("{}\n", lister_stdout))?;
+
+    // Before we can return, we need to reset the current working directory. Technically, since jot
+    // is only ran for a single command at a time, this is actually not necessary, so really, we're
+    // just being polite. I don't think there really is a reason to care, it just bothers me.
+    std::env::set_current_dir(&args.base_dir).context(format!(
+        "failed to change jot's working directory to {} for listing",
+        args.base_dir.display(),
+    ))?;
+
+    Ok(())
+}
+
+/// How long changes must be left alone for before sync --watch syncs them.
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+pub fn sync(args: &cli::Args, sync_args: &cli::SyncArgs) -> Result<()> {
+    if let Some(interval_mins) = sync_args.watch {
+        return daemon::run(
+            args,
+            WATCH_DEBOUNCE,
+            Duration::from_secs(interval_mins * 60),
+            false,
+        );
+    }
+
+    // TODO: We should only run the following chain of git commands if there are new changes.
+    let backend = sync::backend(args)?;
+    if sync_args.dry_run {
+        return backend.print_sync(sync_args);
+    }
+    let webhook_url = args
+        .webhook_url
+        .as_deref()
+        .filter(|_| matches!(args.sync_backend, cli::SyncBackend::Git));
+    let before = webhook_url.map(|_| webhook::head()).transpose()?;
+    let result = hooks::around(
+        args,
+        (hooks::Hook::PreSync, hooks::Hook::PostSync),
+        None,
+        || backend.sync(sync_args),
+    );
+    notify::sync_result(args, &result, sync_args.background);
+    result?;
+
+    // The sync itself succeeded, so a webhook that fails is not worth failing over.
+    if let (Some(url), Some(before)) = (webhook_url, before) {
+        if let Err(err) = webhook::post(args, url, before.as_deref()) {
+            println!("warning: {:#}", err);
+        }
+    }
+    Ok(())
+}
+
+/// Formats the given location in a note the way grep does, e.g. `notes/a.md:12:`, in grep's
+/// colors. The separator is `:` for matching lines, and `-` for context lines.
+fn grep_location(styler: Styler, path: &Path, lineno: usize, separator: &str) -> String {
+    format!(
+        "{}{}{}{}",
+        styler.paint(&path.display().to_string(), &[style::MAGENTA]),
+        styler.paint(separator, &[style::CYAN]),
+        styler.paint(&lineno.to_string(), &[style::GREEN]),
+        styler.paint(separator, &[style::CYAN])
+    )
+}
+
+pub fn backlinks(args: &cli::Args, note: &std::path::PathBuf, context: usize) -> Result<()> {
+    let styler = Styler::stdout(args);
+    let target = vault_relative_path(args, note)?;
+    let notes = vault::notes(&args.base_dir)?;
+    let resolver = links::Resolver::new(&notes);
+
+    let mut printed_any = false;
+    for source in notes.iter().filter(|source| **source != target) {
+        let content = read_note(args, source)?;
+        let linking = links::extract(&content)
+            .into_iter()
+            .filter(|link| resolver.resolve(source, link).as_ref() == Some(&target))
+            .collect::<Vec<links::Link>>();
+        let linking_lines = linking
+            .iter()
+            .map(|link| link.line)
+            .collect::<BTreeSet<usize>>();
+        if linking_lines.is_empty() {
+            continue;
+        }
+        let line_starts = content
+            .split_inclusive('\n')
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some(start)
+            })
+            .collect::<Vec<usize>>();
+
+        // We mimic grep's output here, including its context line format, since it is a format
+        // that most people (and editors) already know how to read.
+        let lines = content.lines().collect::<Vec<&str>>();
+        let printed_lines = linking_lines
+            .iter()
+            .flat_map(|lineno| lineno.saturating_sub(context).max(1)..=(lineno + context))
+            .filter(|lineno| *lineno <= lines.len())
+            .collect::<BTreeSet<usize>>();
+        let mut last_printed: Option<usize> = None;
+        for lineno in printed_lines {
+            let is_new_group = last_printed.map_or(printed_any, |last| lineno > last + 1);
+            if context > 0 && is_new_group {
+                println!("{}", styler.paint("--", &[style::CYAN]));
+            }
+            let separator = if linking_lines.contains(&lineno) {
+                ":"
+            } else {
+                "-"
+            };
+            // The links themselves are highlighted like grep highlights its matches.
+            let spans = linking
+                .iter()
+                .filter(|link| link.line == lineno)
+                .map(|link| {
+                    link.span.start - line_starts[lineno - 1]
+                        ..link.span.end - line_starts[lineno - 1]
+                })
+                .collect::<Vec<_>>();
+            println!(
+                "{} {}",
+                grep_location(styler, source, lineno, separator),
+                styler.paint_ranges(lines[lineno - 1], &spans, &[style::BOLD, style::RED])
+            );
+            last_printed = Some(lineno);
+        }
+        printed_any = true;
+    }
+
+    Ok(())
+}
+
+pub fn graph(args: &cli::Args, format: cli::GraphFormat) -> Result<()> {
+    let graph = Graph::build(&args.base_dir)?;
+    match format {
+        cli::GraphFormat::Dot => println!("{}", graph.to_dot()),
+        cli::GraphFormat::Json => println!("{}", graph.to_json()),
+    }
+
+    Ok(())
+}
+
+pub fn orphans(args: &cli::Args, exclude: &[std::path::PathBuf], print0: bool) -> Result<()> {
+    let excluded_dirs = exclude
+        .iter()
+        .map(|dir| vault_relative_path(args, dir))
+        .collect::<Result<Vec<std::path::PathBuf>>>()?;
+
+    let graph = Graph::build(&args.base_dir)?;
+    let orphans = graph
+        .orphans()
+        .into_iter()
+        .filter(|orphan| !excluded_dirs.iter().any(|dir| orphan.starts_with(dir)));
+    print!("{}", output::paths(orphans, print0));
+
+    Ok(())
+}
+
+/// Checks that the given URL responds successfully, returning a description of the problem if it
+/// does not.
+fn check_url(url: &str, timeout: u64) -> Option<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--head", "--location", "--output", "/dev/null"])
+        .arg("--max-time")
+        .arg(timeout.to_string())
+        .arg("--write-out")
+        .arg("%{http_code}")
+        .arg(url)
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => return Some(format!("failed to execute curl: {}", err)),
+    };
+
+    let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match status.parse::<u16>() {
+        Ok(0) | Err(_) => Some(format!(
+            "no response (curl exit code {})",
+            output
+                .status
+                .code()
+                .map_or("N/A".to_string(), |code| code.to_string())
+        )),
+        // Some servers refuse HEAD requests outright, but that still tells us the page is there.
+        Ok(code) if code < 400 || code == 405 => None,
+        Ok(code) => Some(format!("HTTP {}", code)),
+    }
+}
+
+pub fn check_links(args: &cli::Args, external: bool, jobs: usize, timeout: u64) -> Result<()> {
+    let notes = vault::notes(&args.base_dir)?;
+    let resolver = links::Resolver::new(&notes);
+    let styler = Styler::stdout(args);
+
+    let mut broken = 0;
+    let mut external_links = Vec::new();
+    for source in &notes {
+        let content = read_note(args, source)?;
+        for link in links::extract(&content) {
+            if links::is_external(&link.target) {
+                if link.target.starts_with("http://") || link.target.starts_with("https://") {
+                    external_links.push((source, link));
+                }
+                continue;
+            }
+
+            if let Some(target) = resolver.resolve(source, &link) {
+                if !args.base_dir.join(&target).exists() {
+                    println!(
+                        "{} {} (no such file: {})",
+                        grep_location(styler, source, link.line, ":"),
+                        styler.paint(&link.target, &[style::BOLD, style::RED]),
+                        target.display()
+                    );
+                    broken += 1;
+                }
+            }
+        }
+    }
+
+    if external {
+        // Checking external links is slow, so we do it with a handful of worker threads pulling
+        // from a shared queue of links.
+        let queue = Mutex::new(external_links.iter());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.max(1) {
+                let sender = sender.clone();
+                let queue = &queue;
+                scope.spawn(move || loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some((source, link)) = next else {
+                        break;
+                    };
+                    if let Some(problem) = check_url(&link.target, timeout) {
+                        // The receiver outlives every worker, so this cannot fail.
+                        sender.send((*source, link, problem)).unwrap();
+                    }
+                });
+            }
+        });
+        drop(sender);
+
+        let mut failures = receiver.iter().collect::<Vec<_>>();
+        failures.sort_by(|(a, a_link, _), (b, b_link, _)| {
+            (a, a_link.line, &a_link.target).cmp(&(b, b_link.line, &b_link.target))
+        });
+        for (source, link, problem) in failures {
+            println!(
+                "{} {} ({})",
+                grep_location(styler, source, link.line, ":"),
+                styler.paint(&link.target, &[style::BOLD, style::RED]),
+                problem
+            );
+            broken += 1;
+        }
+    }
+
+    if broken > 0 {
+        bail!("found {} broken link(s)", broken);
+    }
+
+    Ok(())
+}
+
+pub fn recent(args: &cli::Args, count: usize, print0: bool) -> Result<()> {
+    // Rather than keeping track of what was recently edited ourselves, we just ask git. Since every
+    // edit ends with a sync, the history is a faithful record of what was touched and when.
+    let log = exec_git(
+        "listing recent changes",
+        &[
+            "-c",
+            "core.quotepath=off",
+            "log",
+            "--relative",
+            "--name-only",
+            "--diff-filter=AM",
+            "--pretty=format:",
+        ],
+        args,
+    )?;
+
+    let mut seen = BTreeSet::new();
+    let recent_notes = log
+        .lines()
+        .map(Path::new)
+        .filter(|path| vault::is_note(path) && args.base_dir.join(path).exists())
+        .filter(|path| seen.insert(path.to_path_buf()))
+        .take(count);
+    print!("{}", output::paths(recent_notes, print0));
+
+    Ok(())
+}
+
+pub fn log(args: &cli::Args, note: Option<&std::path::PathBuf>, count: usize) -> Result<()> {
+    let count = count.to_string();
+    let mut git_args = vec!["log", history::LOG_FORMAT, "-n", &count];
+    let note = note
+        .map(|note| vault_relative_path(args, note))
+        .transpose()?;
+    let note_str = note.as_ref().map(|note| note.to_string_lossy());
+    if let Some(note_str) = &note_str {
+        // Following renames keeps a note's history intact when it is moved around.
+        git_args.extend(["--follow", "--", note_str]);
+    }
+    let commits = history::parse(&exec_git("reading history", &git_args, args)?)?;
+
+    let now = history::now();
+    let styler = Styler::stdout(args);
+    let log = commits
+        .iter()
+        .map(|commit| {
+            format!(
+                "{}  {:<15}  {}\n",
+                styler.paint(&commit.hash, &[style::YELLOW]),
+                history::relative_time(commit.timestamp, now),
+                commit.subject
+            )
+        })
+        .collect::<String>();
+    output::page(args, &log)
+}
+
+/// Resolves the given revision, or failing that, date (in any format git understands, e.g. `3 days
+/// ago` or `2024-01-31`), to a commit hash. For dates, this is the last commit made before it.
+fn resolve_revision(args: &cli::Args, revision: &str) -> Result<String> {
+    let is_revision = Command::new(GIT_CMD)
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", revision),
+        ])
+        .stdout(Stdio::null())
+        .status()
+        .context(format!("failed to resolve {}", revision))?
+        .success();
+    if is_revision {
+        return exec_git(
+            "resolving revision",
+            &["rev-parse", &format!("{}^{{commit}}", revision)],
+            args,
+        );
+    }
+
+    let commit = exec_git(
+        "resolving date",
+        &["rev-list", "-1", &format!("--before={}", revision), "HEAD"],
+        args,
+    )?;
+    if commit.is_empty() {
+        bail!(
+            "{} is neither a revision nor a date after the first commit",
+            revision
+        );
+    }
+    Ok(commit)
+}
+
+pub fn diff(
+    args: &cli::Args,
+    note: Option<&std::path::PathBuf>,
+    since: Option<&str>,
+    since_snapshot: Option<&str>,
+) -> Result<()> {
+    let snapshot = since_snapshot
+        .map(|snapshot| snapshot::resolve(args, snapshot))
+        .transpose()?;
+    let base = match snapshot.as_deref().or(since) {
+        Some(since) => resolve_revision(args, since)?,
+        // The remote-tracking branch is where base-dir was at as of the last sync.
+        None => {
+            let tracking_ref = format!(
+                "refs/remotes/{}/{}",
+                args.git_remote_name, args.git_upstream_branch
+            );
+            resolve_revision(args, &tracking_ref).unwrap_or_else(|_| "HEAD".to_string())
+        }
+    };
+    let note = note
+        .map(|note| vault_relative_path(args, note))
+        .transpose()?;
+
+    // Word-level diffs suit prose far better than line-level ones, since paragraphs tend to be
+    // single long lines. Without colors, the changed words are marked up with [-...-] and {+...+}.
+    let word_diff = if Styler::stdout(args).is_enabled() {
+        "--word-diff=color"
+    } else {
+        "--word-diff=plain"
+    };
+    let mut git_diff_exec = Command::new(GIT_CMD);
+    git_diff_exec.args(["diff", word_diff, &base, "--"]);
+    git_diff_exec.args(&note);
+    let mut diff = git_diff_exec
+        .stderr(Stdio::inherit())
+        .output()
+        .context("failed to execute diffing: `git diff`")?
+        .stdout;
+
+    // Files that were never committed are not part of git diff's output, so they are diffed
+    // against nothing, one by one. Those that sync would never commit are left out.
+    let ignore = Ignore::load(&args.base_dir, &args.ignore)?;
+    let mut git_ls_files_args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+    let note_str = note.as_ref().map(|note| note.to_string_lossy());
+    git_ls_files_args.extend(note_str.as_deref());
+    let untracked = exec_git("listing untracked files", &git_ls_files_args, args)?;
+    for path in untracked
+        .split('\0')
+        .filter(|path| !path.is_empty() && !ignore.is_ignored(Path::new(path)))
+    {
+        // git diff --no-index exits unsuccessfully whenever there are differences, which there
+        // always are here.
+        let untracked_diff = Command::new(GIT_CMD)
+            .args(["diff", word_diff, "--no-index", "/dev/null", path])
+            .stderr(Stdio::inherit())
+            .output()
+            .context("failed to execute diffing: `git diff --no-index`")?;
+        diff.extend(untracked_diff.stdout);
+    }
+
+    output::page(args, &String::from_utf8_lossy(&diff))
+}
+
+pub fn restore(
+    args: &cli::Args,
+    note: &std::path::PathBuf,
+    at: Option<&str>,
+    snapshot: Option<&str>,
+    copy: bool,
+) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+    let at = match snapshot {
+        Some(snapshot) => snapshot::resolve(args, snapshot)?,
+        None => at.expect("at is required without a snapshot").to_string(),
+    };
+    let revision = resolve_revision(args, &at)?;
+
+    // Unlike most git output, we need the note's content verbatim, so it is not trimmed.
+    let object = format!("{}:{}", revision, note.display());
+    let output = Command::new(GIT_CMD)
+        .args(["show", &object])
+        .output()
+        .context(format!(
+            "failed to execute restoring: `git show {}`",
+            object
+        ))?;
+    if !output.status.success() {
+        bail!("{} did not exist at {}", note.display(), at);
+    }
+
+    let mut restored_path = args.base_dir.join(&note);
+    if copy {
+        let mut file_name = restored_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".restored");
+        restored_path.set_file_name(file_name);
+    }
+    if let Some(parent) = restored_path.parent() {
+        std::fs::create_dir_all(parent)
+            .context(format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&restored_path, output.stdout)
+        .context(format!("failed to write {}", restored_path.display()))?;
+    println!("restored {}", restored_path.display());
+
+    // A copy is left for the user to pick from, whereas overwriting the note is an edit like any
+    // other, and so it is synced.
+    if !copy {
+        sync_edits(
+            args,
+            Some(&format!(
+                "restore {} from {}",
+                note.display(),
+                &revision[..revision.len().min(7)]
+            )),
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn undo(args: &cli::Args) -> Result<()> {
+    let head = exec_git(
+        "reading the last commit",
+        &["log", "-1", "--format=%H%x00%P%x00%s"],
+        args,
+    )?;
+    let [hash, parents, subject] = head.splitn(3, '\0').collect::<Vec<&str>>()[..] else {
+        bail!("unexpected output from git log: {}", head);
+    };
+    let short_hash = &hash[..hash.len().min(7)];
+    if !undo::is_recorded(hash)? {
+        bail!(
+            "the last commit ({} {}) was not made by jot, undo it with git instead",
+            short_hash,
+            subject
+        );
+    }
+    match parents.split_whitespace().count() {
+        0 if git::is_shallow()? => bail!(
+            "the commit before the last one is missing from this shallow clone, fetch it first \
+            with git fetch --deepen=1"
+        ),
+        0 => bail!("there is nothing to undo, the last commit is the first one"),
+        1 => {}
+        _ => bail!(
+            "the last commit ({} {}) is a merge, which jot undo does not handle",
+            short_hash,
+            subject
+        ),
+    }
+
+    // Rewriting history that was already pushed would break every other clone, so pushed commits
+    // are reverted instead. Commits that were never pushed can simply be dropped. The commit may
+    // have been pushed to any remote-tracking branch, e.g. a mirror's, or this device's own branch
+    // rather than git-upstream-branch.
+    let pushed_to = exec_git(
+        "determining if the last commit was pushed",
+        &[
+            "for-each-ref",
+            "--contains",
+            "HEAD",
+            "--format=%(refname:short)",
+            "refs/remotes",
+        ],
+        args,
+    )?;
+    if pushed_to.is_empty() {
+        exec_git("undoing", &["reset", "--keep", "HEAD~1"], args)?;
+        println!(
+            "dropped {} {}, which was never pushed, its changes can still be recovered with jot \
+            restore --at {}",
+            short_hash, subject, short_hash
+        );
+        return Ok(());
+    }
+
+    let mut git_args = vec!["revert", "--no-edit"];
+    let gpg_sign_arg = git::gpg_sign_arg(args);
+    git_args.extend(gpg_sign_arg.as_deref());
+    git_args.push("HEAD");
+    exec_git("undoing", &git_args, args)?;
+    undo::record(args)?;
+    println!(
+        "reverted {} {}, since it was pushed to {}",
+        short_hash,
+        subject,
+        pushed_to.lines().collect::<Vec<&str>>().join(", ")
+    );
+
+    if prompt(&format!(
+        "push the revert to {}/{}? [y/N]: ",
+        args.git_remote_name, args.git_upstream_branch
+    ))? == "y"
+    {
+        sync::Steps::new(args)?.push()?;
+    }
+
+    Ok(())
+}
+
+pub fn blame(args: &cli::Args, note: &std::path::PathBuf) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+
+    // Blank lines matter here, so unlike most git output, this is not trimmed.
+    let output = Command::new(GIT_CMD)
+        .arg("blame")
+        .arg("--porcelain")
+        .arg("--")
+        .arg(&note)
+        .output()
+        .context("failed to execute blaming: `git blame`")?;
+    if !output.status.success() {
+        bail!(
+            "failed to blame {}: {}",
+            note.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let lines = history::parse_blame(&String::from_utf8_lossy(&output.stdout))?;
+
+    let now = history::now();
+    let described = lines
+        .iter()
+        .map(|line| {
+            if line.hash.chars().all(|c| c == '0') {
+                ("not synced".to_string(), "you".to_string())
+            } else {
+                (
+                    history::relative_time(line.timestamp, now),
+                    line.author.clone(),
+                )
+            }
+        })
+        .collect::<Vec<(String, String)>>();
+    let date_width = described
+        .iter()
+        .map(|(date, _)| date.len())
+        .max()
+        .unwrap_or(0);
+    let author_width = described
+        .iter()
+        .map(|(_, author)| author.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (line, (date, author)) in lines.iter().zip(described) {
+        println!(
+            "{}  {:<date_width$}  {:<author_width$} | {}",
+            line.hash,
+            date,
+            author,
+            line.text,
+            date_width = date_width,
+            author_width = author_width
+        );
+    }
+
+    Ok(())
+}
+
+pub fn cat(args: &cli::Args, note: &std::path::PathBuf, render: bool) -> Result<()> {
+    let note = vault_relative_path(args, note)?;
+    let content = read_note(args, &note)?;
+    match render {
+        true => output::page(args, &render::render(&content, Styler::stdout(args))),
+        false => output::page(args, &content),
+    }
+}
+
+/// Moves the given note to the given path, rewriting the links to it, and its own relative links,
+/// to point where they should, and syncs.
+#[cfg(feature = "tui")]
+fn rename(args: &cli::Args, from: &std::path::PathBuf, to: &std::path::PathBuf) -> Result<()> {
+    let from = vault_relative_path(args, from)?;
+    let to = vault_relative_path(args, to)?;
+    if !args.base_dir.join(&from).is_file() {
+        bail!("there is no note at {}", from.display());
+    }
+    if args.base_dir.join(&to).exists() {
+        bail!("{} exists already", to.display());
+    }
+
+    let mut notes = vault::notes(&args.base_dir)?;
+    notes.push(to.clone());
+    let resolver = links::Resolver::new(&notes);
+    let moved = |note: &Path, heading: Option<&str>| {
+        (note == from).then(|| (to.clone(), heading.map(str::to_string)))
+    };
+    let content = read_note(args, &from)?;
+    write_note(args, &to, &resolver.relink(&content, &from, &to, moved))?;
+    std::fs::remove_file(args.base_dir.join(&from))
+        .context(format!("failed to delete note at {}", from.display()))?;
+    for note in vault::notes(&args.base_dir)? {
+        let content = read_note(args, &note)?;
+        let relinked = resolver.relink(&content, &note, &note, moved);
+        if relinked != content {
+            std::fs::write(args.base_dir.join(&note), relinked)
+                .context(format!("failed to write {}", note.display()))?;
+        }
+    }
+
+    sync_edits(args, None)
+}
+
+#[cfg(feature = "tui")]
+pub fn tui(args: &cli::Args) -> Result<()> {
+    let mut state = tui::State::default();
+    loop {
+        let result = match tui::browse(args, &mut state)? {
+            tui::Action::Quit => return Ok(()),
+            tui::Action::Open(note) => open_editor_at_path(&note, args, None),
+            tui::Action::New(note) => {
+                let note = match note.extension() {
+                    Some(_) => note,
+                    None => note.with_extension("md"),
+                };
+                let filepath = vault_relative_path(args, &note)?;
+                let cursor = match args.base_dir.join(&filepath).exists() {
+                    true => Ok(None),
+                    false => create_note(
+                        args,
+                        &filepath,
+                        &template::title(&filepath),
+                        None,
+                        date::Date::today()?,
+                        Vec::new(),
+                        &[],
+                    ),
+                };
+                cursor.and_then(|cursor| open_editor_at_cursor(&filepath, args, cursor, None))
+            }
+            tui::Action::Rename(from, to) => rename(args, &from, &to),
+            tui::Action::Delete(note) => std::fs::remove_file(args.base_dir.join(&note))
+                .context(format!("failed to delete note at {}", note.display()))
+                .and_then(|_| sync_edits(args, None)),
+        };
+        // Failures are shown in the browser, rather than ending it.
+        if let Err(err) = result {
+            state.status = Some(format!("error: {:#}", err));
+        }
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+pub fn tui(_: &cli::Args) -> Result<()> {
+    bail!("jot tui is unavailable, jot was built without the tui feature")
+}
+
+pub fn git_passthrough(git_args: &[String]) -> Result<()> {
+    let status = Command::new(GIT_CMD)
+        .args(git_args)
+        .status()
+        .context("failed to execute git")?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Creates a commit of the given tree with the given parent and message, returning its hash. The
+/// author and committer default to the user's, at the current time.
+fn commit_tree(
+    args: &cli::Args,
+    tree: &str,
+    parent: Option<&str>,
+    message: &str,
+    author: Option<&compact::Identity>,
+    committer: &compact::Identity,
+) -> Result<String> {
+    let mut git_commit_tree_exec = Command::new(GIT_CMD);
+    git_commit_tree_exec
+        .arg("commit-tree")
+        .args(git::gpg_sign_arg(args))
+        .arg(tree)
+        .arg("-m")
+        .arg(message);
+    if let Some(parent) = parent {
+        git_commit_tree_exec.arg("-p").arg(parent);
+    }
+    if let Some(author) = author {
+        git_commit_tree_exec
+            .env("GIT_AUTHOR_NAME", &author.name)
+            .env("GIT_AUTHOR_EMAIL", &author.email);
+    }
+    if !committer.name.is_empty() {
+        git_commit_tree_exec
+            .env("GIT_COMMITTER_NAME", &committer.name)
+            .env("GIT_COMMITTER_EMAIL", &committer.email);
+    }
+    git_commit_tree_exec
+        .env("GIT_AUTHOR_DATE", &author.unwrap_or(committer).date)
+        .env("GIT_COMMITTER_DATE", &committer.date);
+    let (hash, _) = exec_cmd(
+        "compacting",
+        git_commit_tree_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(hash)
+}
+
+/// The .gitignore that new vaults start out with, which ignores the clutter that operating systems
+/// and editors leave around.
+static STARTER_GITIGNORE: &str = ".DS_Store\nThumbs.db\n*.swp\n*~\n";
+
+pub fn init(
+    args: &cli::Args,
+    path: Option<&Path>,
+    remote: Option<&str>,
+    encrypted: bool,
+) -> Result<()> {
+    let path = path.unwrap_or(&args.base_dir);
+    if path.join(".git").exists() {
+        bail!("{} is already a git repository", path.display());
+    }
+    std::fs::create_dir_all(path).context(format!("failed to create {}", path.display()))?;
+    std::env::set_current_dir(path).context(format!(
+        "failed to change jot's working directory to {}",
+        path.display(),
+    ))?;
+
+    exec_git(
+        "initializing",
+        &[
+            "init",
+            &format!("--initial-branch={}", args.git_upstream_branch),
+        ],
+        args,
+    )?;
+    if let Some(remote) = remote {
+        exec_git(
+            "adding remote",
+            &["remote", "add", &args.git_remote_name, remote],
+            args,
+        )?;
+    }
+
+    let gitignore_path = Path::new(".gitignore");
+    if !gitignore_path.exists() {
+        std::fs::write(gitignore_path, STARTER_GITIGNORE).context("failed to write .gitignore")?;
+    }
+    merge::register_in_gitattributes(Path::new("."))?;
+    for (name, driver) in MERGE_DRIVERS {
+        register_merge_driver(args, name, driver)?;
+    }
+    if encrypted {
+        let mut git_crypt_exec = Command::new(crypt::GIT_CRYPT_CMD);
+        git_crypt_exec.arg("init");
+        exec_cmd(
+            "setting up git-crypt",
+            git_crypt_exec,
+            true,
+            args.quiet_on_ctrl_c,
+        )?;
+        let mut gitattributes =
+            std::fs::read_to_string(".gitattributes").context("failed to read .gitattributes")?;
+        for line in crypt::GITATTRIBUTES_LINES {
+            gitattributes.push_str(line);
+            gitattributes.push('\n');
+        }
+        std::fs::write(".gitattributes", gitattributes)
+            .context("failed to write .gitattributes")?;
+    }
+
+    exec_git(
+        "staging",
+        &["add", "--", ".gitignore", ".gitattributes"],
+        args,
+    )?;
+    let mut git_args = vec!["commit", "--message", "Initialize jot vault"];
+    let gpg_sign_arg = git::gpg_sign_arg(args);
+    git_args.extend(gpg_sign_arg.as_deref());
+    exec_git("committing", &git_args, args)?;
+    println!("created a vault in {}", path.display());
+    if encrypted {
+        println!(
+            "it is encrypted with git-crypt, export its key with git-crypt export-key to unlock \
+            other clones of it"
+        );
+    }
+
+    // Syncing starts by pulling, which fails until the remote has the branch.
+    if remote.is_some() {
+        exec_git(
+            "pushing",
+            &[
+                "push",
+                "--set-upstream",
+                &args.git_remote_name,
+                &args.git_upstream_branch,
+            ],
+            args,
+        )?;
+        println!(
+            "pushed it to {}/{}",
+            args.git_remote_name, args.git_upstream_branch
+        );
+    }
+
+    Ok(())
+}
+
+pub fn clone(
+    args: &cli::Args,
+    remote: &str,
+    path: Option<&Path>,
+    depth: Option<u32>,
+    blobless: bool,
+) -> Result<()> {
+    let path = path.unwrap_or(&args.base_dir);
+    // The remote and branch are named the way jot refers to them, so syncing works right away.
+    let mut git_clone_exec = Command::new(GIT_CMD);
+    git_clone_exec
+        .arg("clone")
+        .arg("--origin")
+        .arg(&args.git_remote_name)
+        .arg("--branch")
+        .arg(&args.git_upstream_branch);
+    if let Some(depth) = depth {
+        git_clone_exec.arg(format!("--depth={}", depth));
+    }
+    if blobless {
+        git_clone_exec.arg("--filter=blob:none");
+    }
+    git_clone_exec.arg(remote).arg(path);
+    exec_cmd("cloning", git_clone_exec, true, args.quiet_on_ctrl_c)?;
+    println!("cloned {} into {}", remote, path.display());
+
+    set_up_clone(args, remote, path)
+}
+
+/// Checks a vault that was just cloned from the given source into the given path, and sets up what
+/// does not come along with the clone.
+fn set_up_clone(args: &cli::Args, source: &str, path: &Path) -> Result<()> {
+    std::env::set_current_dir(path).context(format!(
+        "failed to change jot's working directory to {}",
+        path.display(),
+    ))?;
+    if vault::notes(Path::new("."))?.is_empty() {
+        println!(
+            "warning: {} has no notes, are you sure it is a jot vault?",
+            source
+        );
+    }
+
+    if crypt::is_encrypted(Path::new("."))? {
+        println!(
+            "the vault is encrypted, unlock it with git-crypt unlock <key file> before syncing"
+        );
+    }
+
+    // Unlike .gitattributes, the merge drivers it refers to are not cloned along with the vault.
+    if merge::is_registered_in_gitattributes(Path::new("."))? {
+        for (name, driver) in MERGE_DRIVERS {
+            register_merge_driver(args, name, driver)?;
+        }
+        println!("registered jot's merge drivers");
+    }
+
+    Ok(())
+}
+
+pub fn backup(args: &cli::Args, file: &Path) -> Result<()> {
+    // The bundle is written from base-dir, so relative paths are resolved before entering it.
+    let file =
+        std::path::absolute(file).context(format!("failed to resolve {}", file.display()))?;
+    std::env::set_current_dir(&args.base_dir).context(format!(
+        "failed to change jot's working directory to {}",
+        args.base_dir.display(),
+    ))?;
+
+    // A shallow clone's bundle would depend on the commits it is missing, so it could only be
+    // restored into a clone that already has them.
+    if git::is_shallow()? {
+        bail!("cannot back up a shallow clone, fetch its entire history first with git fetch --unshallow");
+    }
+
+    let mut git_bundle_exec = Command::new(GIT_CMD);
+    git_bundle_exec
+        .args(["bundle", "create", "--quiet"])
+        .arg(&file)
+        .arg("--all");
+    exec_cmd("bundling", git_bundle_exec, true, args.quiet_on_ctrl_c)?;
+    println!(
+        "backed up {} to {}",
+        args.base_dir.display(),
+        file.display()
+    );
+
+    if lfs::is_used(Path::new("."))? {
+        println!("warning: the contents of files tracked by Git LFS are not part of the backup");
+    }
+
+    Ok(())
+}
+
+pub fn restore_backup(args: &cli::Args, file: &Path, path: &Path) -> Result<()> {
+    let mut git_clone_exec = Command::new(GIT_CMD);
+    git_clone_exec
+        .arg("clone")
+        .arg("--branch")
+        .arg(&args.git_upstream_branch)
+        .arg(file)
+        .arg(path);
+    exec_cmd("restoring", git_clone_exec, true, args.quiet_on_ctrl_c)?;
+
+    // Cloning makes the bundle the vault's origin, which it is not.
+    let mut git_remote_exec = Command::new(GIT_CMD);
+    git_remote_exec
+        .current_dir(path)
+        .args(["remote", "remove", "origin"]);
+    exec_cmd(
+        "removing the bundle remote",
+        git_remote_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    println!("restored {} into {}", file.display(), path.display());
+
+    set_up_clone(args, &file.display().to_string(), path)?;
+    println!(
+        "add the vault's remote with jot git remote add {} <url> to sync it",
+        args.git_remote_name
+    );
+
+    Ok(())
+}
+
+/// Returns how many KiB git's objects take up, loose or packed, garbage included.
+fn repository_size(args: &cli::Args) -> Result<u64> {
+    let counts = exec_git("measuring the repository", &["count-objects", "-v"], args)?;
+    Ok(counts
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .filter(|(key, _)| matches!(*key, "size" | "size-pack" | "size-garbage"))
+        .filter_map(|(_, kib)| kib.parse::<u64>().ok())
+        .sum())
+}
+
+/// Formats the given number of KiB with whichever unit suits it best, e.g. `3.2 MiB`.
+fn format_kib(kib: u64) -> String {
+    match kib {
+        0..=1023 => format!("{} KiB", kib),
+        1024..=1_048_575 => format!("{:.1} MiB", kib as f64 / 1024.0),
+        _ => format!("{:.1} GiB", kib as f64 / 1_048_576.0),
+    }
+}
+
+pub fn daemon(args: &cli::Args, debounce_secs: u64, interval_mins: u64) -> Result<()> {
+    daemon::run(
+        args,
+        Duration::from_secs(debounce_secs),
+        Duration::from_secs(interval_mins * 60),
+        true,
+    )
+}
+
+pub fn install_daemon(args: &cli::Args) -> Result<()> {
+    service::install(args)
+}
+
+pub fn gc(args: &cli::Args, aggressive: bool) -> Result<()> {
+    let before = repository_size(args)?;
+
+    // Reflog entries keep whatever they point to from being pruned, long after it was replaced.
+    // Those older than git's gc.reflogExpire (90 days by default) are expired.
+    exec_git("expiring reflogs", &["reflog", "expire", "--all"], args)?;
+    let mut git_gc_args = vec!["gc", "--quiet"];
+    if aggressive {
+        git_gc_args.push("--aggressive");
+    }
+    println!("collecting garbage, this may take a while");
+    exec_git("collecting garbage", &git_gc_args, args)?;
+
+    let after = repository_size(args)?;
+    println!(
+        "reclaimed {} ({} to {})",
+        format_kib(before.saturating_sub(after)),
+        format_kib(before),
+        format_kib(after)
+    );
+
+    Ok(())
+}
+
+pub fn compact(
+    args: &cli::Args,
+    keep_days: u64,
+    period: cli::Period,
+    force_push: bool,
+    branch: Option<&str>,
+) -> Result<()> {
+    // The compacted history would start at the oldest commit we have, so force-pushing it would
+    // throw away whatever history a shallow clone is missing.
+    if git::is_shallow()? {
+        bail!("cannot compact a shallow clone, fetch its entire history first with git fetch --unshallow");
+    }
+
+    // Merges are flattened along their first parent, since their other parents lead back into the
+    // history that is being squashed.
+    let log = exec_git(
+        "reading history",
+        &[
+            "log",
+            "--first-parent",
+            "--reverse",
+            "--date=raw",
+            compact::LOG_FORMAT,
+            "HEAD",
+        ],
+        args,
+    )?;
+    let commits = compact::parse(&log)?;
+    let cutoff = history::now().saturating_sub(keep_days * 24 * 60 * 60);
+    let (rollups, recent) = compact::rollups(&commits, cutoff, period);
+    let squashed = rollups
+        .iter()
+        .map(|rollup| rollup.commits.len())
+        .sum::<usize>();
+    if squashed == rollups.len() {
+        println!("nothing to compact");
+        return Ok(());
+    }
+
+    println!(
+        "{} commit(s) older than {} days squash into {} rollup(s), {} recent commit(s) are kept",
+        squashed,
+        keep_days,
+        rollups.len(),
+        recent.len()
+    );
+    if !force_push && branch.is_none() {
+        println!("run again with --force-push or --branch to compact");
+        return Ok(());
+    }
+
+    let mut parent: Option<String> = None;
+    for rollup in &rollups {
+        let last = &rollup.commits[rollup.commits.len() - 1];
+        let committer = compact::Identity {
+            name: String::new(),
+            email: String::new(),
+            date: last.committer.date.clone(),
+        };
+        let message = format!(
+            "jot: rollup of {} commit(s) from {}",
+            rollup.commits.len(),
+            rollup.period
+        );
+        parent = Some(commit_tree(
+            args,
+            &last.tree,
+            parent.as_deref(),
+            &message,
+            None,
+            &committer,
+        )?);
+    }
+    for commit in recent {
+        parent = Some(commit_tree(
+            args,
+            &commit.tree,
+            parent.as_deref(),
+            &commit.message,
+            Some(&commit.author),
+            &commit.committer,
+        )?);
+    }
+    let Some(compacted) = parent else {
+        bail!("compacting produced no commits");
+    };
+
+    exec_git(
+        "backing up history",
+        &["update-ref", "refs/jot/before-compact", "HEAD"],
+        args,
+    )?;
+    if let Some(branch) = branch {
+        exec_git("creating branch", &["branch", branch, &compacted], args)?;
+        println!("created {} with the compacted history", branch);
+        return Ok(());
+    }
+
+    // The compacted history ends in the same tree as the current one, so nothing is checked out.
+    exec_git("compacting", &["reset", "--soft", &compacted], args)?;
+    exec_git(
+        "force-pushing",
+        &[
+            "push",
+            "--force-with-lease",
+            &args.git_remote_name,
+            &format!("HEAD:{}", args.git_upstream_branch),
+        ],
+        args,
+    )?;
+    println!(
+        "force-pushed the compacted history, reset every other clone to {}/{} before syncing it",
+        args.git_remote_name, args.git_upstream_branch
+    );
+
+    Ok(())
+}
+
+pub fn random(
+    args: &cli::Args,
+    subpath: Option<&std::path::PathBuf>,
+    tag: Option<&str>,
+    print: bool,
+    print0: bool,
+) -> Result<()> {
+    let subtree = subpath
+        .map(|path| vault_relative_path(args, path))
+        .transpose()?;
+
+    let mut candidates = Vec::new();
+    for note in vault::notes(&args.base_dir)? {
+        if subtree
+            .as_ref()
+            .is_some_and(|subtree| !note.starts_with(subtree))
+        {
+            continue;
+        }
+        if let Some(tag) = tag {
+            if !frontmatter::tags(&read_note(args, &note)?)
+                .iter()
+                .any(|note_tag| note_tag == tag.trim_start_matches('#'))
+            {
+                continue;
+            }
+        }
+        candidates.push(note);
+    }
+
+    if candidates.is_empty() {
+        bail!("found no notes to pick from")
+    }
+
+    // We don't need anything fancy here, and the standard library's RandomState is already seeded
+    // randomly, so we can get a good enough random number out of it without pulling in a crate.
+    let random = RandomState::new().build_hasher().finish();
+    let note = &candidates[(random % candidates.len() as u64) as usize];
+
+    if print {
+        print!("{}", output::paths([note], print0));
+        return Ok(());
+    }
+
+    open_editor_at_path(note, args, None)
+}
+
+pub fn dedupe(args: &cli::Args, fuzzy: Option<f64>, interactive: bool) -> Result<()> {
+    let notes = vault::notes(&args.base_dir)?;
+    let duplicates = dedupe::find(&args.base_dir, &notes, fuzzy)?;
+
+    let mut removed = BTreeSet::new();
+    for duplicate in duplicates {
+        let (first, second) = (&duplicate.first, &duplicate.second);
+        if removed.contains(first) || removed.contains(second) {
+            continue;
+        }
+
+        if duplicate.similarity >= 1.0 {
+            println!("{} == {}", first.display(), second.display());
+        } else {
+            println!(
+                "{} ~= {} ({:.0}%)",
+                first.display(),
+                second.display(),
+                duplicate.similarity * 100.0
+            );
+        }
+
+        if !interactive {
+            continue;
+        }
+
+        let answer = prompt(&format!(
+            "  [1] delete {0}, [2] delete {1}, [m] merge {1} into {0}, [s] skip: ",
+            first.display(),
+            second.display()
+        ))?;
+        let (keep, remove) = match answer.as_str() {
+            "1" => (second, first),
+            "2" | "m" => (first, second),
+            _ => continue,
+        };
+        if answer == "m" {
+            let merged = format!(
+                "{}\n\n{}",
+                read_note(args, keep)?.trim_end(),
+                read_note(args, remove)?
+            );
+            std::fs::write(args.base_dir.join(keep), merged)
+                .context(format!("failed to write note at {}", keep.display()))?;
+        }
+        std::fs::remove_file(args.base_dir.join(remove))
+            .context(format!("failed to delete note at {}", remove.display()))?;
+        removed.insert(remove.clone());
+    }
+
+    if !removed.is_empty() {
+        sync_edits(args, None)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the two given sides of a conflict next to each other, each under its label.
+fn print_conflict((left_label, left): (&str, &[&str]), (right_label, right): (&str, &[&str])) {
+    let width = var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80);
+    let underline = |label: &str| format!("{}\n", "-".repeat(label.len()));
+    println!();
+    print!(
+        "{}",
+        conflict::side_by_side(
+            &[&format!("{}\n", left_label), &underline(left_label)],
+            &[&format!("{}\n", right_label), &underline(right_label)],
+            width
+        )
+    );
+    print!("{}", conflict::side_by_side(left, right, width));
+}
+
+/// Walks the user through the given conflicted files one by one, showing the local and remote
+/// sides of each conflict next to each other and asking which one to keep.
+fn resolve_conflicts(
+    args: &cli::Args,
+    git: &dyn crate::git::Backend,
+    conflicted: &[std::path::PathBuf],
+) -> Result<()> {
+    // When merging, ours is the local side. When rebasing, or when restoring local changes that
+    // were stashed while pulling, upstream changes have already been applied, so ours is remote.
+    let local = match git.operation_in_progress()? {
+        Some(Operation::Merge) => conflict::Side::Ours,
+        _ => conflict::Side::Theirs,
+    };
+    for path in conflicted {
+        let content = read_note(args, path)?;
+        let regions = conflict::parse(&content);
+        let conflicts = regions
+            .iter()
+            .filter_map(|region| match region {
+                conflict::Region::Conflict { ours, theirs } => Some((ours, theirs)),
+                conflict::Region::Common(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        println!("\n=== {} ({} conflict(s))", path.display(), conflicts.len());
+        if conflicts.is_empty() {
+            // E.g. the note was deleted on one side and edited on the other.
+            println!("no conflict markers, the note can only be edited by hand");
+        }
+        for (ours, theirs) in &conflicts {
+            let (local_lines, remote_lines) = match local {
+                conflict::Side::Ours => (ours, theirs),
+                conflict::Side::Theirs => (theirs, ours),
+            };
+            print_conflict(("local", local_lines), ("remote", remote_lines));
+        }
+        println!();
+
+        let answer = match conflicts.is_empty() {
+            true => prompt_choice("[e] edit by hand, [s] skip: ", &["e", "s"])?,
+            false => prompt_choice(
+                "[l] keep local, [r] keep remote, [e] edit by hand, [s] skip: ",
+                &["l", "r", "e", "s"],
+            )?,
+        };
+        let side = match answer.as_deref() {
+            Some("l") => local,
+            Some("r") => match local {
+                conflict::Side::Ours => conflict::Side::Theirs,
+                conflict::Side::Theirs => conflict::Side::Ours,
+            },
+            Some("e") => {
+                open_editor(path, args, None)?;
+                continue;
+            }
+            Some("s") => continue,
+            // No answer was given, e.g. because stdin is closed, so the rest are left as they are.
+            _ => break,
+        };
+        std::fs::write(args.base_dir.join(path), conflict::resolve(&content, side))
+            .context(format!("failed to write note at {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Like conflicts, but for the conflict copies Syncthing keeps of files changed on two devices at
+/// once. Copies of notes are merged into the notes, leaving any conflicts between them in the notes
+/// themselves.
+fn syncthing_conflicts(
+    args: &cli::Args,
+    list: bool,
+    print0: bool,
+    interactive: bool,
+    finish: bool,
+) -> Result<()> {
+    if finish {
+        // There is no sync to finish, only notes to check.
+        let unresolved = vault::notes(Path::new("."))?
+            .into_iter()
+            .filter(|path| conflict::has_markers(path))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<String>>();
+        if !unresolved.is_empty() {
+            bail!(
+                "the following note(s) still have conflict markers, please resolve them:\n\t! {}",
+                unresolved.join("\n\t! ")
+            );
+        }
+        println!("no conflicts");
+        return Ok(());
+    }
+
+    let copies = syncthing::conflict_copies()?;
+    if print0 {
+        print!(
+            "{}",
+            output::paths(copies.iter().map(|(_, copy)| copy), true)
+        );
+        return Ok(());
+    }
+    if copies.is_empty() {
+        println!("no conflicts");
+        return Ok(());
+    }
+
+    for (original, copy) in &copies {
+        println!(
+            "{} (a conflict copy of {})",
+            copy.display(),
+            original.display()
+        );
+    }
+    if list {
+        return Ok(());
+    }
+
+    let mut left_conflicts = false;
+    for (original, copy) in &copies {
+        let Some((merged, conflicts)) = syncthing::merge(original, copy)? else {
+            println!(
+                "warning: {} is not a note, so it cannot be merged, keep whichever of it and {} \
+                is right by hand",
+                copy.display(),
+                original.display()
+            );
+            continue;
+        };
+        if conflicts == 0 {
+            syncthing::resolve(original, copy, &merged)?;
+            println!("merged {} into {}", copy.display(), original.display());
+            continue;
+        }
+        if !interactive {
+            syncthing::resolve(original, copy, &merged)?;
+            open_editor(original, args, None)?;
+            left_conflicts = true;
+            continue;
+        }
+
+        println!("\n=== {} ({} conflict(s))", original.display(), conflicts);
+        for region in conflict::parse(&merged) {
+            if let conflict::Region::Conflict { ours, theirs } = region {
+                print_conflict(("note", &ours), ("conflict copy", &theirs));
+            }
+        }
+        println!();
+        let answer = prompt_choice(
+            "[n] keep note, [c] keep conflict copy, [e] edit by hand, [s] skip: ",
+            &["n", "c", "e", "s"],
+        )?;
+        let side = match answer.as_deref() {
+            Some("n") => conflict::Side::Ours,
+            Some("c") => conflict::Side::Theirs,
+            Some("e") => {
+                syncthing::resolve(original, copy, &merged)?;
+                open_editor(original, args, None)?;
+                left_conflicts = true;
+                continue;
+            }
+            Some("s") => continue,
+            // No answer was given, e.g. because stdin is closed, so the rest are left as they are.
+            _ => break,
+        };
+        syncthing::resolve(original, copy, &conflict::resolve(&merged, side))?;
+    }
+    if left_conflicts {
+        println!("once all conflicts are resolved, run jot conflicts --continue to check that none are left");
+    }
+
+    Ok(())
+}
+
+pub fn conflicts(
+    args: &cli::Args,
+    list: bool,
+    print0: bool,
+    interactive: bool,
+    finish: bool,
+) -> Result<()> {
+    if matches!(args.sync_backend, cli::SyncBackend::Syncthing) {
+        return syncthing_conflicts(args, list, print0, interactive, finish);
+    }
+
+    let steps = sync::Steps::new(args)?;
+    let git = steps.git();
+    let conflicted = git.conflicted_files()?;
+
+    if finish {
+        let unresolved = conflicted
+            .iter()
+            .filter(|path| conflict::has_markers(&args.base_dir.join(path)))
+            .collect::<Vec<&std::path::PathBuf>>();
+        if !unresolved.is_empty() {
+            bail!(
+                "the following file(s) still have conflict markers, please resolve them and run \
+                jot conflicts --continue:\n\t! {}",
+                unresolved
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n\t! ")
+            );
+        }
+
+        git.stage(&conflicted)?;
+        match git.operation_in_progress()? {
+            Some(operation) => git.continue_operation(operation)?,
+            // Without a merge or rebase in progress, the conflicts came from restoring the local
+            // changes stashed while pulling. Git applies them anyway, but keeps them stashed too.
+            None if !conflicted.is_empty() && git.has_autostash()? => git.drop_stash()?,
+            None => {}
+        }
+
+        // If the pull that conflicted had stashed local changes, they could not have been restored
+        // at the time, so now is the time to do it, and to commit them.
+        if git.has_autostash()? {
+            git.unstash().context(
+                "failed to restore local changes, they are still saved in git's stash (see git \
+                stash list)",
+            )?;
+        }
+        if steps.has_changes()? {
+            steps.commit(Some(&steps.default_message()?))?;
+        }
+
+        return steps.push();
+    }
+
+    if print0 {
+        print!("{}", output::paths(&conflicted, true));
+        return Ok(());
+    }
+    if conflicted.is_empty() {
+        println!("no conflicts");
+        return Ok(());
+    }
+
+    print!("{}", output::paths(&conflicted, false));
+    if list {
+        return Ok(());
+    }
+
+    if interactive {
+        resolve_conflicts(args, git, &conflicted)?;
+    } else {
+        for path in &conflicted {
+            open_editor(path, args, None)?;
+        }
+    }
+    println!("once all conflicts are resolved, run jot conflicts --continue to finish the sync");
+
+    Ok(())
+}
+
+pub fn merge_file(base: &Path, ours: &Path, theirs: &Path, union: bool) -> Result<()> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).context(format!("failed to read {}", path.display()))
+    };
+    let (merged, conflicts) = merge::merge(&read(base)?, &read(ours)?, &read(theirs)?, union);
+    std::fs::write(ours, merged).context(format!("failed to write {}", ours.display()))?;
+    if conflicts > 0 {
+        bail!("{} conflict(s) could not be merged", conflicts);
+    }
+
+    Ok(())
+}
+
+/// Returns the value of the given git config key, or None if it is unset.
+fn git_config(key: &str) -> Result<Option<String>> {
+    let output = Command::new(GIT_CMD)
+        .args(["config", "--get", key])
+        .output()
+        .context(format!("failed to read git config {}", key))?;
+    // git config exits with 1 when the key is unset, and anything else on actual errors.
+    match output.status.code() {
+        Some(0) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        )),
+        Some(1) => Ok(None),
+        _ => bail!(
+            "failed to read git config {}: {}",
+            key,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// The names of jot's merge drivers, and the commands git runs them with. Git runs merge drivers
+/// from the root of the repository, and merge-file does not use the finder, though it is still
+/// required.
+static MERGE_DRIVERS: [(&str, &str); 2] = [
+    (merge::DRIVER_NAME, "jot -b . -f true merge-file %O %A %B"),
+    (
+        merge::UNION_DRIVER_NAME,
+        "jot -b . -f true merge-file --union %O %A %B",
+    ),
+];
+
+fn register_merge_driver(args: &cli::Args, name: &str, driver: &str) -> Result<()> {
+    exec_git(
+        "registering merge driver",
+        &[
+            "config",
+            &format!("merge.{}.name", name),
+            "jot Markdown merge",
+        ],
+        args,
+    )?;
+    exec_git(
+        "registering merge driver",
+        &["config", &format!("merge.{}.driver", name), driver],
+        args,
+    )?;
+    Ok(())
+}
+
+pub fn doctor(args: &cli::Args) -> Result<()> {
+    let mut problems = 0;
+
+    // Merge drivers are configured per clone, since git's config is never synced.
+    for (name, driver) in MERGE_DRIVERS {
+        if git_config(&format!("merge.{}.driver", name))?.as_deref() == Some(driver) {
+            continue;
+        }
+        problems += 1;
+        println!(
+            "the {} merge driver is not registered in the git config",
+            name
+        );
+        if prompt("  register it? [y/N]: ")? == "y" {
+            register_merge_driver(args, name, driver)?;
+            problems -= 1;
+        }
+    }
+
+    if !merge::is_registered_in_gitattributes(&args.base_dir)? {
+        problems += 1;
+        println!("notes do not use the jot merge driver in .gitattributes");
+        if prompt("  add it (this is synced to other devices)? [y/N]: ")? == "y" {
+            merge::register_in_gitattributes(&args.base_dir)?;
+            sync(
+                args,
+                &cli::SyncArgs {
+                    message: Some("Use jot's merge driver for notes".to_string()),
+                    ..Default::default()
+                },
+            )?;
+            problems -= 1;
+        }
+    }
+
+    if problems > 0 {
+        bail!("found {} problem(s)", problems);
+    }
+    println!("no problems found");
+
+    Ok(())
+}
+
+pub fn fsck(args: &cli::Args) -> Result<()> {
+    let steps = sync::Steps::new(args)?;
+    let git = steps.git();
+    let mut problems = 0;
+    let mut report = |problem: String, fix: &str| {
+        problems += 1;
+        println!("{}\n  {}", problem, fix);
+    };
+
+    let index_lock = Path::new(&exec_git(
+        "locating .git",
+        &["rev-parse", "--git-dir"],
+        args,
+    )?)
+    .join("index.lock");
+    if index_lock.exists() {
+        report(
+            format!("git's index is locked, {} exists", index_lock.display()),
+            "if no git process is running (e.g. one that crashed left it behind), delete it",
+        );
+    }
+
+    let operation = git.operation_in_progress()?;
+    match operation {
+        Some(Operation::Merge) => report(
+            "a merge is in progress".to_string(),
+            "resolve its conflicts and finish it with jot conflicts, or abort it with jot git \
+            merge --abort",
+        ),
+        Some(Operation::Rebase) => report(
+            "a rebase is in progress".to_string(),
+            "resolve its conflicts and finish it with jot conflicts, or abort it with jot git \
+            rebase --abort",
+        ),
+        None => {}
+    }
+
+    // Rebases detach HEAD while they are in progress, which is already reported above.
+    let branch = steps.branch();
+    let head = Command::new(GIT_CMD)
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .output()
+        .context("failed to determine the current branch")?;
+    let current_branch = String::from_utf8_lossy(&head.stdout).trim().to_string();
+    if operation.is_none() && !head.status.success() {
+        report(
+            "HEAD is detached, i.e. not on any branch".to_string(),
+            &format!("check out {} with jot git switch {}", branch, branch),
+        );
+    } else if operation.is_none() && current_branch != branch {
+        report(
+            format!("{} is checked out, rather than {}", current_branch, branch),
+            &format!(
+                "check out {} with jot git switch {}, or set the git-upstream-branch flag",
+                branch, branch
+            ),
+        );
+    }
+
+    let changed = exec_git(
+        "listing changes",
+        &["diff-index", "--name-only", "HEAD", "--"],
+        args,
+    )?;
+    if !changed.is_empty() && operation.is_none() {
+        report(
+            format!(
+                "base-dir has uncommitted changes to tracked files:\n\t{}",
+                changed.lines().collect::<Vec<&str>>().join("\n\t")
+            ),
+            "commit them with jot git commit --all, or set them aside with jot git stash",
+        );
+    }
+    if git.has_autostash()? {
+        report(
+            "local changes from a sync that failed are still stashed".to_string(),
+            "restore them with jot git stash pop",
+        );
+    }
+
+    let tracked = exec_git("listing notes", &["ls-files", "-z"], args)?;
+    let with_markers = tracked
+        .split('\0')
+        .map(Path::new)
+        .filter(|path| vault::is_note(path) && conflict::has_markers(path))
+        .map(|path| path.display().to_string())
+        .collect::<Vec<String>>();
+    if !with_markers.is_empty() {
+        report(
+            format!(
+                "notes still contain conflict markers:\n\t{}",
+                with_markers.join("\n\t")
+            ),
+            "edit them to keep what you want, and remove the markers",
+        );
+    }
+
+    if problems > 0 {
+        bail!("found {} problem(s)", problems);
+    }
+    println!("no problems found");
+
+    Ok(())
+}
+
+pub fn status(args: &cli::Args, fetch: bool) -> Result<()> {
+    let steps = sync::Steps::new(args)?;
+    let git = steps.git();
+    let (remote, branch) = (&args.git_remote_name, &args.git_upstream_branch);
+
+    if fetch {
+        git.fetch(remote, branch)?;
+    }
+    match git.ahead_behind(remote, branch)? {
+        Some((0, 0)) => println!("up to date with {}/{}", remote, branch),
+        Some((ahead, behind)) => println!(
+            "{} commit(s) ahead of and {} behind {}/{}",
+            ahead, behind, remote, branch
+        ),
+        None if !git::has_remote(remote)? => println!("there is no remote named {}", remote),
+        None => println!("{}/{} has never been fetched", remote, branch),
+    }
+    if !fetch {
+        println!("(as of the last sync, use --fetch to check again)");
+    }
+
+    let conflicted = git.conflicted_files()?;
+    match git.operation_in_progress()? {
+        Some(operation) => println!(
+            "\nan unfinished {} is in progress",
+            match operation {
+                Operation::Merge => "merge",
+                Operation::Rebase => "rebase",
+            }
+        ),
+        None if !conflicted.is_empty() => {
+            println!("\nrestoring local changes after pulling left conflicts")
+        }
+        None => {}
+    }
+    if !conflicted.is_empty() {
+        println!("conflicts (resolve them with jot conflicts):");
+        for path in &conflicted {
+            println!("\t{}", path.display());
+        }
+    }
+    if git.has_autostash()? {
+        println!("\nlocal changes from an interrupted sync are still stashed (see git stash list)");
+    }
+
+    daemon::print_status()?;
+
+    let ignore = Ignore::load(&args.base_dir, &args.ignore)?;
+    let diffs = git.diff_stats(&git.changed_files()?)?;
+    if diffs.is_empty() {
+        println!("\nno local changes");
+        return Ok(());
+    }
+    println!("\nlocal changes:");
+    for diff in diffs {
+        let kind = match diff.kind {
+            ChangeKind::Add => "new",
+            ChangeKind::Edit => "modified",
+            ChangeKind::Delete => "deleted",
+        };
+        let lines = match (diff.kind, diff.lines) {
+            (ChangeKind::Delete, _) | (_, None) => String::new(),
+            (ChangeKind::Add, Some((insertions, _))) => format!(" (+{})", insertions),
+            (ChangeKind::Edit, Some((insertions, deletions))) => {
+                format!(" (+{} -{})", insertions, deletions)
+            }
+        };
+        let ignored = if ignore.is_ignored(&diff.path) {
+            " [ignored, never synced]"
+        } else {
+            ""
+        };
+        println!("\t{:<9}{}{}{}", kind, diff.path.display(), lines, ignored);
     }
-    exec_cmd("committing", git_commit_exec, true, args.quiet_on_ctrl_c)?;
 
-    // Fourth, push to upstream to finish the sync.
-    let mut git_push_exec = Command::new(GIT_CMD);
-    git_push_exec
-        .arg("push")
-        .arg(&args.git_remote_name)
-        .arg(&args.git_upstream_branch);
-    exec_cmd("pushing", git_push_exec, true, args.quiet_on_ctrl_c)
-        .context("failed to push to upstream, please fix the issue and run jot sync")?;
     Ok(())
 }
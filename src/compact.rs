@@ -0,0 +1,165 @@
+use anyhow::{bail, Result};
+use humantime::format_rfc3339_seconds;
+
+use crate::cli;
+
+/// The git log format that parse understands: NUL-separated fields, with commits separated by
+/// \x01 since messages may span multiple lines. Dates must be formatted with --date=raw.
+pub static LOG_FORMAT: &str =
+    "--format=%H%x00%T%x00%an%x00%ae%x00%ad%x00%cn%x00%ce%x00%cd%x00%B%x01";
+
+/// Who made a commit, and when, in git's raw date format (`<seconds since epoch> <timezone>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+    pub date: String,
+}
+
+impl Identity {
+    fn timestamp(&self) -> u64 {
+        self.date
+            .split_whitespace()
+            .next()
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub tree: String,
+    pub author: Identity,
+    pub committer: Identity,
+    pub message: String,
+}
+
+impl Commit {
+    pub fn timestamp(&self) -> u64 {
+        self.committer.timestamp()
+    }
+}
+
+/// Parses the output of git log with LOG_FORMAT.
+pub fn parse(log: &str) -> Result<Vec<Commit>> {
+    log.split('\x01')
+        .map(|record| record.trim_start_matches('\n'))
+        .filter(|record| !record.is_empty())
+        .map(|record| {
+            let fields = record.splitn(9, '\0').collect::<Vec<&str>>();
+            let [hash, tree, author_name, author_email, author_date, committer_name, committer_email, committer_date, message] =
+                fields[..]
+            else {
+                bail!("unexpected commit in git log output: {}", record);
+            };
+            let identity = |name: &str, email: &str, date: &str| Identity {
+                name: name.to_string(),
+                email: email.to_string(),
+                date: date.to_string(),
+            };
+            Ok(Commit {
+                hash: hash.to_string(),
+                tree: tree.to_string(),
+                author: identity(author_name, author_email, author_date),
+                committer: identity(committer_name, committer_email, committer_date),
+                message: message.trim_end().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the (UTC) period that the given timestamp falls in, e.g. `2024-01` for months, or
+/// `week of 2024-01-29` for weeks, which start on Mondays.
+pub fn period(timestamp: u64, period: cli::Period) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    let date = |timestamp: u64| {
+        format_rfc3339_seconds(std::time::UNIX_EPOCH + std::time::Duration::from_secs(timestamp))
+            .to_string()
+    };
+    match period {
+        cli::Period::Day => date(timestamp)[..10].to_string(),
+        cli::Period::Week => {
+            // The Unix epoch was a Thursday, three days after a Monday.
+            let days = timestamp / DAY;
+            let monday = days.saturating_sub((days + 3) % 7) * DAY;
+            format!("week of {}", &date(monday)[..10])
+        }
+        cli::Period::Month => date(timestamp)[..7].to_string(),
+    }
+}
+
+/// A run of consecutive commits in the same period, to be squashed into one.
+pub struct Rollup<'a> {
+    pub period: String,
+    pub commits: &'a [Commit],
+}
+
+/// Splits the given commits (oldest first) into rollups of those made before the given cutoff, and
+/// the rest, which are kept as they are.
+pub fn rollups(
+    commits: &[Commit],
+    cutoff: u64,
+    period_kind: cli::Period,
+) -> (Vec<Rollup<'_>>, &[Commit]) {
+    let old_count = commits
+        .iter()
+        .rposition(|commit| commit.timestamp() < cutoff)
+        .map_or(0, |idx| idx + 1);
+    let (old, recent) = commits.split_at(old_count);
+
+    let mut rollups: Vec<Rollup> = Vec::new();
+    let mut start = 0;
+    for idx in 1..=old.len() {
+        let period_at = |idx: usize| period(old[idx].timestamp(), period_kind);
+        if idx == old.len() || period_at(idx) != period_at(start) {
+            rollups.push(Rollup {
+                period: period_at(start),
+                commits: &old[start..idx],
+            });
+            start = idx;
+        }
+    }
+    (rollups, recent)
+}
+
+#[test]
+fn compact_periods() {
+    // 2024-01-31T12:00:00Z, a Wednesday.
+    let timestamp = 1_706_702_400;
+    assert_eq!(period(timestamp, cli::Period::Day), "2024-01-31");
+    assert_eq!(period(timestamp, cli::Period::Week), "week of 2024-01-29");
+    assert_eq!(period(timestamp, cli::Period::Month), "2024-01");
+
+    let commit = |timestamp: u64| Commit {
+        hash: timestamp.to_string(),
+        tree: String::new(),
+        author: Identity {
+            name: String::new(),
+            email: String::new(),
+            date: String::new(),
+        },
+        committer: Identity {
+            name: String::new(),
+            email: String::new(),
+            date: format!("{} +0000", timestamp),
+        },
+        message: String::new(),
+    };
+    let day = 24 * 60 * 60;
+    let commits = [
+        commit(timestamp),
+        commit(timestamp + 1),
+        commit(timestamp + day),
+        commit(timestamp + 10 * day),
+    ];
+    let (rollups, recent) = rollups(&commits, timestamp + 5 * day, cli::Period::Day);
+    assert_eq!(
+        rollups
+            .iter()
+            .map(|rollup| (rollup.period.as_str(), rollup.commits.len()))
+            .collect::<Vec<_>>(),
+        vec![("2024-01-31", 2), ("2024-02-01", 1)]
+    );
+    assert_eq!(recent.len(), 1);
+}
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+/// One side of a conflict, named the way git names them: ours is the part between `<<<<<<<` and
+/// `=======`, theirs is the part between `=======` and `>>>>>>>`. Which of them is the local side
+/// depends on how the conflict came about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+/// A region of a conflicted file: either lines that both sides agree on, or a conflict between
+/// them. Lines keep their line endings.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Region<'a> {
+    Common(Vec<&'a str>),
+    Conflict {
+        ours: Vec<&'a str>,
+        theirs: Vec<&'a str>,
+    },
+}
+
+enum State {
+    Common,
+    Ours,
+    Base,
+    Theirs,
+}
+
+/// Splits the given file contents into regions along git's conflict markers. The base section of
+/// diff3-style conflicts (`|||||||`) is dropped.
+pub fn parse(content: &str) -> Vec<Region<'_>> {
+    let mut regions = Vec::new();
+    let mut state = State::Common;
+    let (mut common, mut ours, mut theirs) = (Vec::new(), Vec::new(), Vec::new());
+    for line in content.split_inclusive('\n') {
+        let marker = line.trim_end();
+        match state {
+            State::Common if marker.starts_with("<<<<<<<") => {
+                if !common.is_empty() {
+                    regions.push(Region::Common(std::mem::take(&mut common)));
+                }
+                state = State::Ours;
+            }
+            State::Ours if marker.starts_with("|||||||") => state = State::Base,
+            State::Ours | State::Base if marker == "=======" => state = State::Theirs,
+            State::Theirs if marker.starts_with(">>>>>>>") => {
+                regions.push(Region::Conflict {
+                    ours: std::mem::take(&mut ours),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                state = State::Common;
+            }
+            State::Common => common.push(line),
+            State::Ours => ours.push(line),
+            State::Base => {}
+            State::Theirs => theirs.push(line),
+        }
+    }
+    if !common.is_empty() {
+        regions.push(Region::Common(common));
+    }
+
+    regions
+}
+
+/// Resolves every conflict in the given file contents in favor of the given side.
+pub fn resolve(content: &str, side: Side) -> String {
+    parse(content)
+        .into_iter()
+        .flat_map(|region| match region {
+            Region::Common(lines) => lines,
+            Region::Conflict { ours, .. } if side == Side::Ours => ours,
+            Region::Conflict { theirs, .. } => theirs,
+        })
+        .collect()
+}
+
+/// Returns true if the given file contains git's conflict markers. Files that cannot be read (e.g.
+/// because they were deleted) are considered to have no markers.
+pub fn has_markers(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| {
+        parse(&content)
+            .iter()
+            .any(|region| matches!(region, Region::Conflict { .. }))
+    })
+}
+
+/// Renders the given lines in two columns that fit in the given width, wrapping long lines.
+pub fn side_by_side(left: &[&str], right: &[&str], width: usize) -> String {
+    fn wrap(lines: &[&str], width: usize) -> Vec<String> {
+        lines
+            .iter()
+            .flat_map(|line| {
+                let chars = line.trim_end().chars().collect::<Vec<char>>();
+                if chars.is_empty() {
+                    return vec![String::new()];
+                }
+                chars
+                    .chunks(width)
+                    .map(|chunk| chunk.iter().collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    let column_width = (width.saturating_sub(3) / 2).max(10);
+    let (left, right) = (wrap(left, column_width), wrap(right, column_width));
+    let mut rendered = String::new();
+    for idx in 0..left.len().max(right.len()) {
+        let left_line = left.get(idx).map_or("", String::as_str);
+        let right_line = right.get(idx).map_or("", String::as_str);
+        rendered.push_str(
+            format!(
+                "{:<width$} | {}",
+                left_line,
+                right_line,
+                width = column_width
+            )
+            .trim_end(),
+        );
+        rendered.push('\n');
+    }
+    rendered
+}
+
+#[test]
+fn resolve_conflicts() {
+    let content =
+        "a\n<<<<<<< HEAD\nmine\n||||||| base\nold\n=======\nyours\nyours too\n>>>>>>> origin\nb\n";
+    assert_eq!(resolve(content, Side::Ours), "a\nmine\nb\n");
+    assert_eq!(resolve(content, Side::Theirs), "a\nyours\nyours too\nb\n");
+    assert_eq!(
+        side_by_side(&["mine\n"], &["yours\n", "yours too\n"], 25),
+        "mine        | yours\n            | yours too\n"
+    );
+}
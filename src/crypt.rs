@@ -0,0 +1,42 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use crate::git::GIT_CMD;
+
+/// The command that encrypted vaults are set up with.
+pub static GIT_CRYPT_CMD: &str = "git-crypt";
+
+/// The .gitattributes lines that make git-crypt encrypt everything, save for the files git needs
+/// to read before it can decrypt anything.
+pub static GITATTRIBUTES_LINES: [&str; 3] = [
+    "* filter=git-crypt diff=git-crypt",
+    ".gitattributes !filter !diff",
+    ".gitignore !filter !diff",
+];
+
+/// Returns true if the .gitattributes in the given directory makes git-crypt encrypt files.
+pub fn is_encrypted(base_dir: &Path) -> Result<bool> {
+    let gitattributes_path = base_dir.join(".gitattributes");
+    if !gitattributes_path.exists() {
+        return Ok(false);
+    }
+    let gitattributes = std::fs::read_to_string(&gitattributes_path)
+        .context(format!("failed to read {}", gitattributes_path.display()))?;
+    Ok(gitattributes
+        .lines()
+        .any(|line| !line.trim_start().starts_with('#') && line.contains("filter=git-crypt")))
+}
+
+/// Returns true if the repository in the current directory has git-crypt's key, i.e. files are
+/// decrypted on checkout and encrypted when staged.
+pub fn is_unlocked() -> Result<bool> {
+    let output = Command::new(GIT_CMD)
+        .args(["rev-parse", "--git-path", "git-crypt/keys/default"])
+        .output()
+        .context("failed to locate git-crypt's key")?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()).exists())
+}
@@ -0,0 +1,148 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use humantime::format_rfc3339_seconds;
+
+use crate::{cli, cmd, git, lock::Lock, sync, watch};
+
+/// The file in the .git directory that a running daemon reports on its last sync in.
+const STATUS_FILE: &str = "jot-daemon-status";
+
+/// Logs the given message with the time it happened, since a daemon's output is read long after.
+fn log(message: &str) {
+    println!(
+        "[{}] {}",
+        format_rfc3339_seconds(SystemTime::now()),
+        message
+    );
+}
+
+fn status_path() -> Result<PathBuf> {
+    git::git_path(STATUS_FILE)
+}
+
+/// Records the outcome of a sync in the status file, for jot status to show.
+fn write_status(result: &Result<()>) -> Result<()> {
+    let outcome = match result {
+        Ok(()) => "succeeded".to_string(),
+        // Only the first line, the rest being git's output, which the daemon's own output has.
+        Err(err) => format!(
+            "failed: {}",
+            format!("{:#}", err).lines().next().unwrap_or_default()
+        ),
+    };
+    let path = status_path()?;
+    std::fs::write(
+        &path,
+        format!(
+            "pid: {}\nlast sync: {}\nlast sync {}\n",
+            std::process::id(),
+            format_rfc3339_seconds(SystemTime::now()),
+            outcome
+        ),
+    )
+    .context(format!("failed to write {}", path.display()))
+}
+
+/// Prints whether a daemon is syncing base-dir, and how its last sync went.
+pub fn print_status() -> Result<()> {
+    if !Lock::is_daemon_running()? {
+        return Ok(());
+    }
+    println!("\na jot daemon is syncing base-dir");
+    if let Ok(status) = std::fs::read_to_string(status_path()?) {
+        for line in status.lines() {
+            println!("\t{}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Syncs base-dir, unless another jot process is in the middle of running git operations in it,
+/// e.g. an interactive jot whose editor is still open. Returns false if the sync was skipped. Only
+/// daemons report on their syncs in the status file.
+fn sync(args: &cli::Args, is_daemon: bool) -> Result<bool> {
+    let Some(_lock) = Lock::try_acquire()? else {
+        return Ok(false);
+    };
+    let steps = sync::Steps::new(args)?;
+    // Whatever git would prompt for could never be answered, so the message is always rendered
+    // from the commit template. Without local changes, only earlier commits are pushed.
+    let result = if steps.has_changes()? {
+        let message = steps.default_message()?;
+        log(&format!("syncing local changes: {}", message));
+        cmd::sync(
+            args,
+            &cli::SyncArgs {
+                message: Some(message),
+                background: true,
+                ..Default::default()
+            },
+        )
+    } else {
+        log("syncing");
+        cmd::sync(
+            args,
+            &cli::SyncArgs {
+                flush: true,
+                background: true,
+                ..Default::default()
+            },
+        )
+    };
+    if let Err(err) = &result {
+        log(&format!("sync failed: {:#}", err));
+    }
+    if is_daemon {
+        write_status(&result)?;
+    }
+    Ok(true)
+}
+
+/// Watches base-dir, and syncs whenever its changes have settled for the given time, as well as
+/// every given interval, so that upstream changes are pulled even when nothing changes locally.
+/// Runs until interrupted, e.g. with Ctrl-C, which takes effect once the sync in progress, if any,
+/// is over. Daemons also mark base-dir as synced by them, which has interactive jot leave pulling
+/// and pushing to them, and report on their syncs for jot status.
+pub fn run(
+    args: &cli::Args,
+    debounce: Duration,
+    interval: Duration,
+    is_daemon: bool,
+) -> Result<()> {
+    let _daemon_lock = is_daemon.then(Lock::acquire_daemon).transpose()?;
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_interrupted = Arc::clone(&interrupted);
+    ctrlc::set_handler(move || handler_interrupted.store(true, Ordering::SeqCst))
+        .context("failed to handle Ctrl-C")?;
+    log(&format!(
+        "watching {}, syncing {}s after changes settle, and every {}m",
+        args.base_dir.display(),
+        debounce.as_secs(),
+        interval.as_secs() / 60
+    ));
+
+    let mut debouncer = watch::Debouncer::new(watch::fingerprint()?);
+    // Syncing right away catches up on whatever happened while nothing was watching.
+    let mut last_sync: Option<Instant> = None;
+    while !interrupted.load(Ordering::SeqCst) {
+        let changed = debouncer.settled(watch::fingerprint()?, debounce);
+        let due = last_sync.is_none_or(|last_sync| last_sync.elapsed() >= interval);
+        // Failed syncs are only retried once something changes or the next one is due, rather
+        // than every time base-dir is checked on.
+        if (changed || due) && sync(args, is_daemon)? {
+            last_sync = Some(Instant::now());
+            debouncer.reset(watch::fingerprint()?);
+        }
+        std::thread::sleep(watch::POLL_INTERVAL);
+    }
+    log("stopped watching");
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::{
+    fmt,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// A date in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns the date the given number of days after the Unix epoch.
+    pub fn from_days(days: i64) -> Date {
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// Returns the number of days after the Unix epoch the date is, i.e. the inverse of
+    /// from_days.
+    pub fn days(&self) -> i64 {
+        // See http://howardhinnant.github.io/date_algorithms.html#days_from_civil.
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month_from_march = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Returns the date the given number of days after this one (or before, if negative).
+    pub fn add_days(&self, days: i64) -> Date {
+        Date::from_days(self.days() + days)
+    }
+
+    /// Returns the day of the week the date is on, from 1 for Monday to 7 for Sunday.
+    pub fn weekday(&self) -> i64 {
+        // The Unix epoch was on a Thursday.
+        (self.days() + 3).rem_euclid(7) + 1
+    }
+
+    /// Returns the ISO 8601 year and week the date is in. Weeks start on Mondays, and belong to
+    /// the year their Thursday is in, so the first days of January may be in the previous year.
+    pub fn iso_week(&self) -> (i64, i64) {
+        let thursday = self.add_days(4 - self.weekday());
+        let new_year = Date {
+            year: thursday.year,
+            month: 1,
+            day: 1,
+        };
+        (thursday.year, (thursday.days() - new_year.days()) / 7 + 1)
+    }
+
+    /// Returns the given strftime-like pattern with the date's fields in it, e.g.
+    /// `journal/2024/05/01.md` for `journal/%Y/%m/%d.md`. Only `%Y`, `%m`, `%d`, `%G` (the ISO
+    /// year), `%V` (the ISO week) and `%%` are understood.
+    pub fn format(&self, pattern: &str) -> Result<String> {
+        let mut formatted = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                formatted.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => formatted.push_str(&format!("{:04}", self.year)),
+                Some('m') => formatted.push_str(&format!("{:02}", self.month)),
+                Some('d') => formatted.push_str(&format!("{:02}", self.day)),
+                Some('G') => formatted.push_str(&format!("{:04}", self.iso_week().0)),
+                Some('V') => formatted.push_str(&format!("{:02}", self.iso_week().1)),
+                Some('%') => formatted.push('%'),
+                Some(other) => bail!("unknown field %{} in date pattern {}", other, pattern),
+                None => bail!("unterminated field in date pattern {}", pattern),
+            }
+        }
+        Ok(formatted)
+    }
+
+    /// Parses the given text as the given strftime-like pattern (see format), e.g. 2024-05-01 from
+    /// `journal/2024/05/01.md` with `journal/%Y/%m/%d.md`. Returns None if the text does not match
+    /// the pattern, or the pattern does not pin down a single day, e.g. `%G/W%V`.
+    pub fn parse(pattern: &str, text: &str) -> Option<Date> {
+        let (mut year, mut month, mut day) = (None, None, None);
+        let mut rest = text;
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                rest = rest.strip_prefix(c)?;
+                continue;
+            }
+            let (field, len) = match chars.next()? {
+                'Y' => (&mut year, 4),
+                'm' => (&mut month, 2),
+                'd' => (&mut day, 2),
+                '%' => {
+                    rest = rest.strip_prefix('%')?;
+                    continue;
+                }
+                _ => return None,
+            };
+            let digits = rest
+                .get(..len)
+                .filter(|digits| digits.chars().all(|digit| digit.is_ascii_digit()))?;
+            *field = Some(digits.parse::<i64>().ok()?);
+            rest = &rest[len..];
+        }
+        let date = Date {
+            year: year?,
+            month: u32::try_from(month?).ok()?,
+            day: u32::try_from(day?).ok()?,
+        };
+        // Out of range days and months, e.g. 2024-02-30, do not survive the round trip.
+        (rest.is_empty() && Date::from_days(date.days()) == date).then_some(date)
+    }
+
+    /// Returns the local date today.
+    pub fn today() -> Result<Date> {
+        Ok(Date::from_days(local_now()?.div_euclid(DAY)))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returns the local timezone's offset from UTC, in seconds, as date reports it, since the
+/// standard library knows nothing about timezones.
+pub fn local_offset() -> Result<i64> {
+    let output = Command::new("date")
+        .arg("+%z")
+        .output()
+        .context("failed to determine the local timezone")?;
+    let offset = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let (sign, digits) = match offset.split_at_checked(1) {
+        Some(("+", digits)) => (1, digits),
+        Some(("-", digits)) => (-1, digits),
+        _ => bail!("failed to parse the local timezone's offset: {}", offset),
+    };
+    match (
+        digits.get(..2).map(str::parse::<i64>),
+        digits.get(2..).map(str::parse::<i64>),
+    ) {
+        (Some(Ok(hours)), Some(Ok(minutes))) => Ok(sign * (hours * 60 + minutes) * 60),
+        _ => bail!("failed to parse the local timezone's offset: {}", offset),
+    }
+}
+
+/// Returns the current local time, as seconds since the Unix epoch in the local timezone.
+pub fn local_now() -> Result<i64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    Ok(now + local_offset()?)
+}
+
+/// Returns the current local time of day, e.g. 09:30.
+pub fn local_time() -> Result<String> {
+    let seconds_of_day = local_now()?.rem_euclid(DAY);
+    Ok(format!(
+        "{:02}:{:02}",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    ))
+}
+
+#[test]
+fn civil_dates() {
+    for (days, date) in [
+        (0, "1970-01-01"),
+        (-1, "1969-12-31"),
+        (11_016, "2000-02-29"),
+        (19_783, "2024-03-01"),
+    ] {
+        assert_eq!(Date::from_days(days).to_string(), date);
+        assert_eq!(Date::from_days(days).days(), days);
+    }
+    assert_eq!(
+        Date::from_days(19_783)
+            .add_days(-1)
+            .format("%Y/%m/%d.md")
+            .unwrap(),
+        "2024/02/29.md"
+    );
+    assert!(Date::from_days(0).format("%H").is_err());
+    assert_eq!(
+        Date::parse("j/%Y/%m/%d.md", "j/2024/02/29.md"),
+        Some(Date::from_days(19_782))
+    );
+    assert_eq!(Date::parse("j/%Y/%m/%d.md", "j/2023/02/29.md"), None);
+    assert_eq!(Date::parse("j/%G/W%V.md", "j/2024/W05.md"), None);
+    // 2021-01-03 was a Sunday in the last ISO week of 2020, and 2024-12-30 a Monday in the first
+    // of 2025.
+    assert_eq!(
+        Date::from_days(18_630).format("%G-W%V").unwrap(),
+        "2020-W53"
+    );
+    assert_eq!(
+        Date::from_days(20_087).format("%G-W%V").unwrap(),
+        "2025-W01"
+    );
+}
@@ -0,0 +1,114 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+/// A pair of notes whose contents are (nearly) the same.
+pub struct Duplicate {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    /// How similar the two notes are, from 0 to 1. Exact duplicates have a similarity of 1.
+    pub similarity: f64,
+}
+
+/// The set of lowercased word trigrams ("shingles") in the given text. Comparing these between two
+/// notes gives a decent measure of similarity that is robust to small edits and reformatting.
+fn shingles(content: &str) -> HashSet<String> {
+    let words = content
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>();
+    if words.len() < 3 {
+        return std::iter::once(words.join(" ")).collect();
+    }
+
+    words.windows(3).map(|window| window.join(" ")).collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Finds pairs of notes in the given list (relative to base_dir) with identical contents. If a
+/// fuzzy threshold is given, also finds pairs that are at least that similar.
+pub fn find(
+    base_dir: &Path,
+    notes: &[PathBuf],
+    fuzzy_threshold: Option<f64>,
+) -> Result<Vec<Duplicate>> {
+    let mut contents = Vec::with_capacity(notes.len());
+    for note in notes {
+        contents.push(
+            std::fs::read_to_string(base_dir.join(note))
+                .context(format!("failed to read note at {}", note.display()))?,
+        );
+    }
+
+    // Exact duplicates are found by bucketing on content hashes, which is cheap.
+    let mut buckets: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    for (idx, content) in contents.iter().enumerate() {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        buckets.entry(hasher.finish()).or_default().push(idx);
+    }
+
+    let mut exact_pairs = HashSet::new();
+    let mut duplicates = Vec::new();
+    for bucket in buckets.values() {
+        for (pos, &first) in bucket.iter().enumerate() {
+            for &second in &bucket[pos + 1..] {
+                // Guard against the (unlikely) case of a hash collision.
+                if contents[first] == contents[second] {
+                    exact_pairs.insert((first, second));
+                    duplicates.push(Duplicate {
+                        first: notes[first].clone(),
+                        second: notes[second].clone(),
+                        similarity: 1.0,
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(threshold) = fuzzy_threshold {
+        // Near-duplicates on the other hand require comparing every pair of notes.
+        let all_shingles = contents
+            .iter()
+            .map(|content| shingles(content))
+            .collect::<Vec<HashSet<String>>>();
+        for first in 0..notes.len() {
+            for second in first + 1..notes.len() {
+                if exact_pairs.contains(&(first, second)) {
+                    continue;
+                }
+                let similarity = jaccard(&all_shingles[first], &all_shingles[second]);
+                if similarity >= threshold {
+                    duplicates.push(Duplicate {
+                        first: notes[first].clone(),
+                        second: notes[second].clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+    }
+
+    duplicates.sort_by(|a, b| (&a.first, &a.second).cmp(&(&b.first, &b.second)));
+    Ok(duplicates)
+}
+
+#[test]
+fn similarity() {
+    let a = shingles("the quick brown fox jumps over the lazy dog");
+    let b = shingles("The quick brown fox jumps over the lazy cat");
+    let c = shingles("something else entirely, nothing in common");
+    assert!(jaccard(&a, &b) > 0.7);
+    assert_eq!(jaccard(&a, &c), 0.0);
+}
@@ -0,0 +1,55 @@
+use std::{ffi::OsString, path::Path};
+
+/// A position in a note to open it at, as a (1-indexed) line and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Returns the arguments to open the given file with the given editor, at the given cursor, if
+/// the editor is one we know how to tell where to put its cursor. Other editors are just given
+/// the file.
+pub fn args(editor: &str, file: &Path, cursor: Option<Cursor>) -> Vec<OsString> {
+    let Some(Cursor { line, column }) = cursor else {
+        return vec![file.into()];
+    };
+    let name = Path::new(editor)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    let with_position = || {
+        let mut file = file.as_os_str().to_owned();
+        file.push(format!(":{}:{}", line, column));
+        file
+    };
+
+    match name.as_str() {
+        "vim" | "nvim" | "gvim" | "mvim" => vec![
+            format!("+call cursor({}, {})", line, column).into(),
+            file.into(),
+        ],
+        "vi" => vec![format!("+{}", line).into(), file.into()],
+        "nano" | "pico" => vec![format!("+{},{}", line, column).into(), file.into()],
+        "emacs" | "emacsclient" | "kak" | "micro" | "gedit" => {
+            vec![format!("+{}:{}", line, column).into(), file.into()]
+        }
+        "code" | "code-insiders" | "codium" | "cursor" => vec!["--goto".into(), with_position()],
+        "hx" | "helix" | "subl" | "sublime_text" | "zed" => vec![with_position()],
+        _ => vec![file.into()],
+    }
+}
+
+#[test]
+fn editor_args() {
+    let file = Path::new("a.md");
+    let cursor = Some(Cursor { line: 3, column: 5 });
+    assert_eq!(
+        args("/usr/bin/nvim", file, cursor),
+        vec!["+call cursor(3, 5)", "a.md"]
+    );
+    assert_eq!(args("code", file, cursor), vec!["--goto", "a.md:3:5"]);
+    assert_eq!(args("nano", file, None), vec!["a.md"]);
+    assert_eq!(args("unknown", file, cursor), vec!["a.md"]);
+}
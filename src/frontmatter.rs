@@ -0,0 +1,276 @@
+use std::fmt;
+
+/// A value in a note's frontmatter. We only understand the tiny subset of YAML that people
+/// actually put in note frontmatter: scalars, and lists of scalars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+/// The YAML frontmatter at the start of a note, i.e. everything between the leading `---` lines.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Frontmatter {
+    pub fields: Vec<(String, Value)>,
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    for quote in ['"', '\''] {
+        if let Some(unquoted) = s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return unquoted.to_string();
+        }
+    }
+    s.to_string()
+}
+
+impl Frontmatter {
+    fn parse(yaml: &str) -> Frontmatter {
+        let mut fields: Vec<(String, Value)> = Vec::new();
+        for line in yaml.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            // Block list items belong to the key on the line(s) before them.
+            if let Some(item) = line.trim_start().strip_prefix("- ") {
+                if let Some((_, value)) = fields.last_mut() {
+                    match value {
+                        Value::List(items) => items.push(unquote(item)),
+                        Value::Scalar(scalar) if scalar.is_empty() => {
+                            *value = Value::List(vec![unquote(item)])
+                        }
+                        Value::Scalar(_) => {}
+                    }
+                }
+                continue;
+            }
+
+            let Some((key, raw_value)) = line.split_once(':') else {
+                continue;
+            };
+            let raw_value = raw_value.trim();
+            let value = match raw_value
+                .strip_prefix('[')
+                .and_then(|list| list.strip_suffix(']'))
+            {
+                Some(list) => Value::List(
+                    list.split(',')
+                        .map(unquote)
+                        .filter(|item| !item.is_empty())
+                        .collect(),
+                ),
+                None => Value::Scalar(unquote(raw_value)),
+            };
+            fields.push((key.trim().to_string(), value));
+        }
+
+        Frontmatter { fields }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the values of the given key. A scalar is treated as a comma or space separated list,
+    /// since both `tags: a, b` and `tags: a b` are common in the wild.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        match self.get(key) {
+            Some(Value::List(items)) => items.clone(),
+            Some(Value::Scalar(scalar)) => scalar
+                .split([',', ' '])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Splits the given note contents into its frontmatter (if any) and the rest of the note.
+pub fn split(content: &str) -> (Option<Frontmatter>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" || line.trim_end() == "..." {
+            let body = &rest[offset + line.len()..];
+            return (Some(Frontmatter::parse(&rest[..offset])), body);
+        }
+        offset += line.len();
+    }
+
+    // An unterminated frontmatter block is not frontmatter at all.
+    (None, content)
+}
+
+/// Quotes the given scalar for YAML if it needs to be, e.g. because it contains a `: `.
+fn quote(scalar: &str) -> String {
+    let is_plain = scalar
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_alphanumeric())
+        && !scalar.ends_with([':', ' '])
+        && !scalar.contains(": ")
+        && !scalar.contains(" #")
+        && !scalar.contains([',', '[', ']', '{', '}', '\'', '"']);
+    match is_plain {
+        true => scalar.to_string(),
+        false => format!("'{}'", scalar.replace('\'', "''")),
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Scalar(scalar) => write!(f, "{}", quote(scalar)),
+            Value::List(items) => write!(
+                f,
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| quote(item))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Returns the given note contents with the given fields in its frontmatter. Fields the note's
+/// frontmatter has already, e.g. from the template it was created from, are left as they are. If
+/// it has no frontmatter, a block with the fields is prepended to it.
+pub fn inject(content: &str, fields: &[(&str, Value)]) -> String {
+    let (frontmatter, _) = split(content);
+    let yaml: String = fields
+        .iter()
+        .filter(|(key, _)| {
+            frontmatter
+                .as_ref()
+                .is_none_or(|frontmatter| frontmatter.get(key).is_none())
+        })
+        .map(|(key, value)| format!("{}: {}\n", key, value))
+        .collect();
+    match (frontmatter, content.split_once('\n')) {
+        (Some(_), Some((delimiter, rest))) => format!("{}\n{}{}", delimiter, yaml, rest),
+        _ => format!("---\n{}---\n{}", yaml, content),
+    }
+}
+
+/// Returns the given note contents without the given fields in its frontmatter, along with any
+/// block list items that belong to them.
+pub fn remove(content: &str, keys: &[&str]) -> String {
+    let (Some(_), body) = split(content) else {
+        return content.to_string();
+    };
+    let yaml_with_delimiters = &content[..content.len() - body.len()];
+
+    let mut kept = String::new();
+    let mut removing = false;
+    for line in yaml_with_delimiters.split_inclusive('\n') {
+        let is_top_level = !line.starts_with([' ', '\t', '-']) || line.trim_end() == "---";
+        if is_top_level {
+            removing = line
+                .split_once(':')
+                .is_some_and(|(key, _)| keys.contains(&key.trim()));
+        }
+        if !removing {
+            kept.push_str(line);
+        }
+    }
+    kept.push_str(body);
+    kept
+}
+
+/// Returns the tags of the given note, from both its frontmatter (`tags`) and any inline `#tags`
+/// in its body. Tags are returned without their leading `#`, deduplicated, in order of appearance.
+pub fn tags(content: &str) -> Vec<String> {
+    let (frontmatter, body) = split(content);
+    let mut tags = frontmatter.map_or_else(Vec::new, |frontmatter| {
+        frontmatter
+            .list("tags")
+            .into_iter()
+            .map(|tag| tag.trim_start_matches('#').to_string())
+            .collect()
+    });
+
+    let mut in_code_block = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        for word in line.split_whitespace() {
+            let Some(tag) = word.strip_prefix('#') else {
+                continue;
+            };
+            let tag = tag.trim_end_matches(|c: char| !c.is_alphanumeric());
+            let is_tag = !tag.is_empty()
+                && !tag.chars().all(|c| c.is_ascii_digit())
+                && tag
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'));
+            if is_tag {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    tags.retain(|tag| seen.insert(tag.clone()));
+    tags
+}
+
+#[test]
+fn parse_frontmatter() {
+    let content = "---\ntitle: \"My note\"\ntags: [work, ideas]\naliases:\n  - a\n  - b\n---\n# Heading\nbody #later and #1\n";
+    let (frontmatter, body) = split(content);
+    let frontmatter = frontmatter.unwrap();
+    assert_eq!(
+        frontmatter.get("title"),
+        Some(&Value::Scalar("My note".to_string()))
+    );
+    assert_eq!(frontmatter.list("aliases"), vec!["a", "b"]);
+    assert_eq!(body, "# Heading\nbody #later and #1\n");
+    assert_eq!(tags(content), vec!["work", "ideas", "later"]);
+}
+
+#[test]
+fn inject_frontmatter() {
+    let fields = [
+        ("title", Value::Scalar("Sync: Q3".to_string())),
+        ("tags", Value::List(vec!["work".to_string()])),
+    ];
+    assert_eq!(
+        inject("# Sync\n", &fields),
+        "---\ntitle: 'Sync: Q3'\ntags: [work]\n---\n# Sync\n"
+    );
+    assert_eq!(
+        inject("---\ntags: [a]\n---\nbody\n", &fields),
+        "---\ntitle: 'Sync: Q3'\ntags: [a]\n---\nbody\n"
+    );
+}
+
+#[test]
+fn remove_frontmatter_fields() {
+    let content = "---\ncreated: 2024-01-01\ntitle: a\ndates:\n  - x\n- y\n---\nbody\n";
+    assert_eq!(
+        remove(content, &["created", "dates"]),
+        "---\ntitle: a\n---\nbody\n"
+    );
+    assert_eq!(remove("no frontmatter\n", &["title"]), "no frontmatter\n");
+}
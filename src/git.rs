@@ -0,0 +1,1208 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli,
+    cmd::{exec_cmd, exec_cmd_with_timeout, Stalled},
+    crypt, lfs,
+};
+
+pub static GIT_CMD: &str = "git";
+
+/// The message jot stashes local changes under while pulling.
+static AUTOSTASH_MESSAGE: &str = "jot: autostash";
+
+/// A multi-step git operation that can be left in progress, e.g. because of conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Merge,
+    Rebase,
+}
+
+/// How a file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Add,
+    Edit,
+    Delete,
+}
+
+/// The change to a single file, compared to HEAD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    /// The number of inserted and deleted lines, or None for binary files.
+    pub lines: Option<(usize, usize)>,
+}
+
+/// The git operations that jot builds its sync out of. There are two implementations: one that
+/// shells out to the git binary, and (if jot is built with the native-git feature) one that uses
+/// libgit2 in-process.
+pub trait Backend {
+    /// Fetches the given branch from the given remote and integrates it into the current branch
+    /// using the given strategy.
+    fn pull(&self, remote: &str, branch: &str, strategy: cli::PullStrategy) -> Result<()>;
+
+    /// Fetches the given branch from the given remote, updating its remote-tracking branch.
+    fn fetch(&self, remote: &str, branch: &str) -> Result<()>;
+
+    /// Returns how many commits the current branch is ahead and behind the remote-tracking branch
+    /// of the given branch on the given remote, as of the last fetch. Returns None if there is no
+    /// such remote-tracking branch, e.g. because nothing was ever fetched from it.
+    fn ahead_behind(&self, remote: &str, branch: &str) -> Result<Option<(usize, usize)>>;
+
+    /// Stashes any uncommitted changes to tracked files. Returns true if there was anything to
+    /// stash.
+    fn stash(&self) -> Result<bool>;
+
+    /// Restores the most recently stashed changes.
+    fn unstash(&self) -> Result<()>;
+
+    /// Returns true if the most recently stashed changes are ones that jot stashed while pulling,
+    /// i.e. they could not be restored afterwards.
+    fn has_autostash(&self) -> Result<bool>;
+
+    /// Discards the most recently stashed changes.
+    fn drop_stash(&self) -> Result<()>;
+
+    /// Returns the files (relative to the repository's root) with unresolved conflicts.
+    fn conflicted_files(&self) -> Result<Vec<PathBuf>>;
+
+    /// Returns the merge or rebase that is currently in progress, if any.
+    fn operation_in_progress(&self) -> Result<Option<Operation>>;
+
+    /// Concludes the given in-progress operation. Any conflicts must already be resolved and
+    /// staged.
+    fn continue_operation(&self, operation: Operation) -> Result<()>;
+
+    /// Returns every changed file in the repository (relative to its root), including untracked
+    /// and deleted files. Files ignored by .gitignore are not included.
+    fn changed_files(&self) -> Result<Vec<PathBuf>>;
+
+    /// Returns how the given files (relative to the repository's root) differ from HEAD, whether
+    /// their changes are staged or not.
+    fn diff_stats(&self, paths: &[PathBuf]) -> Result<Vec<FileDiff>>;
+
+    /// Stages the changes to the given files (relative to the repository's root), including their
+    /// deletion.
+    fn stage(&self, paths: &[PathBuf]) -> Result<()>;
+
+    /// Commits the staged changes with the given message. If no message is given, the user is
+    /// prompted for one by git, as with a bare `git commit`.
+    fn commit(&self, message: Option<&str>) -> Result<()>;
+
+    /// Pushes the current branch to the given branch of the given remote.
+    fn push(&self, remote: &str, branch: &str) -> Result<()>;
+}
+
+/// Returns the git backend that was asked for via the git-backend flag.
+pub fn backend(args: &cli::Args) -> Result<Box<dyn Backend + '_>> {
+    match args.git_backend {
+        cli::GitBackend::Subprocess => Ok(Box::new(Subprocess::new(args))),
+        // libgit2 can neither fetch the objects that partial clones are missing, nor run filters
+        // like git-crypt's and Git LFS's.
+        cli::GitBackend::Native if is_partial()? || uses_filters()? => bail!(
+            "the native git backend does not support partial clones, or filters like git-crypt \
+            and Git LFS, please use the subprocess git backend"
+        ),
+        cli::GitBackend::Native => native(args),
+        cli::GitBackend::Auto if is_partial()? || uses_filters()? => {
+            Ok(Box::new(Subprocess::new(args)))
+        }
+        cli::GitBackend::Auto => {
+            Ok(native(args).unwrap_or_else(|_| Box::new(Subprocess::new(args))))
+        }
+    }
+}
+
+/// Has the native git backend give up on remotes that stop responding for the given time. libgit2
+/// keeps this setting globally, so it must be set before jot starts any threads.
+#[cfg(feature = "native-git")]
+pub fn set_native_timeout(timeout: Duration) -> Result<()> {
+    let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+    // This is sound as long as no other thread uses libgit2 at the same time (see above).
+    unsafe {
+        git2::opts::set_server_connect_timeout_in_milliseconds(millis)?;
+        git2::opts::set_server_timeout_in_milliseconds(millis)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "native-git")]
+fn native(args: &cli::Args) -> Result<Box<dyn Backend + '_>> {
+    Ok(Box::new(native::Native::open(args)?))
+}
+
+#[cfg(not(feature = "native-git"))]
+fn native(_: &cli::Args) -> Result<Box<dyn Backend + '_>> {
+    bail!("the native git backend is unavailable, jot was built without the native-git feature")
+}
+
+/// Returns the argument that makes git commands that create commits sign them, as asked for via
+/// the sign-commits and signing-key flags, if at all.
+pub fn gpg_sign_arg(args: &cli::Args) -> Option<String> {
+    match (&args.signing_key, args.sign_commits) {
+        (Some(key), _) => Some(format!("--gpg-sign={}", key)),
+        (None, true) => Some("--gpg-sign".to_string()),
+        (None, false) => None,
+    }
+}
+
+/// Returns true if the repository in the current directory has a remote with the given name.
+pub fn has_remote(name: &str) -> Result<bool> {
+    Ok(Command::new(GIT_CMD)
+        .args(["config", "--get", &format!("remote.{}.url", name)])
+        .stdout(Stdio::null())
+        .status()
+        .context(format!("failed to look up remote {}", name))?
+        .success())
+}
+
+/// Returns the branches of the given remote whose names start with the given prefix, as of the
+/// last fetch.
+pub fn remote_branches(remote: &str, prefix: &str) -> Result<Vec<String>> {
+    let output = Command::new(GIT_CMD)
+        .args([
+            "for-each-ref",
+            "--format=%(refname:lstrip=3)",
+            &format!("refs/remotes/{}/{}", remote, prefix),
+        ])
+        .output()
+        .context(format!("failed to list the branches of {}", remote))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|branch| branch.starts_with(prefix))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Checks out the given branch, creating it at the current commit if it does not exist yet.
+pub fn switch_branch(args: &cli::Args, branch: &str) -> Result<()> {
+    let output = Command::new(GIT_CMD)
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .output()
+        .context("failed to determine the current branch")?;
+    if String::from_utf8_lossy(&output.stdout).trim() == branch {
+        return Ok(());
+    }
+
+    let exists = Command::new(GIT_CMD)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{}", branch))
+        .stdout(Stdio::null())
+        .status()
+        .context(format!("failed to look up {}", branch))?
+        .success();
+    let mut git_switch_exec = Command::new(GIT_CMD);
+    git_switch_exec.arg("switch");
+    if !exists {
+        git_switch_exec.arg("--create");
+    }
+    git_switch_exec.arg(branch);
+    exec_cmd(
+        "switching branch",
+        git_switch_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(())
+}
+
+/// Returns true if the .gitattributes of the repository in the current directory has git run files
+/// through a filter when checking them out or staging them, as git-crypt and Git LFS do.
+fn uses_filters() -> Result<bool> {
+    let base_dir = std::path::Path::new(".");
+    Ok(crypt::is_encrypted(base_dir)? || lfs::is_used(base_dir)?)
+}
+
+/// Returns true if the current directory is in a git repository.
+pub fn is_repository() -> Result<bool> {
+    Ok(Command::new(GIT_CMD)
+        .args(["rev-parse", "--git-dir"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to determine if base-dir is a git repository")?
+        .success())
+}
+
+/// Returns the path of the given file (e.g. `jot.lock`) in the .git directory of the repository in
+/// the current directory.
+pub fn git_path(name: &str) -> Result<PathBuf> {
+    let output = Command::new(GIT_CMD)
+        .args(["rev-parse", "--git-path", name])
+        .output()
+        .context("failed to locate .git")?;
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
+
+/// Returns true if the repository in the current directory is a shallow clone, i.e. one that is
+/// missing the history before some commit.
+pub fn is_shallow() -> Result<bool> {
+    let output = Command::new(GIT_CMD)
+        .args(["rev-parse", "--is-shallow-repository"])
+        .output()
+        .context("failed to determine if base-dir is a shallow clone")?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+/// Returns true if the repository in the current directory is a partial clone, e.g. a blob-less
+/// one, which fetches the objects it is missing from its remote when they are needed.
+pub fn is_partial() -> Result<bool> {
+    // Git marks the remotes that a partial clone fetches its missing objects from as promisors.
+    let output = Command::new(GIT_CMD)
+        .args(["config", "--get-regexp", r"^remote\..*\.promisor$"])
+        .output()
+        .context("failed to determine if base-dir is a partial clone")?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line.ends_with(" true")))
+}
+
+/// Fragments of the errors git prints when it cannot reach a remote at all, as opposed to e.g.
+/// being refused by it.
+static NETWORK_ERRORS: [&str; 9] = [
+    "Could not resolve host",
+    "Could not resolve hostname",
+    "Temporary failure in name resolution",
+    "Connection refused",
+    "Connection timed out",
+    "Operation timed out",
+    "Network is unreachable",
+    "No route to host",
+    "Failed to connect",
+];
+
+/// Returns true if the given error came from failing to reach a remote, e.g. because there is no
+/// network, as opposed to e.g. a merge conflict or a rejected push.
+pub fn is_network_error(err: &anyhow::Error) -> bool {
+    #[cfg(feature = "native-git")]
+    if err.chain().any(|cause| {
+        cause.downcast_ref::<git2::Error>().is_some_and(|err| {
+            matches!(
+                err.class(),
+                git2::ErrorClass::Net | git2::ErrorClass::Ssh | git2::ErrorClass::Http
+            ) && !matches!(
+                err.code(),
+                git2::ErrorCode::Auth | git2::ErrorCode::Certificate
+            )
+        })
+    }) {
+        return true;
+    }
+
+    // Git operations that stall are most likely waiting on a remote that stopped responding.
+    if err.chain().any(|cause| cause.is::<Stalled>()) {
+        return true;
+    }
+
+    let message = format!("{:#}", err);
+    NETWORK_ERRORS
+        .iter()
+        .any(|fragment| message.contains(fragment))
+}
+
+/// Returns the diff of an untracked file, i.e. one that was added in its entirety.
+fn untracked_diff(base_dir: &std::path::Path, path: &std::path::Path) -> FileDiff {
+    FileDiff {
+        path: path.to_path_buf(),
+        kind: ChangeKind::Add,
+        lines: std::fs::read_to_string(base_dir.join(path))
+            .ok()
+            .map(|content| (content.lines().count(), 0)),
+    }
+}
+
+/// Performs git operations by executing the git binary. All standard streams are captured, except
+/// when git needs to prompt the user for a commit message.
+pub struct Subprocess<'a> {
+    args: &'a cli::Args,
+}
+
+impl<'a> Subprocess<'a> {
+    pub fn new(args: &'a cli::Args) -> Subprocess<'a> {
+        Subprocess { args }
+    }
+
+    fn exec(&self, label: &str, git_exec: Command) -> Result<()> {
+        self.exec_output(label, git_exec)?;
+        Ok(())
+    }
+
+    fn exec_output(&self, label: &str, git_exec: Command) -> Result<String> {
+        let (stdout, _) = exec_cmd_with_timeout(
+            label,
+            git_exec,
+            true,
+            self.args.quiet_on_ctrl_c,
+            self.args.git_timeout_secs.map(Duration::from_secs),
+        )?;
+        Ok(stdout)
+    }
+
+    /// Fetches more and more history into a shallow clone until it includes where the current
+    /// branch and the given upstream branch diverged, since neither merging nor rebasing can do
+    /// without it. Gives up once there is no history left to fetch, in which case the two
+    /// branches simply have nothing in common.
+    fn deepen_until_merge_base(&self, remote: &str, branch: &str) -> Result<()> {
+        self.fetch(remote, branch)?;
+        while is_shallow()? {
+            let has_merge_base = Command::new(GIT_CMD)
+                .args(["merge-base", "HEAD", "FETCH_HEAD"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .context("failed to look for a common ancestor with upstream")?
+                .success();
+            if has_merge_base {
+                break;
+            }
+
+            let mut git_fetch_exec = Command::new(GIT_CMD);
+            git_fetch_exec
+                .arg("fetch")
+                .arg(format!("--deepen={}", self.args.deepen_by))
+                .arg(remote)
+                .arg(branch);
+            self.exec("deepening history", git_fetch_exec)?;
+        }
+        Ok(())
+    }
+}
+
+impl Backend for Subprocess<'_> {
+    fn pull(&self, remote: &str, branch: &str, strategy: cli::PullStrategy) -> Result<()> {
+        if is_shallow()? {
+            self.deepen_until_merge_base(remote, branch)?;
+        }
+
+        let mut git_pull_exec = Command::new(GIT_CMD);
+        git_pull_exec
+            .arg("pull")
+            .arg(match strategy {
+                cli::PullStrategy::Merge => "--no-rebase",
+                cli::PullStrategy::Rebase => "--rebase",
+                cli::PullStrategy::FfOnly => "--ff-only",
+            })
+            .args(gpg_sign_arg(self.args))
+            .arg(remote)
+            .arg(branch);
+        self.exec("pulling", git_pull_exec)
+    }
+
+    fn fetch(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut git_fetch_exec = Command::new(GIT_CMD);
+        git_fetch_exec.arg("fetch").arg(remote).arg(branch);
+        self.exec("fetching", git_fetch_exec)
+    }
+
+    fn ahead_behind(&self, remote: &str, branch: &str) -> Result<Option<(usize, usize)>> {
+        let tracking_ref = format!("refs/remotes/{}/{}", remote, branch);
+        let has_tracking_ref = Command::new(GIT_CMD)
+            .args(["rev-parse", "--verify", "--quiet", &tracking_ref])
+            .stdout(Stdio::null())
+            .status()
+            .context(format!("failed to look up {}", tracking_ref))?
+            .success();
+        if !has_tracking_ref {
+            return Ok(None);
+        }
+
+        let mut git_rev_list_exec = Command::new(GIT_CMD);
+        git_rev_list_exec.args([
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("HEAD...{}", tracking_ref),
+        ]);
+        let counts = self.exec_output("comparing with upstream", git_rev_list_exec)?;
+        match counts.split_once('\t') {
+            Some((ahead, behind)) => Ok(Some((ahead.parse()?, behind.parse()?))),
+            None => bail!("unexpected output from git rev-list: {}", counts),
+        }
+    }
+
+    fn stash(&self) -> Result<bool> {
+        let mut git_status_exec = Command::new(GIT_CMD);
+        git_status_exec.args(["status", "--porcelain", "--untracked-files=no"]);
+        if self
+            .exec_output("checking for local changes", git_status_exec)?
+            .is_empty()
+        {
+            return Ok(false);
+        }
+
+        let mut git_stash_exec = Command::new(GIT_CMD);
+        git_stash_exec.args(["stash", "push", "--message", AUTOSTASH_MESSAGE]);
+        self.exec("stashing", git_stash_exec)?;
+        Ok(true)
+    }
+
+    fn unstash(&self) -> Result<()> {
+        let mut git_stash_exec = Command::new(GIT_CMD);
+        git_stash_exec.args(["stash", "pop"]);
+        self.exec("unstashing", git_stash_exec)
+    }
+
+    fn drop_stash(&self) -> Result<()> {
+        let mut git_stash_exec = Command::new(GIT_CMD);
+        git_stash_exec.args(["stash", "drop"]);
+        self.exec("dropping stash", git_stash_exec)
+    }
+
+    fn has_autostash(&self) -> Result<bool> {
+        let mut git_stash_exec = Command::new(GIT_CMD);
+        git_stash_exec.args(["stash", "list", "-n", "1", "--format=%s"]);
+        // Stash subjects look like `On <branch>: <message>`.
+        Ok(self
+            .exec_output("listing stashes", git_stash_exec)?
+            .ends_with(AUTOSTASH_MESSAGE))
+    }
+
+    fn conflicted_files(&self) -> Result<Vec<PathBuf>> {
+        let mut git_diff_exec = Command::new(GIT_CMD);
+        git_diff_exec.args(["diff", "--name-only", "--diff-filter=U", "-z"]);
+        Ok(self
+            .exec_output("listing conflicts", git_diff_exec)?
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    fn operation_in_progress(&self) -> Result<Option<Operation>> {
+        let mut git_rev_parse_exec = Command::new(GIT_CMD);
+        git_rev_parse_exec.args(["rev-parse", "--git-dir"]);
+        let git_dir = PathBuf::from(self.exec_output("locating .git", git_rev_parse_exec)?);
+        if git_dir.join("MERGE_HEAD").exists() {
+            Ok(Some(Operation::Merge))
+        } else if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+            Ok(Some(Operation::Rebase))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn continue_operation(&self, operation: Operation) -> Result<()> {
+        let mut git_continue_exec = Command::new(GIT_CMD);
+        match operation {
+            Operation::Merge => {
+                git_continue_exec
+                    .args(["commit", "--no-edit"])
+                    .args(gpg_sign_arg(self.args))
+                    .args(self.args.no_verify.then_some("--no-verify"));
+            }
+            Operation::Rebase => {
+                // Rebasing may want to open an editor for each commit message, which we skip. Git
+                // remembers whether the rebase signs commits from when it was started.
+                git_continue_exec.args(["-c", "core.editor=true", "rebase", "--continue"]);
+            }
+        }
+        self.exec("continuing", git_continue_exec)
+    }
+
+    fn changed_files(&self) -> Result<Vec<PathBuf>> {
+        let mut git_status_exec = Command::new(GIT_CMD);
+        git_status_exec.args([
+            "status",
+            "--porcelain=v2",
+            "-z",
+            "--untracked-files=all",
+            "--no-renames",
+        ]);
+        let status = self.exec_output("listing changes", git_status_exec)?;
+
+        // Entries are either `? path` for untracked files, `1 XY <6 fields> path` for changed
+        // files, or `u XY <8 fields> path` for unmerged files.
+        Ok(status
+            .split('\0')
+            .filter_map(|entry| match entry.chars().next() {
+                Some('?') => entry.split_once(' ').map(|(_, path)| path),
+                Some('1') => entry.splitn(9, ' ').nth(8),
+                Some('u') => entry.splitn(11, ' ').nth(10),
+                _ => None,
+            })
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    fn diff_stats(&self, paths: &[PathBuf]) -> Result<Vec<FileDiff>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut git_name_status_exec = Command::new(GIT_CMD);
+        git_name_status_exec
+            .args(["diff", "HEAD", "--name-status", "--no-renames", "-z", "--"])
+            .args(paths);
+        let name_status = self.exec_output("diffing changes", git_name_status_exec)?;
+        let mut git_numstat_exec = Command::new(GIT_CMD);
+        git_numstat_exec
+            .args(["diff", "HEAD", "--numstat", "--no-renames", "-z", "--"])
+            .args(paths);
+        let numstat = self.exec_output("diffing changes", git_numstat_exec)?;
+
+        // Entries are `<status>\0<path>\0` and `<insertions>\t<deletions>\t<path>\0`
+        // respectively, where binary files have `-` for both counts.
+        let mut lines = std::collections::HashMap::new();
+        for entry in numstat.split('\0') {
+            let mut fields = entry.splitn(3, '\t');
+            if let (Some(insertions), Some(deletions), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            {
+                let counts = insertions.parse().ok().zip(deletions.parse().ok());
+                lines.insert(PathBuf::from(path), counts);
+            }
+        }
+        let mut diffs = Vec::new();
+        let mut name_status = name_status.split('\0');
+        while let (Some(status), Some(path)) = (name_status.next(), name_status.next()) {
+            let path = PathBuf::from(path);
+            diffs.push(FileDiff {
+                kind: match status {
+                    "A" => ChangeKind::Add,
+                    "D" => ChangeKind::Delete,
+                    _ => ChangeKind::Edit,
+                },
+                lines: lines.get(&path).copied().flatten(),
+                path,
+            });
+        }
+
+        // Untracked files are not part of git diff's output at all.
+        for path in paths {
+            if !diffs.iter().any(|diff| diff.path == *path) {
+                diffs.push(untracked_diff(&self.args.base_dir, path));
+            }
+        }
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(diffs)
+    }
+
+    fn stage(&self, paths: &[PathBuf]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let mut git_add_exec = Command::new(GIT_CMD);
+        git_add_exec.arg("add").arg("-A").arg("--").args(paths);
+        self.exec("staging", git_add_exec)
+    }
+
+    fn commit(&self, message: Option<&str>) -> Result<()> {
+        let mut git_commit_exec = Command::new(GIT_CMD);
+        git_commit_exec
+            .arg("commit")
+            .args(gpg_sign_arg(self.args))
+            .args(self.args.no_verify.then_some("--no-verify"));
+        match message {
+            Some(message) => {
+                git_commit_exec.arg("-m").arg(message);
+            }
+            None => {
+                git_commit_exec
+                    .stdin(Stdio::inherit())
+                    .stdout(Stdio::inherit());
+            }
+        }
+        // Hooks report what they are doing (e.g. reformatting notes), and why they fail, on
+        // stderr, so it is passed through as it happens, rather than captured.
+        git_commit_exec.stderr(Stdio::inherit());
+        exec_cmd(
+            "committing",
+            git_commit_exec,
+            false,
+            self.args.quiet_on_ctrl_c,
+        )?;
+        Ok(())
+    }
+
+    fn push(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut git_push_exec = Command::new(GIT_CMD);
+        git_push_exec
+            .arg("push")
+            .args(self.args.no_verify.then_some("--no-verify"))
+            .arg(remote)
+            .arg(format!("HEAD:refs/heads/{}", branch));
+        self.exec("pushing", git_push_exec)
+    }
+}
+
+#[cfg(feature = "native-git")]
+mod native {
+    use std::{cell::RefCell, path::PathBuf, process::Command};
+
+    use anyhow::{bail, Context, Result};
+    use git2::{
+        build::CheckoutBuilder, AnnotatedCommit, Cred, CredentialType, Delta, DiffOptions,
+        ErrorClass, ErrorCode, FetchOptions, Patch, PushOptions, RemoteCallbacks, Repository,
+        RepositoryState, Signature, StatusOptions,
+    };
+
+    use super::{
+        gpg_sign_arg, untracked_diff, Backend, ChangeKind, FileDiff, Operation, Subprocess,
+        AUTOSTASH_MESSAGE, GIT_CMD,
+    };
+    use crate::cli;
+
+    /// The hooks that git runs when committing, which jot runs too unless told not to.
+    static COMMIT_HOOKS: [&str; 4] = [
+        "pre-commit",
+        "prepare-commit-msg",
+        "commit-msg",
+        "post-commit",
+    ];
+
+    /// The environment variable that an access token for HTTPS remotes is read from.
+    static TOKEN_ENV_VARNAME: &str = "JOT_GIT_TOKEN";
+
+    /// The private keys that are tried for SSH remotes if ssh-agent has none they accept, relative
+    /// to the home directory.
+    static SSH_KEYS: [&str; 3] = [".ssh/id_ed25519", ".ssh/id_ecdsa", ".ssh/id_rsa"];
+
+    /// Returns callbacks that authenticate with remotes the way the git binary commonly would: via
+    /// ssh-agent or the default SSH keys for SSH remotes, and via the token in JOT_GIT_TOKEN or
+    /// git's credential helpers (which is how OS keyrings are integrated with) for HTTPS remotes.
+    /// libgit2 asks for credentials again whenever it is refused, so each is only offered once,
+    /// after which authentication fails with what was tried.
+    fn remote_callbacks<'cb>(repo: &Repository) -> RemoteCallbacks<'cb> {
+        let config = repo.config().ok();
+        let mut ssh_keys = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .into_iter()
+            .flat_map(|home| SSH_KEYS.map(|key| home.join(key)))
+            .filter(|key| key.exists())
+            .collect::<Vec<PathBuf>>()
+            .into_iter();
+        let mut tried: Vec<String> = Vec::new();
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(move |url, username_from_url, allowed| {
+            let username = username_from_url.unwrap_or("git");
+            let mut first_try = |method: &str| {
+                let is_first = !tried.iter().any(|tried| tried == method);
+                if is_first {
+                    tried.push(method.to_string());
+                }
+                is_first
+            };
+            if allowed.contains(CredentialType::USERNAME) {
+                return Cred::username(username);
+            }
+            if allowed.contains(CredentialType::SSH_KEY) {
+                if first_try("ssh-agent") {
+                    return Cred::ssh_key_from_agent(username);
+                }
+                if let Some(key) = ssh_keys.next() {
+                    first_try(&key.display().to_string());
+                    return Cred::ssh_key(username, None, &key, None);
+                }
+            }
+            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                if let Ok(token) = std::env::var(TOKEN_ENV_VARNAME) {
+                    if first_try(&format!("${}", TOKEN_ENV_VARNAME)) {
+                        return Cred::userpass_plaintext(
+                            username_from_url.unwrap_or("jot"),
+                            &token,
+                        );
+                    }
+                }
+                // Without any credential helper that knows the remote, there is nothing to offer.
+                if let Some(config) = &config {
+                    if first_try("git's credential helpers") {
+                        if let Ok(cred) = Cred::credential_helper(config, url, username_from_url) {
+                            return Ok(cred);
+                        }
+                    }
+                }
+            }
+            if allowed.contains(CredentialType::DEFAULT) && first_try("the default credentials") {
+                return Cred::default();
+            }
+            Err(git2::Error::new(
+                ErrorCode::Auth,
+                ErrorClass::Callback,
+                format!(
+                    "failed to authenticate with {} ({}), for SSH remotes, add a key to ssh-agent, \
+                    and for HTTPS remotes, set ${} to an access token or configure a git \
+                    credential helper, or use the subprocess git backend",
+                    url,
+                    match tried.is_empty() {
+                        true => "no supported credentials were asked for".to_string(),
+                        false => format!("tried {}", tried.join(", ")),
+                    },
+                    TOKEN_ENV_VARNAME
+                ),
+            ))
+        });
+        callbacks
+    }
+
+    /// Returns the signature to commit as, i.e. git's user.name and user.email, unless the
+    /// git-author-name or git-author-email flags override them.
+    fn signature(repo: &Repository, args: &cli::Args) -> Result<Signature<'static>, git2::Error> {
+        if args.git_author_name.is_none() && args.git_author_email.is_none() {
+            return repo.signature();
+        }
+        let config = repo.config()?;
+        let name = match &args.git_author_name {
+            Some(name) => name.clone(),
+            None => config.get_string("user.name")?,
+        };
+        let email = match &args.git_author_email {
+            Some(email) => email.clone(),
+            None => config.get_string("user.email")?,
+        };
+        Signature::now(&name, &email)
+    }
+
+    /// Returns true if the repository in the current directory has any of the given git hooks,
+    /// honoring core.hooksPath.
+    fn has_hooks(names: &[&str]) -> Result<bool> {
+        for name in names {
+            let output = Command::new(GIT_CMD)
+                .args(["rev-parse", "--git-path", &format!("hooks/{}", name)])
+                .output()
+                .context("failed to locate git hooks")?;
+            if PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()).exists() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Performs git operations in-process via libgit2. libgit2 does not run git hooks, so commits
+    /// and pushes that hooks would run for are deferred to the git binary.
+    pub struct Native<'a> {
+        args: &'a cli::Args,
+        repo: Repository,
+    }
+
+    impl<'a> Native<'a> {
+        pub fn open(args: &'a cli::Args) -> Result<Native<'a>> {
+            let repo = Repository::open(&args.base_dir).context(format!(
+                "failed to open {} as a git repository",
+                args.base_dir.display()
+            ))?;
+            Ok(Native { args, repo })
+        }
+
+        fn conflicted_paths(&self) -> Result<Vec<String>> {
+            let index = self.repo.index()?;
+            let mut paths = Vec::new();
+            for conflict in index.conflicts()? {
+                let conflict = conflict?;
+                let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+                if let Some(entry) = entry {
+                    paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+                }
+            }
+            Ok(paths)
+        }
+
+        fn merge(&self, remote: &str, branch: &str, fetched: &AnnotatedCommit) -> Result<()> {
+            self.repo
+                .merge(&[fetched], None, Some(CheckoutBuilder::new().safe()))
+                .context("failed to merge upstream changes")?;
+            let message = format!("Merge branch '{}' of {}", branch, remote);
+            if self.repo.index()?.has_conflicts() {
+                // Leave the same message the git binary would for whoever concludes the merge.
+                std::fs::write(self.repo.path().join("MERGE_MSG"), &message)
+                    .context("failed to write the merge message")?;
+                bail!(
+                    "merge conflict in the following file(s):\n\t! {}",
+                    self.conflicted_paths()?.join("\n\t! ")
+                );
+            }
+
+            // libgit2 cannot sign commits, so signed merges are concluded by the git binary.
+            if gpg_sign_arg(self.args).is_some() {
+                std::fs::write(self.repo.path().join("MERGE_MSG"), &message)
+                    .context("failed to write the merge message")?;
+                return Subprocess::new(self.args).continue_operation(Operation::Merge);
+            }
+
+            let mut index = self.repo.index()?;
+            let tree = self.repo.find_tree(index.write_tree()?)?;
+            let signature = signature(&self.repo, self.args)?;
+            let ours = self.repo.head()?.peel_to_commit()?;
+            let theirs = self.repo.find_commit(fetched.id())?;
+            self.repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &[&ours, &theirs],
+            )?;
+            self.repo.cleanup_state()?;
+            Ok(())
+        }
+
+        fn rebase(&self, fetched: &AnnotatedCommit) -> Result<()> {
+            if gpg_sign_arg(self.args).is_some() {
+                bail!(
+                    "the native git backend cannot sign rebased commits, please use the \
+                    subprocess git backend"
+                );
+            }
+            let signature = signature(&self.repo, self.args)?;
+            let mut rebase = self
+                .repo
+                .rebase(None, Some(fetched), None, None)
+                .context("failed to start rebasing onto upstream changes")?;
+            while let Some(operation) = rebase.next() {
+                operation.context("failed to rebase onto upstream changes")?;
+                if self.repo.index()?.has_conflicts() {
+                    let conflicted_paths = self.conflicted_paths()?;
+                    rebase.abort()?;
+                    bail!(
+                        "rebase conflict in the following file(s):\n\t! {}",
+                        conflicted_paths.join("\n\t! ")
+                    );
+                }
+                match rebase.commit(None, &signature, None) {
+                    // A commit whose changes are already upstream simply disappears.
+                    Err(err) if err.code() == ErrorCode::Applied => {}
+                    committed => {
+                        committed.context("failed to rebase onto upstream changes")?;
+                    }
+                }
+            }
+            rebase.finish(Some(&signature))?;
+            Ok(())
+        }
+    }
+
+    impl Backend for Native<'_> {
+        fn pull(&self, remote: &str, branch: &str, strategy: cli::PullStrategy) -> Result<()> {
+            // libgit2 cannot deepen a shallow clone whose history is too short to merge with.
+            if self.repo.is_shallow() {
+                return Subprocess::new(self.args).pull(remote, branch, strategy);
+            }
+            self.fetch(remote, branch)?;
+
+            let fetch_head = self.repo.find_reference("FETCH_HEAD")?;
+            let fetched = self.repo.reference_to_annotated_commit(&fetch_head)?;
+            let (analysis, _) = self.repo.merge_analysis(&[&fetched])?;
+            if analysis.is_up_to_date() {
+                return Ok(());
+            }
+
+            // Checkouts are done safely, i.e. they fail rather than clobber local modifications,
+            // which is exactly what git pull does too.
+            if analysis.is_fast_forward() {
+                let target = self.repo.find_commit(fetched.id())?;
+                self.repo
+                    .checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))
+                    .context("failed to fast-forward, local changes would be overwritten")?;
+                self.repo
+                    .head()?
+                    .set_target(fetched.id(), &format!("jot: fast-forward to {}", remote))?;
+                return Ok(());
+            }
+
+            match strategy {
+                cli::PullStrategy::Merge => self.merge(remote, branch, &fetched),
+                cli::PullStrategy::Rebase => self.rebase(&fetched),
+                cli::PullStrategy::FfOnly => bail!(
+                    "cannot fast-forward to {}/{}, local and upstream history have diverged",
+                    remote,
+                    branch
+                ),
+            }
+        }
+
+        fn fetch(&self, remote: &str, branch: &str) -> Result<()> {
+            let mut git_remote = self
+                .repo
+                .find_remote(remote)
+                .context(format!("failed to find remote {}", remote))?;
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(remote_callbacks(&self.repo));
+            git_remote
+                .fetch(&[branch], Some(&mut fetch_options), None)
+                .context(format!("failed to fetch {} from {}", branch, remote))
+        }
+
+        fn ahead_behind(&self, remote: &str, branch: &str) -> Result<Option<(usize, usize)>> {
+            let tracking_ref = format!("refs/remotes/{}/{}", remote, branch);
+            let upstream = match self.repo.refname_to_id(&tracking_ref) {
+                Ok(upstream) => upstream,
+                Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+                Err(err) => return Err(err).context(format!("failed to look up {}", tracking_ref)),
+            };
+            let head = self.repo.head()?.peel_to_commit()?.id();
+            Ok(Some(self.repo.graph_ahead_behind(head, upstream)?))
+        }
+
+        fn stash(&self) -> Result<bool> {
+            // Stashing needs a mutable repository, so we just open a second handle for it.
+            let mut repo = Repository::open(self.repo.path())?;
+            let signature = signature(&repo, self.args)?;
+            match repo.stash_save(&signature, AUTOSTASH_MESSAGE, None) {
+                Ok(_) => Ok(true),
+                Err(err) if err.code() == ErrorCode::NotFound => Ok(false),
+                Err(err) => Err(err).context("failed to stash local changes"),
+            }
+        }
+
+        fn unstash(&self) -> Result<()> {
+            let mut repo = Repository::open(self.repo.path())?;
+            repo.stash_pop(0, None)
+                .context("failed to restore stashed local changes")
+        }
+
+        fn drop_stash(&self) -> Result<()> {
+            let mut repo = Repository::open(self.repo.path())?;
+            repo.stash_drop(0)
+                .context("failed to drop stashed local changes")
+        }
+
+        fn has_autostash(&self) -> Result<bool> {
+            let mut repo = Repository::open(self.repo.path())?;
+            let mut has_autostash = false;
+            repo.stash_foreach(|_, message, _| {
+                has_autostash = message.ends_with(AUTOSTASH_MESSAGE);
+                // We only care about the most recent stash, so stop iterating right away.
+                false
+            })?;
+            Ok(has_autostash)
+        }
+
+        fn conflicted_files(&self) -> Result<Vec<PathBuf>> {
+            Ok(self
+                .conflicted_paths()?
+                .into_iter()
+                .map(PathBuf::from)
+                .collect())
+        }
+
+        fn operation_in_progress(&self) -> Result<Option<Operation>> {
+            Ok(match self.repo.state() {
+                RepositoryState::Merge => Some(Operation::Merge),
+                RepositoryState::Rebase
+                | RepositoryState::RebaseInteractive
+                | RepositoryState::RebaseMerge
+                | RepositoryState::ApplyMailboxOrRebase => Some(Operation::Rebase),
+                _ => None,
+            })
+        }
+
+        fn continue_operation(&self, operation: Operation) -> Result<()> {
+            // We never leave rebases in progress ourselves, so if there is one, it was started by
+            // the git binary, and it is the one that knows how to continue it.
+            // The same goes for signed merges, since libgit2 cannot sign commits.
+            if operation == Operation::Rebase || gpg_sign_arg(self.args).is_some() {
+                return Subprocess::new(self.args).continue_operation(operation);
+            }
+
+            let mut parents = vec![self.repo.head()?.peel_to_commit()?];
+            let mut merge_heads = Vec::new();
+            Repository::open(self.repo.path())?.mergehead_foreach(|oid| {
+                merge_heads.push(*oid);
+                true
+            })?;
+            for merge_head in merge_heads {
+                parents.push(self.repo.find_commit(merge_head)?);
+            }
+
+            let tree = self.repo.find_tree(self.repo.index()?.write_tree()?)?;
+            let signature = signature(&self.repo, self.args)?;
+            let message = self
+                .repo
+                .message()
+                .unwrap_or_else(|_| "Merge upstream changes".to_string());
+            self.repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &message,
+                    &tree,
+                    &parents.iter().collect::<Vec<_>>(),
+                )
+                .context("failed to commit the merge")?;
+            self.repo.cleanup_state()?;
+            Ok(())
+        }
+
+        fn changed_files(&self) -> Result<Vec<PathBuf>> {
+            let mut status_options = StatusOptions::new();
+            status_options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true);
+            let statuses = self
+                .repo
+                .statuses(Some(&mut status_options))
+                .context("failed to list changes")?;
+            statuses
+                .iter()
+                .map(|entry| entry.path().map(PathBuf::from))
+                .collect::<Result<Vec<PathBuf>, git2::Error>>()
+                .context("failed to list changes")
+        }
+
+        fn diff_stats(&self, paths: &[PathBuf]) -> Result<Vec<FileDiff>> {
+            if paths.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let head_tree = self.repo.head()?.peel_to_tree()?;
+            let mut diff_options = DiffOptions::new();
+            diff_options
+                .include_untracked(true)
+                .show_untracked_content(true);
+            for path in paths {
+                diff_options.pathspec(path);
+            }
+            let diff = self
+                .repo
+                .diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut diff_options))
+                .context("failed to diff changes")?;
+
+            let mut diffs = Vec::new();
+            for idx in 0..diff.deltas().len() {
+                let Some(delta) = diff.get_delta(idx) else {
+                    continue;
+                };
+                let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                    continue;
+                };
+                let kind = match delta.status() {
+                    Delta::Added | Delta::Untracked => ChangeKind::Add,
+                    Delta::Deleted => ChangeKind::Delete,
+                    _ => ChangeKind::Edit,
+                };
+                let lines = match Patch::from_diff(&diff, idx)? {
+                    Some(patch) if !delta.flags().is_binary() => {
+                        let (_, insertions, deletions) = patch.line_stats()?;
+                        Some((insertions, deletions))
+                    }
+                    _ => None,
+                };
+                diffs.push(FileDiff {
+                    path: path.to_path_buf(),
+                    kind,
+                    lines,
+                });
+            }
+
+            // Files in untracked directories may not be reported individually.
+            for path in paths {
+                if !diffs.iter().any(|diff| diff.path == *path) {
+                    diffs.push(untracked_diff(&self.args.base_dir, path));
+                }
+            }
+            diffs.sort_by(|a, b| a.path.cmp(&b.path));
+            Ok(diffs)
+        }
+
+        fn stage(&self, paths: &[PathBuf]) -> Result<()> {
+            let workdir = self
+                .repo
+                .workdir()
+                .context("cannot stage changes in a bare repository")?;
+            let mut index = self.repo.index()?;
+            for path in paths {
+                let staged = if workdir.join(path).exists() {
+                    index.add_path(path)
+                } else {
+                    index.remove_path(path)
+                };
+                staged.context(format!("failed to stage {}", path.display()))?;
+            }
+            index.write().context("failed to write the index")?;
+            Ok(())
+        }
+
+        fn commit(&self, message: Option<&str>) -> Result<()> {
+            // Prompting for a commit message in the user's configured editor, signing commits and
+            // running hooks are things only the git binary knows how to do properly, so we defer
+            // to it for those.
+            let defers = gpg_sign_arg(self.args).is_some()
+                || (!self.args.no_verify && has_hooks(&COMMIT_HOOKS)?);
+            let Some(message) = message.filter(|_| !defers) else {
+                return Subprocess::new(self.args).commit(message);
+            };
+
+            let tree = self.repo.find_tree(self.repo.index()?.write_tree()?)?;
+            let parent = self.repo.head()?.peel_to_commit()?;
+            if parent.tree_id() == tree.id() {
+                bail!("nothing to commit, the working tree is clean");
+            }
+
+            let signature = signature(&self.repo, self.args).context(
+                "failed to determine the commit author, please set user.name and user.email in \
+                your git config, or the git-author-name and git-author-email flags",
+            )?;
+            self.repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &[&parent],
+                )
+                .context("failed to commit staged changes")?;
+            Ok(())
+        }
+
+        fn push(&self, remote: &str, branch: &str) -> Result<()> {
+            if !self.args.no_verify && has_hooks(&["pre-push"])? {
+                return Subprocess::new(self.args).push(remote, branch);
+            }
+
+            let mut git_remote = self
+                .repo
+                .find_remote(remote)
+                .context(format!("failed to find remote {}", remote))?;
+
+            // libgit2 only reports rejected refs through this callback, rather than as an error.
+            let rejection = RefCell::new(None);
+            let mut callbacks = remote_callbacks(&self.repo);
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(status) = status {
+                    *rejection.borrow_mut() = Some(format!("{}: {}", refname, status));
+                }
+                Ok(())
+            });
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+
+            let head = self.repo.head()?;
+            let refspec = format!(
+                "{}:refs/heads/{}",
+                head.name()
+                    .context("failed to determine the current branch")?,
+                branch
+            );
+            git_remote
+                .push(&[&refspec], Some(&mut push_options))
+                .context(format!("failed to push {} to {}", branch, remote))?;
+            drop(push_options);
+
+            if let Some(rejection) = rejection.into_inner() {
+                bail!("{} rejected the push ({})", remote, rejection);
+            }
+
+            // Unlike git push, libgit2 leaves the remote-tracking branch as it was.
+            let head = self.repo.head()?.peel_to_commit()?.id();
+            self.repo.reference(
+                &format!("refs/remotes/{}/{}", remote, branch),
+                head,
+                true,
+                "jot: push",
+            )?;
+            Ok(())
+        }
+    }
+}
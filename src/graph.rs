@@ -0,0 +1,107 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{json, links, vault};
+
+/// The graph of links between the notes in a vault. Only links from one note to another existing
+/// note are considered edges, i.e. links to attachments, websites or notes that do not exist are
+/// not part of the graph.
+pub struct Graph {
+    pub nodes: Vec<PathBuf>,
+    pub edges: BTreeSet<(PathBuf, PathBuf)>,
+}
+
+impl Graph {
+    pub fn build(base_dir: &Path) -> Result<Graph> {
+        let nodes = vault::notes(base_dir)?;
+        let resolver = links::Resolver::new(&nodes);
+        let mut edges = BTreeSet::new();
+        for source in &nodes {
+            let content = std::fs::read_to_string(base_dir.join(source))
+                .context(format!("failed to read note at {}", source.display()))?;
+            for link in links::extract(&content) {
+                if link.kind == links::LinkKind::Image {
+                    continue;
+                }
+                if let Some(target) = resolver.resolve(source, &link) {
+                    if target != *source && nodes.contains(&target) {
+                        edges.insert((source.clone(), target));
+                    }
+                }
+            }
+        }
+
+        Ok(Graph { nodes, edges })
+    }
+
+    /// Returns the notes that neither link to, nor are linked from, any other note.
+    pub fn orphans(&self) -> Vec<&PathBuf> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                !self
+                    .edges
+                    .iter()
+                    .any(|(source, target)| source == *node || target == *node)
+            })
+            .collect()
+    }
+
+    /// Renders the graph in Graphviz's DOT language.
+    pub fn to_dot(&self) -> String {
+        fn quote(path: &Path) -> String {
+            format!(
+                "\"{}\"",
+                path.display()
+                    .to_string()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+            )
+        }
+
+        let mut dot = String::from("digraph jot {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("    {};\n", quote(node)));
+        }
+        for (source, target) in &self.edges {
+            dot.push_str(&format!("    {} -> {};\n", quote(source), quote(target)));
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Renders the graph as a JSON object with `nodes` and `edges` arrays, which is the shape most
+    /// web-based graph viewers (e.g. d3) expect.
+    pub fn to_json(&self) -> json::Value {
+        let path_value = |path: &PathBuf| json::Value::from(path.display().to_string());
+        json::Value::object([
+            (
+                "nodes",
+                json::Value::Array(
+                    self.nodes
+                        .iter()
+                        .map(|node| json::Value::object([("id", path_value(node))]))
+                        .collect(),
+                ),
+            ),
+            (
+                "edges",
+                json::Value::Array(
+                    self.edges
+                        .iter()
+                        .map(|(source, target)| {
+                            json::Value::object([
+                                ("source", path_value(source)),
+                                ("target", path_value(target)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+
+/// The git log format that parse understands: NUL-separated fields, one commit per line.
+pub static LOG_FORMAT: &str = "--pretty=format:%h%x00%at%x00%s";
+
+/// A commit in the history of base-dir.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub subject: String,
+}
+
+/// Parses the output of git log with LOG_FORMAT.
+pub fn parse(log: &str) -> Result<Vec<Commit>> {
+    log.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields = line.splitn(3, '\0').collect::<Vec<&str>>();
+            let [hash, timestamp, subject] = fields[..] else {
+                bail!("unexpected line in git log output: {}", line);
+            };
+            Ok(Commit {
+                hash: hash.to_string(),
+                timestamp: timestamp.parse().context(format!(
+                    "invalid timestamp in git log output: {}",
+                    timestamp
+                ))?,
+                subject: subject.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A line of a note, and the commit that last changed it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlameLine {
+    /// The abbreviated hash of the commit, which is all zeroes for uncommitted changes.
+    pub hash: String,
+    pub author: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub text: String,
+}
+
+/// Parses the output of git blame --porcelain. In it, each line is preceded by a header naming its
+/// commit, and the details of each commit are only given the first time it appears.
+pub fn parse_blame(blame: &str) -> Result<Vec<BlameLine>> {
+    let mut commits: HashMap<&str, (&str, u64)> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut hash = "";
+    for line in blame.lines() {
+        if let Some(text) = line.strip_prefix('\t') {
+            let (author, timestamp) = commits.get(hash).copied().unwrap_or_default();
+            lines.push(BlameLine {
+                hash: hash[..hash.len().min(7)].to_string(),
+                author: author.to_string(),
+                timestamp,
+                text: text.to_string(),
+            });
+        } else if let Some(author) = line.strip_prefix("author ") {
+            commits.entry(hash).or_default().0 = author;
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            commits.entry(hash).or_default().1 = time
+                .parse()
+                .context(format!("invalid timestamp in git blame output: {}", time))?;
+        } else if let Some((header_hash, _)) = line.split_once(' ') {
+            if header_hash.len() == 40 && header_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                hash = header_hash;
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Describes the given timestamp relative to now, e.g. `3 days ago`.
+pub fn relative_time(timestamp: u64, now: u64) -> String {
+    const UNITS: [(&str, u64); 6] = [
+        ("year", 365 * 24 * 60 * 60),
+        ("month", 30 * 24 * 60 * 60),
+        ("week", 7 * 24 * 60 * 60),
+        ("day", 24 * 60 * 60),
+        ("hour", 60 * 60),
+        ("minute", 60),
+    ];
+
+    let elapsed = now.saturating_sub(timestamp);
+    for (unit, seconds) in UNITS {
+        let count = elapsed / seconds;
+        if count == 1 {
+            return format!("1 {} ago", unit);
+        } else if count > 1 {
+            return format!("{} {}s ago", count, unit);
+        }
+    }
+    "just now".to_string()
+}
+
+#[test]
+fn relative_times() {
+    let now = 1_000_000_000;
+    assert_eq!(relative_time(now - 30, now), "just now");
+    assert_eq!(relative_time(now - 60, now), "1 minute ago");
+    assert_eq!(relative_time(now - 3 * 24 * 60 * 60, now), "3 days ago");
+    assert_eq!(relative_time(now + 60, now), "just now");
+    assert_eq!(
+        parse("abc1234\u{0}1700000000\u{0}edit a.md; add b.md\n").unwrap(),
+        vec![Commit {
+            hash: "abc1234".to_string(),
+            timestamp: 1_700_000_000,
+            subject: "edit a.md; add b.md".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn blame() {
+    let hash = "04221c102fd6ee03994d119ad4c37fad997e0447";
+    let blame = format!(
+        "{0} 1 1 2\nauthor Jane\nauthor-time 1700000000\nsummary a\nfilename a.md\n\tfirst\n\
+        {0} 2 2\n\t\n",
+        hash
+    );
+    let line = |text: &str| BlameLine {
+        hash: "04221c1".to_string(),
+        author: "Jane".to_string(),
+        timestamp: 1_700_000_000,
+        text: text.to_string(),
+    };
+    assert_eq!(parse_blame(&blame).unwrap(), vec![line("first"), line("")]);
+}
@@ -0,0 +1,100 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cli,
+    cmd::{exec_cmd, get_env_var, SHELL_ENV_VARNAME},
+};
+
+/// The points at which jot runs the user's hook invocations, if any were given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PreEdit,
+    PostEdit,
+    PreSync,
+    PostSync,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PreEdit => "pre-edit",
+            Hook::PostEdit => "post-edit",
+            Hook::PreSync => "pre-sync",
+            Hook::PostSync => "post-sync",
+        }
+    }
+
+    fn invocation(self, args: &cli::Args) -> Option<&str> {
+        match self {
+            Hook::PreEdit => args.pre_edit_hook.as_deref(),
+            Hook::PostEdit => args.post_edit_hook.as_deref(),
+            Hook::PreSync => args.pre_sync_hook.as_deref(),
+            Hook::PostSync => args.post_sync_hook.as_deref(),
+        }
+    }
+}
+
+/// Runs the invocation given for the given hook, if any, from base-dir, which jot is in already.
+/// Besides jot's own environment, it gets JOT_HOOK (the hook's name), JOT_BASE_DIR and JOT_NOTE
+/// (the note being edited, for edit hooks), both absolute, and JOT_EXIT_STATUS (0 if what the
+/// hook follows succeeded, 1 otherwise, for post hooks).
+pub fn run(
+    args: &cli::Args,
+    hook: Hook,
+    note: Option<&Path>,
+    succeeded: Option<bool>,
+) -> Result<()> {
+    let Some(invocation) = hook.invocation(args) else {
+        return Ok(());
+    };
+
+    let mut hook_exec = Command::new(get_env_var(SHELL_ENV_VARNAME)?);
+    hook_exec
+        .arg(&args.shell_cmd_flag)
+        .arg(invocation)
+        .env("JOT_HOOK", hook.name())
+        .env("JOT_BASE_DIR", &args.base_dir)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    if let Some(note) = note {
+        hook_exec.env("JOT_NOTE", args.base_dir.join(note));
+    }
+    if let Some(succeeded) = succeeded {
+        hook_exec.env("JOT_EXIT_STATUS", if succeeded { "0" } else { "1" });
+    }
+    exec_cmd(
+        &format!("the {} hook", hook.name()),
+        hook_exec,
+        false,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(())
+}
+
+/// Runs what the given pre and post hooks surround, along with them. A failing pre hook stops it
+/// from running at all. The post hook runs whether it succeeded or not, but if it did not, that
+/// failure is what is reported, and the post hook's own failure is only printed.
+pub fn around<T>(
+    args: &cli::Args,
+    (pre, post): (Hook, Hook),
+    note: Option<&Path>,
+    operation: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    run(args, pre, note, None)?;
+    let result = operation();
+    let post_result = run(args, post, note, Some(result.is_ok()));
+    match (result, post_result) {
+        (Ok(value), post_result) => post_result.map(|_| value),
+        (Err(err), Ok(())) => Err(err),
+        (Err(err), Err(post_err)) => {
+            println!("warning: {:#}", post_err);
+            Err(err)
+        }
+    }
+}
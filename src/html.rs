@@ -0,0 +1,475 @@
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::{cli, cmd::exec_cmd};
+
+static CURL_CMD: &str = "curl";
+
+/// How long fetching a page may take before it is given up on.
+const TIMEOUT_SECS: &str = "30";
+
+/// The elements whose contents are never part of a page's readable content.
+static SKIPPED_ELEMENTS: [&str; 10] = [
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "svg", "iframe",
+];
+
+/// The elements that are blocks of their own, i.e. separated from what is around them by a blank
+/// line.
+static BLOCK_ELEMENTS: [&str; 9] = [
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "table",
+    "tr",
+    "figure",
+    "figcaption",
+];
+
+/// The readable parts of a web page.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Page {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    /// The page's main content, as Markdown.
+    pub body: String,
+}
+
+/// Decodes the character references in the given HTML text, e.g. `&amp;` and `&#39;`.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        // Entities are short, so a `;` much further along is not the end of one.
+        let Some(end) = rest.bytes().take(12).position(|byte| byte == b';') else {
+            decoded.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "hellip" => Some('…'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            _ => match entity.strip_prefix('#') {
+                Some(number) => match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => None,
+            },
+        };
+        match character {
+            Some(character) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Returns the value of the given attribute among the given attributes of a tag, if it has it.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    // Lowercasing ASCII keeps offsets as they are, so they can be used on the original.
+    let lowercase = attributes.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(found) = lowercase[search_from..].find(name) {
+        let start = search_from + found;
+        search_from = start + name.len();
+        let is_boundary = start == 0 || lowercase.as_bytes()[start - 1].is_ascii_whitespace();
+        let rest = attributes[start + name.len()..].trim_start();
+        let Some(value) = rest.strip_prefix('=').map(str::trim_start) else {
+            continue;
+        };
+        if !is_boundary {
+            continue;
+        }
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '>')
+                .next()
+                .unwrap_or_default(),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// Resolves the given link on the page at the given URL, e.g. `https://example.com/a/b` for `b`
+/// on `https://example.com/a/`. Returns None for anchors into the page itself and scripts.
+fn resolve_url(base: &str, href: &str) -> Option<String> {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+        return None;
+    }
+    if href.contains("://") || href.starts_with("mailto:") {
+        return Some(href.to_string());
+    }
+    let (scheme, rest) = base.split_once("://")?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if let Some(path) = href.strip_prefix("//") {
+        return Some(format!("{}://{}", scheme, path));
+    }
+    if href.starts_with('/') {
+        return Some(format!("{}://{}{}", scheme, host, href));
+    }
+    let path = rest[host.len()..]
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let dir = &path[..path.rfind('/').map_or(0, |idx| idx + 1)];
+    let dir = if dir.is_empty() { "/" } else { dir };
+    Some(format!("{}://{}{}{}", scheme, host, dir, href))
+}
+
+/// Returns the byte range of the contents of the first of the given element in the given page, if
+/// it has one.
+fn element_contents(lowercase: &str, name: &str) -> Option<std::ops::Range<usize>> {
+    let open = lowercase.find(&format!("<{}", name))?;
+    let after_name = lowercase.as_bytes().get(open + name.len() + 1)?;
+    if !(after_name.is_ascii_whitespace() || *after_name == b'>') {
+        return None;
+    }
+    let start = open + lowercase[open..].find('>')? + 1;
+    let end = lowercase[start..]
+        .find(&format!("</{}", name))
+        .map_or(lowercase.len(), |end| start + end);
+    Some(start..end)
+}
+
+/// Converts HTML to Markdown, one tag and one piece of text at a time.
+struct Converter<'a> {
+    url: &'a str,
+    markdown: String,
+    /// How many skipped elements, e.g. scripts, the converter is in.
+    skipping: usize,
+    in_pre: bool,
+    /// The lists the converter is in, innermost last, with whether they are ordered and how many
+    /// items they have had.
+    lists: Vec<(bool, usize)>,
+    /// The links the converter is in, innermost last, with their targets and where their text
+    /// starts.
+    links: Vec<(Option<String>, usize)>,
+    /// Where the blockquotes the converter is in start, innermost last.
+    quotes: Vec<usize>,
+}
+
+impl<'a> Converter<'a> {
+    /// Makes sure that what follows starts a new line.
+    fn line_break(&mut self) {
+        let trimmed = self.markdown.trim_end_matches(' ').len();
+        self.markdown.truncate(trimmed);
+        if !self.markdown.is_empty() && !self.markdown.ends_with('\n') {
+            self.markdown.push('\n');
+        }
+    }
+
+    /// Makes sure that what follows starts a new block, i.e. after a blank line.
+    fn block_break(&mut self) {
+        self.line_break();
+        if !self.markdown.is_empty() && !self.markdown.ends_with("\n\n") {
+            self.markdown.push('\n');
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skipping > 0 {
+            return;
+        }
+        let text = decode_entities(text);
+        if self.in_pre {
+            self.markdown.push_str(&text);
+            return;
+        }
+        for (idx, word) in text.split_whitespace().enumerate() {
+            let starts_with_space = idx > 0 || text.starts_with(char::is_whitespace);
+            if starts_with_space && !self.markdown.ends_with([' ', '\n']) {
+                self.markdown.push(' ');
+            }
+            self.markdown.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) && !self.markdown.ends_with([' ', '\n']) {
+            self.markdown.push(' ');
+        }
+    }
+
+    fn open(&mut self, name: &str, attributes: &str) {
+        if SKIPPED_ELEMENTS.contains(&name) {
+            self.skipping += 1;
+            return;
+        }
+        if self.skipping > 0 {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                // The unwrap is fine, since name is one of the headings matched above.
+                let level = name[1..].parse().unwrap();
+                self.markdown.push_str(&"#".repeat(level));
+                self.markdown.push(' ');
+            }
+            "br" => self.markdown.push('\n'),
+            "hr" => {
+                self.block_break();
+                self.markdown.push_str("---");
+                self.block_break();
+            }
+            "strong" | "b" => self.markdown.push_str("**"),
+            "em" | "i" => self.markdown.push('*'),
+            "code" if !self.in_pre => self.markdown.push('`'),
+            "pre" => {
+                self.block_break();
+                self.markdown.push_str("```\n");
+                self.in_pre = true;
+            }
+            "blockquote" => {
+                self.block_break();
+                self.quotes.push(self.markdown.len());
+            }
+            "ul" | "ol" => {
+                self.line_break();
+                self.lists.push((name == "ol", 0));
+            }
+            "li" => {
+                self.line_break();
+                let depth = self.lists.len().saturating_sub(1);
+                self.markdown.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some((true, count)) => {
+                        *count += 1;
+                        self.markdown.push_str(&format!("{}. ", count));
+                    }
+                    _ => self.markdown.push_str("- "),
+                }
+            }
+            "a" => {
+                let href =
+                    attribute(attributes, "href").and_then(|href| resolve_url(self.url, &href));
+                self.links.push((href, self.markdown.len()));
+            }
+            "img" => {
+                if let Some(src) =
+                    attribute(attributes, "src").and_then(|src| resolve_url(self.url, &src))
+                {
+                    let alt = attribute(attributes, "alt").unwrap_or_default();
+                    self.markdown
+                        .push_str(&format!("![{}]({})", alt.trim(), src));
+                }
+            }
+            "td" | "th" => self.markdown.push(' '),
+            _ if BLOCK_ELEMENTS.contains(&name) => self.block_break(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if SKIPPED_ELEMENTS.contains(&name) {
+            self.skipping = self.skipping.saturating_sub(1);
+            return;
+        }
+        if self.skipping > 0 {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.block_break(),
+            "strong" | "b" => self.markdown.push_str("**"),
+            "em" | "i" => self.markdown.push('*'),
+            "code" if !self.in_pre => self.markdown.push('`'),
+            "pre" => {
+                self.line_break();
+                self.markdown.push_str("```");
+                self.in_pre = false;
+                self.block_break();
+            }
+            "blockquote" => {
+                if let Some(start) = self.quotes.pop() {
+                    let quoted: String = self.markdown[start..]
+                        .trim_end()
+                        .lines()
+                        .map(|line| format!("> {}\n", line).replace("> \n", ">\n"))
+                        .collect();
+                    self.markdown.truncate(start);
+                    self.markdown.push_str(&quoted);
+                }
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                match self.lists.is_empty() {
+                    true => self.block_break(),
+                    false => self.line_break(),
+                }
+            }
+            "a" => {
+                if let Some((Some(href), start)) = self.links.pop() {
+                    let text = self.markdown[start..].trim().to_string();
+                    if !text.is_empty() {
+                        self.markdown.truncate(start);
+                        self.markdown.push_str(&format!("[{}]({})", text, href));
+                    }
+                }
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => self.block_break(),
+            _ => {}
+        }
+    }
+}
+
+/// Converts the given HTML to Markdown. Links in it are resolved against the given URL.
+fn to_markdown(html: &str, url: &str) -> String {
+    let mut converter = Converter {
+        url,
+        markdown: String::new(),
+        skipping: 0,
+        in_pre: false,
+        lists: Vec::new(),
+        links: Vec::new(),
+        quotes: Vec::new(),
+    };
+
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        converter.text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with(['!', '?']) {
+            continue;
+        }
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let tag = tag.trim_end_matches('/');
+        let (name, attributes) = tag
+            .split_once(|c: char| c.is_whitespace())
+            .unwrap_or((tag, ""));
+        let name = name.to_ascii_lowercase();
+        match closing {
+            true => converter.close(&name),
+            false => converter.open(&name, attributes),
+        }
+    }
+    converter.text(rest);
+
+    let mut markdown = String::new();
+    let mut blank_lines = 0;
+    for line in converter.markdown.lines() {
+        let line = line.trim_end();
+        blank_lines = if line.is_empty() { blank_lines + 1 } else { 0 };
+        if blank_lines < 2 {
+            markdown.push_str(line);
+            markdown.push('\n');
+        }
+    }
+    markdown.trim().to_string()
+}
+
+/// Fetches the web page at the given URL, following redirects.
+pub fn fetch(args: &cli::Args, url: &str) -> Result<String> {
+    let mut curl_exec = Command::new(CURL_CMD);
+    curl_exec
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--max-time",
+            TIMEOUT_SECS,
+        ])
+        .args(["--user-agent", "jot"])
+        .arg("--")
+        .arg(url);
+    let (html, _) = exec_cmd(
+        &format!("fetching {}", url),
+        curl_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(html)
+}
+
+/// Returns the readable parts of the given web page, fetched from the given URL: its title, its
+/// byline and its main content, i.e. that of its article element, or else its main element, or
+/// else its body, as Markdown.
+pub fn readable(html: &str, url: &str) -> Page {
+    let lowercase = html.to_ascii_lowercase();
+    let title = element_contents(&lowercase, "title")
+        .map(|range| decode_entities(html[range].trim()))
+        .filter(|title| !title.is_empty());
+
+    let mut byline = None;
+    let mut search_from = 0;
+    while let Some(found) = lowercase[search_from..].find("<meta") {
+        let start = search_from + found;
+        let end = lowercase[start..]
+            .find('>')
+            .map_or(lowercase.len(), |end| start + end);
+        search_from = end;
+        let attributes = &html[start + "<meta".len()..end];
+        if attribute(attributes, "name").is_some_and(|name| name.eq_ignore_ascii_case("author")) {
+            byline = attribute(attributes, "content").filter(|author| !author.trim().is_empty());
+            break;
+        }
+    }
+
+    let body = ["article", "main", "body"]
+        .iter()
+        .find_map(|name| element_contents(&lowercase, name))
+        .unwrap_or(0..html.len());
+    Page {
+        title,
+        byline,
+        body: to_markdown(&html[body], url),
+    }
+}
+
+#[test]
+fn convert_html() {
+    let html = "<html><head><title>A &amp; B</title><meta name=\"author\" content=\"Ann\">\
+                <script>var x = '<p>';</script></head><body><nav><a href=\"/\">Home</a></nav>\
+                <article><h1>A &amp; B</h1><p>Some <b>bold</b> and <a href=\"c\">a link</a>.</p>\
+                <ul><li>one</li><li>two<ol><li>nested</li></ol></li></ul>\
+                <pre><code>let x = 1;\n</code></pre><blockquote><p>quoted</p></blockquote>\
+                <img src=\"/i.png\" alt=\"pic\"></article></body></html>";
+    let page = readable(html, "https://example.com/posts/a.html");
+    assert_eq!(page.title.as_deref(), Some("A & B"));
+    assert_eq!(page.byline.as_deref(), Some("Ann"));
+    assert_eq!(
+        page.body,
+        "# A & B\n\nSome **bold** and [a link](https://example.com/posts/c).\n\n\
+         - one\n- two\n  1. nested\n\n```\nlet x = 1;\n```\n\n> quoted\n\n\
+         ![pic](https://example.com/i.png)"
+    );
+}
@@ -0,0 +1,190 @@
+use crate::date::Date;
+
+/// When an event starts or ends, in local time. All-day events have no time of day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Time {
+    pub date: Date,
+    /// The time of day, e.g. 09:30.
+    pub time: Option<String>,
+}
+
+/// An event in an iCalendar (.ics) file, with just the properties that meeting notes need.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    pub summary: String,
+    pub start: Option<Time>,
+    pub end: Option<Time>,
+    pub location: Option<String>,
+    /// The attendees' names, or their email addresses for those without one.
+    pub attendees: Vec<String>,
+}
+
+/// Undoes the escaping of commas, semicolons, backslashes and newlines in text values.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// A content line, e.g. `ATTENDEE;CN="Doe, Jane":mailto:jane@example.com`.
+struct Property<'a> {
+    name: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+    value: &'a str,
+}
+
+/// Splits a content line into its property name, its parameters and its value. Colons in quoted
+/// parameter values do not end them.
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(idx, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(idx),
+        _ => None,
+    })?;
+
+    let mut params = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    let head = &line[..colon];
+    for (idx, c) in head.char_indices().chain([(head.len(), ';')]) {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                params.push(&head[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    let name = params.remove(0);
+    let params = params
+        .into_iter()
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key, value.trim_matches('"')))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: &line[colon + 1..],
+    })
+}
+
+/// Parses a DATE or DATE-TIME value, e.g. `20240501` or `20240501T093000Z`. Times in UTC are
+/// moved into the local timezone by the given offset, in seconds, and any others are assumed to
+/// be local already.
+fn parse_time(value: &str, utc_offset: i64) -> Option<Time> {
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    let date = Date {
+        year: number(0..4)?,
+        month: number(4..6)? as u32,
+        day: number(6..8)? as u32,
+    };
+    if value.get(8..9) != Some("T") {
+        return Some(Time { date, time: None });
+    }
+
+    let mut seconds = date.days() * 86_400 + number(9..11)? * 3600 + number(11..13)? * 60;
+    if value.ends_with('Z') {
+        seconds += utc_offset;
+    }
+    let seconds_of_day = seconds.rem_euclid(86_400);
+    Some(Time {
+        date: Date::from_days(seconds.div_euclid(86_400)),
+        time: Some(format!(
+            "{:02}:{:02}",
+            seconds_of_day / 3600,
+            seconds_of_day % 3600 / 60
+        )),
+    })
+}
+
+/// Returns the events in the given iCalendar file contents, in the order they are in, with their
+/// times in the timezone that is the given offset, in seconds, from UTC.
+pub fn events(ics: &str, utc_offset: i64) -> Vec<Event> {
+    // Long lines are folded onto lines that start with whitespace.
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in &lines {
+        let Some(Property {
+            name,
+            params,
+            value,
+        }) = parse_property(line)
+        else {
+            continue;
+        };
+        match (name.to_uppercase().as_str(), value, &mut event) {
+            ("BEGIN", "VEVENT", _) => event = Some(Event::default()),
+            ("END", "VEVENT", _) => events.extend(event.take()),
+            ("SUMMARY", _, Some(event)) => event.summary = unescape(value),
+            ("LOCATION", _, Some(event)) => {
+                event.location = Some(unescape(value)).filter(|location| !location.is_empty())
+            }
+            ("DTSTART", _, Some(event)) => event.start = parse_time(value, utc_offset),
+            ("DTEND", _, Some(event)) => event.end = parse_time(value, utc_offset),
+            ("ATTENDEE", _, Some(event)) => {
+                let name = params
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("CN"))
+                    .map(|(_, name)| name.to_string());
+                let email = value
+                    .strip_prefix("mailto:")
+                    .or_else(|| value.strip_prefix("MAILTO:"))
+                    .unwrap_or(value);
+                event
+                    .attendees
+                    .push(name.unwrap_or_else(|| email.to_string()));
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[test]
+fn parse_events() {
+    let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Weekly sync\\, Q3\r\n\
+               DTSTART:20240501T223000Z\r\nDTEND;TZID=Europe/Berlin:20240502T010000\r\n\
+               ATTENDEE;CN=\"Doe, Jane\";ROLE=REQ-PARTICIPANT:mailto:jane@example.com\r\n\
+               ATTENDEE:mailto:bob@exa\r\n mple.com\r\nEND:VEVENT\r\n\
+               BEGIN:VEVENT\r\nSUMMARY:Offsite\r\nDTSTART;VALUE=DATE:20240510\r\nEND:VEVENT\r\n\
+               END:VCALENDAR\r\n";
+    let events = events(ics, 2 * 3600);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].summary, "Weekly sync, Q3");
+    assert_eq!(
+        events[0].start,
+        Some(Time {
+            date: Date::from_days(19_845),
+            time: Some("00:30".to_string())
+        })
+    );
+    assert_eq!(
+        events[0].end.as_ref().unwrap().time.as_deref(),
+        Some("01:00")
+    );
+    assert_eq!(events[0].attendees, vec!["Doe, Jane", "bob@example.com"]);
+    assert_eq!(events[1].start.as_ref().unwrap().time, None);
+}
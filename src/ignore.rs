@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// The name of the file, at the root of base-dir, that lists patterns of paths that jot should
+/// never stage when syncing.
+pub static JOTIGNORE_FILENAME: &str = ".jotignore";
+
+/// A single gitignore-style pattern.
+struct Pattern {
+    glob: Vec<char>,
+    /// Negated patterns (`!pattern`) re-include paths excluded by an earlier pattern.
+    negated: bool,
+    /// Patterns ending in `/` only match directories.
+    dir_only: bool,
+    /// Patterns containing a `/` are matched against the full path (relative to base-dir) rather
+    /// than just the file name.
+    anchored: bool,
+}
+
+/// A set of gitignore-style patterns. The syntax is a subset of gitignore's: `*`, `?`, `**`,
+/// leading `/` anchoring, trailing `/` for directories, `!` negation and `#` comments.
+pub struct Ignore {
+    patterns: Vec<Pattern>,
+}
+
+/// Matches the given text against the given glob, where `*` and `?` do not match `/`, but `**`
+/// does.
+fn glob_match(glob: &[char], text: &[char]) -> bool {
+    match glob.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) if rest.first() == Some(&'*') => {
+            // `**/` may also match nothing at all, e.g. `a/**/b` matches `a/b`.
+            let rest = &rest[1..];
+            let rest = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len())
+                .filter(|idx| *idx == 0 || text[idx - 1] == '/')
+                .any(|idx| glob_match(rest, &text[idx..]))
+        }
+        Some(('*', rest)) => {
+            let component_len = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=component_len).any(|idx| glob_match(rest, &text[idx..]))
+        }
+        Some(('?', rest)) => {
+            text.first().is_some_and(|c| *c != '/') && glob_match(rest, &text[1..])
+        }
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+impl Pattern {
+    fn parse(line: &str) -> Option<Pattern> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+
+        Some(Pattern {
+            glob: line.chars().collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let text = if self.anchored {
+            path.to_string_lossy()
+        } else {
+            path.file_name().unwrap_or_default().to_string_lossy()
+        };
+        glob_match(&self.glob, &text.chars().collect::<Vec<char>>())
+    }
+}
+
+impl Ignore {
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Ignore {
+        Ignore {
+            patterns: lines.into_iter().filter_map(Pattern::parse).collect(),
+        }
+    }
+
+    /// Loads the patterns from base-dir's .jotignore (if it exists), followed by the given extra
+    /// patterns.
+    pub fn load(base_dir: &Path, extra_patterns: &[String]) -> Result<Ignore> {
+        let jotignore_path = base_dir.join(JOTIGNORE_FILENAME);
+        let jotignore = if jotignore_path.exists() {
+            std::fs::read_to_string(&jotignore_path)
+                .context(format!("failed to read {}", jotignore_path.display()))?
+        } else {
+            String::new()
+        };
+
+        Ok(Ignore::parse(
+            jotignore
+                .lines()
+                .chain(extra_patterns.iter().map(String::as_str)),
+        ))
+    }
+
+    fn is_match(&self, path: &Path, is_dir: bool) -> bool {
+        // Like gitignore, the last matching pattern wins.
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(path, is_dir))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+
+    /// Returns the patterns as rsync filter rules, e.g. `- *.swp`. rsync applies the first rule
+    /// that matches rather than the last, so the rules are in reverse order.
+    pub fn rsync_filters(&self) -> Vec<String> {
+        self.patterns
+            .iter()
+            .rev()
+            .map(|pattern| {
+                format!(
+                    "{} {}{}{}",
+                    if pattern.negated { "+" } else { "-" },
+                    if pattern.anchored { "/" } else { "" },
+                    pattern.glob.iter().collect::<String>(),
+                    if pattern.dir_only { "/" } else { "" }
+                )
+            })
+            .collect()
+    }
+
+    /// Returns true if the given file (relative to base-dir) is ignored, either directly or because
+    /// one of the directories it is in is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let mut prefix = PathBuf::new();
+        let mut components = path.components().peekable();
+        while let Some(component) = components.next() {
+            prefix.push(component);
+            let is_dir = components.peek().is_some();
+            if self.is_match(&prefix, is_dir) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[test]
+fn ignore_patterns() {
+    let ignore = Ignore::parse([
+        "# editor files",
+        "*.swp",
+        ".DS_Store",
+        "scratch/",
+        "/drafts/**/*.md",
+        "!drafts/keep/*.md",
+    ]);
+    assert!(ignore.is_ignored(Path::new("a/.note.md.swp")));
+    assert!(ignore.is_ignored(Path::new("a/b/.DS_Store")));
+    assert!(ignore.is_ignored(Path::new("a/scratch/idea.md")));
+    assert!(!ignore.is_ignored(Path::new("a/scratch")));
+    assert!(ignore.is_ignored(Path::new("drafts/x/y.md")));
+    assert!(ignore.is_ignored(Path::new("drafts/y.md")));
+    assert!(!ignore.is_ignored(Path::new("drafts/keep/y.md")));
+    assert!(!ignore.is_ignored(Path::new("notes/drafts/y.md")));
+}
+
+#[test]
+fn rsync_filters() {
+    let ignore = Ignore::parse(["*.swp", "scratch/", "drafts/*.md", "!drafts/keep.md"]);
+    assert_eq!(
+        ignore.rsync_filters(),
+        [
+            "+ /drafts/keep.md",
+            "- /drafts/*.md",
+            "- scratch/",
+            "- *.swp"
+        ]
+    );
+}
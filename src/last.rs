@@ -0,0 +1,38 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::git;
+
+/// The file that the note opened last is recorded in.
+const LAST_NOTE: &str = "jot-last";
+
+/// Returns where the note opened last is recorded. Like the lock, it lives in the .git directory,
+/// so that it is never synced, unless base-dir is not a git repository, in which case it is
+/// hidden in base-dir instead.
+fn path() -> Result<PathBuf> {
+    match git::is_repository()? {
+        true => git::git_path(LAST_NOTE),
+        false => Ok(PathBuf::from(format!(".{}", LAST_NOTE))),
+    }
+}
+
+/// Records the given note, relative to base-dir, as the one opened last.
+pub fn record(note: &Path) -> Result<()> {
+    let path = path()?;
+    std::fs::write(&path, format!("{}\n", note.display()))
+        .context(format!("failed to write {}", path.display()))
+}
+
+/// Returns the note, relative to base-dir, that was opened last, if any has been.
+pub fn read() -> Result<Option<PathBuf>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let note =
+        std::fs::read_to_string(&path).context(format!("failed to read {}", path.display()))?;
+    Ok(Some(note.trim_end_matches('\n'))
+        .filter(|note| !note.is_empty())
+        .map(PathBuf::from))
+}
@@ -0,0 +1,102 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, cmd::exec_cmd, git::GIT_CMD};
+
+/// Returns true if Git LFS is installed.
+pub fn is_installed() -> bool {
+    Command::new(GIT_CMD)
+        .args(["lfs", "version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Returns true if the .gitattributes in the given directory makes Git LFS store any files.
+pub fn is_used(base_dir: &Path) -> Result<bool> {
+    let gitattributes_path = base_dir.join(".gitattributes");
+    if !gitattributes_path.exists() {
+        return Ok(false);
+    }
+    let gitattributes = std::fs::read_to_string(&gitattributes_path)
+        .context(format!("failed to read {}", gitattributes_path.display()))?;
+    Ok(gitattributes
+        .lines()
+        .any(|line| !line.trim_start().starts_with('#') && line.contains("filter=lfs")))
+}
+
+/// Fails with guidance on installing Git LFS if the vault in the given directory uses it, but it
+/// is not installed, since syncing would then commit and check out pointers in place of files.
+pub fn check(base_dir: &Path) -> Result<()> {
+    if is_used(base_dir)? && !is_installed() {
+        bail!(
+            "base-dir stores files with Git LFS, which is not installed, please install it (see \
+            https://git-lfs.com) and run git lfs install"
+        );
+    }
+    Ok(())
+}
+
+/// Returns true if the given file (relative to the repository's root) is stored with Git LFS.
+pub fn is_tracked(path: &Path) -> Result<bool> {
+    let output = Command::new(GIT_CMD)
+        .args(["check-attr", "filter", "--"])
+        .arg(path)
+        .output()
+        .context(format!(
+            "failed to check the attributes of {}",
+            path.display()
+        ))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .ends_with(": filter: lfs"))
+}
+
+/// Returns the pattern that files like the given one are tracked with: every file with its
+/// extension, or, if it has none, just itself.
+pub fn pattern(path: &Path) -> String {
+    match path.extension() {
+        Some(ext) => format!("*.{}", ext.to_string_lossy()),
+        None => format!("/{}", path.display()),
+    }
+}
+
+/// Makes Git LFS store the files matching the given pattern from now on, setting it up for the
+/// repository first if needed.
+pub fn track(args: &cli::Args, pattern: &str) -> Result<()> {
+    if !is_installed() {
+        bail!(
+            "files matching {} should be stored with Git LFS, which is not installed, please \
+            install it (see https://git-lfs.com), or raise the lfs-threshold-kib flag",
+            pattern
+        );
+    }
+    let mut git_lfs_install_exec = Command::new(GIT_CMD);
+    git_lfs_install_exec.args(["lfs", "install", "--local"]);
+    exec_cmd(
+        "setting up Git LFS",
+        git_lfs_install_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    let mut git_lfs_track_exec = Command::new(GIT_CMD);
+    git_lfs_track_exec.args(["lfs", "track", "--", pattern]);
+    exec_cmd(
+        "tracking with Git LFS",
+        git_lfs_track_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn lfs_patterns() {
+    assert_eq!(pattern(Path::new("images/diagram.png")), "*.png");
+    assert_eq!(pattern(Path::new("bin/blob")), "/bin/blob");
+}
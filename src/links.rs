@@ -0,0 +1,484 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{vault, zettel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A regular Markdown link, e.g. `[text](path/to/note.md)`.
+    Markdown,
+    /// A Markdown image reference, e.g. `![alt](images/diagram.png)`.
+    Image,
+    /// A wiki-link, e.g. `[[note]]`, `[[note|alias]]` or `[[note#heading]]`.
+    Wiki,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// The raw link target, exactly as it was written in the note.
+    pub target: String,
+    /// The (1-indexed) line number the link was found on.
+    pub line: usize,
+    /// The byte range of the raw link target in the note it was found in.
+    pub span: Range<usize>,
+}
+
+impl Link {
+    /// Splits the link's target into the note it points at, which is empty if it points into the
+    /// note it is in, and the heading (or anchor) it points at in that note, if any. A
+    /// wiki-link's alias is left out.
+    fn note_and_heading(&self) -> (&str, Option<&str>) {
+        let target = match self.kind {
+            LinkKind::Wiki => self.target.split('|').next().unwrap_or_default(),
+            LinkKind::Markdown | LinkKind::Image => &self.target,
+        };
+        match target.split_once('#') {
+            Some((note, heading)) => (note.trim(), Some(heading.trim())),
+            None => (target.trim(), None),
+        }
+    }
+}
+
+/// Returns true if the given link target points somewhere outside of the vault, e.g. a website.
+pub fn is_external(target: &str) -> bool {
+    // This is not a proper URL scheme check, but it catches everything people actually write,
+    // e.g. https://, ftp:// and mailto:.
+    target.contains("://") || target.starts_with("mailto:")
+}
+
+/// Parses the destination of a Markdown link, i.e. everything after the opening parenthesis.
+/// Returns the target, its byte range in the given text, and how many bytes were consumed,
+/// including the closing parenthesis.
+fn parse_destination(rest: &str) -> Option<(String, Range<usize>, usize)> {
+    let (target, target_range, target_end) = if let Some(stripped) = rest.strip_prefix('<') {
+        let end = stripped.find('>')?;
+        (stripped[..end].to_string(), 1..end + 1, end + 2)
+    } else {
+        // Parentheses are allowed in destinations, so long as they are balanced.
+        let mut depth = 0;
+        let mut end = rest.len();
+        for (idx, c) in rest.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => {
+                    end = idx;
+                    break;
+                }
+                ')' => depth -= 1,
+                c if c.is_whitespace() => {
+                    end = idx;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        (rest[..end].to_string(), 0..end, end)
+    };
+
+    // Skip past any title, e.g. `[text](note.md "title")`, to the closing parenthesis.
+    let close = rest[target_end..].find(')')?;
+    Some((target, target_range, target_end + close + 1))
+}
+
+/// Extracts the links from the given line, which is the given (1-indexed) line of its note and
+/// starts at the given byte offset in it.
+fn extract_from_line(line: &str, lineno: usize, offset: usize, links: &mut Vec<Link>) {
+    let bytes = line.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            // Links inside of inline code are not links.
+            b'`' => match line[idx + 1..].find('`') {
+                Some(end) => {
+                    idx += end + 2;
+                    continue;
+                }
+                None => break,
+            },
+            b'[' if line[idx..].starts_with("[[") => {
+                if let Some(end) = line[idx + 2..].find("]]") {
+                    links.push(Link {
+                        kind: LinkKind::Wiki,
+                        target: line[idx + 2..idx + 2 + end].to_string(),
+                        line: lineno,
+                        span: offset + idx + 2..offset + idx + 2 + end,
+                    });
+                    idx += end + 4;
+                    continue;
+                }
+            }
+            b'[' => {
+                if let Some(close) = line[idx + 1..].find(']') {
+                    let after_text = idx + close + 2;
+                    if line[after_text..].starts_with('(') {
+                        if let Some((target, range, consumed)) =
+                            parse_destination(&line[after_text + 1..])
+                        {
+                            let start = offset + after_text + 1;
+                            let is_image = idx > 0 && bytes[idx - 1] == b'!';
+                            links.push(Link {
+                                kind: if is_image {
+                                    LinkKind::Image
+                                } else {
+                                    LinkKind::Markdown
+                                },
+                                target,
+                                line: lineno,
+                                span: start + range.start..start + range.end,
+                            });
+                            idx = after_text + 1 + consumed;
+                            continue;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+}
+
+/// Extracts all Markdown links, image references and wiki-links from the given note contents.
+/// Anything inside of code blocks or inline code is ignored.
+pub fn extract(content: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+    let mut offset = 0;
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        extract_from_line(line, idx + 1, line_offset, &mut links);
+    }
+
+    links
+}
+
+/// Decodes percent-encoded bytes (e.g. `%20`) in a Markdown link target. Anything that isn't a
+/// valid escape is left as-is.
+fn percent_decode(target: &str) -> String {
+    let bytes = target.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let escape = std::str::from_utf8(&bytes[idx + 1..idx + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(escape, 16) {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the anchor a Markdown link uses to point at the given heading, e.g. `my-heading` for
+/// `My heading!`. Anchors are anchors of themselves.
+pub fn anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Percent-encodes the characters in the given path that would otherwise end a Markdown link's
+/// target early.
+fn percent_encode(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// Resolves links found in notes to the paths (relative to base-dir) that they point at.
+pub struct Resolver<'a> {
+    notes: &'a [PathBuf],
+}
+
+impl<'a> Resolver<'a> {
+    /// Creates a resolver over the given notes, which are expected to be relative to base-dir, as
+    /// returned by vault::notes().
+    pub fn new(notes: &'a [PathBuf]) -> Resolver<'a> {
+        Resolver { notes }
+    }
+
+    fn resolve_wiki(&self, target: &str) -> PathBuf {
+        // Wiki-links may carry an alias or a heading, neither of which matter for resolution.
+        let name = target.split(['|', '#']).next().unwrap_or_default().trim();
+        let wanted = Path::new(name);
+
+        // Prefer an exact match on the path (sans extension), and only then fall back to matching
+        // on the file name alone, which is how most wiki-link flavored tools behave.
+        self.notes
+            .iter()
+            .find(|note| note.with_extension("") == wanted || note.as_path() == wanted)
+            .or_else(|| {
+                self.notes
+                    .iter()
+                    .find(|note| note.file_stem().is_some_and(|stem| stem == name))
+            })
+            // Zettelkasten notes are linked to by their ID alone, e.g. [[202405021130]] for
+            // 202405021130-some-title.md.
+            .or_else(|| {
+                self.notes
+                    .iter()
+                    .find(|note| zettel::is_id(name) && zettel::note_id(note) == Some(name))
+            })
+            .cloned()
+            // If nothing matches, then the link points at a note that does not exist (yet). Most
+            // tools would create it under the vault's root, so that is where we say it points.
+            .unwrap_or_else(|| wanted.with_extension("md"))
+    }
+
+    fn resolve_markdown(&self, source: &Path, target: &str) -> Option<PathBuf> {
+        let path = target.split(['#', '?']).next().unwrap_or_default();
+        if path.is_empty() {
+            // This is an anchor into the source note itself.
+            return None;
+        }
+
+        let decoded = percent_decode(path);
+        let joined = match decoded.strip_prefix('/') {
+            Some(from_root) => PathBuf::from(from_root),
+            None => source
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(&decoded),
+        };
+        let resolved = vault::normalize(&joined)?;
+
+        // Some tools omit the extension in Markdown links too, so be lenient about it.
+        if resolved.extension().is_none() {
+            let with_ext = resolved.with_extension("md");
+            if self.notes.contains(&with_ext) {
+                return Some(with_ext);
+            }
+        }
+
+        Some(resolved)
+    }
+
+    /// Returns the name wiki-links use for the given note: its file stem, unless another note has
+    /// the same one, in which case its path without its extension.
+    fn wiki_name(&self, note: &Path) -> String {
+        let stem = note.file_stem().unwrap_or_default();
+        let is_ambiguous = self
+            .notes
+            .iter()
+            .any(|other| other != note && other.file_stem() == Some(stem));
+        let name = match is_ambiguous {
+            true => note.with_extension(""),
+            false => PathBuf::from(stem),
+        };
+        name.to_string_lossy().replace('\\', "/")
+    }
+
+    /// Returns the given note contents, which moved from the given path to the other given path
+    /// (both relative to base-dir, and possibly the same), with their links rewritten to point at
+    /// where they pointed before. Links to notes or headings the given function says have moved
+    /// are rewritten to point at where they moved to instead. The function is given the note a
+    /// link points at and the heading it points at in it, if any, and returns the note it moved
+    /// to and the heading to point at in it, if any.
+    pub fn relink(
+        &self,
+        content: &str,
+        from: &Path,
+        to: &Path,
+        moved: impl Fn(&Path, Option<&str>) -> Option<(PathBuf, Option<String>)>,
+    ) -> String {
+        let mut relinked = String::new();
+        let mut end = 0;
+        for link in extract(content) {
+            if is_external(&link.target) {
+                continue;
+            }
+            let (note, heading) = link.note_and_heading();
+            let target = match note.is_empty() {
+                true => Some(from.to_path_buf()),
+                false => self.resolve(from, &link),
+            };
+            let Some(target) = target else {
+                continue;
+            };
+
+            let (new_target, new_heading) = match moved(&target, heading) {
+                Some(moved_to) => moved_to,
+                // Links into the note itself move along with it, and links elsewhere from a
+                // note that did not move keep pointing the same way.
+                None if note.is_empty() || from == to || link.kind == LinkKind::Wiki => continue,
+                None => (target, heading.map(str::to_string)),
+            };
+
+            let mut rewritten = match link.kind {
+                LinkKind::Wiki if new_target == to => String::new(),
+                LinkKind::Wiki => self.wiki_name(&new_target),
+                LinkKind::Markdown | LinkKind::Image if new_target == to => String::new(),
+                LinkKind::Markdown | LinkKind::Image => percent_encode(
+                    &vault::relative_to(to.parent().unwrap_or_else(|| Path::new("")), &new_target)
+                        .to_string_lossy()
+                        .replace('\\', "/"),
+                ),
+            };
+            if let Some(heading) = new_heading {
+                rewritten.push('#');
+                match link.kind {
+                    LinkKind::Wiki => rewritten.push_str(&heading),
+                    LinkKind::Markdown | LinkKind::Image => rewritten.push_str(&anchor(&heading)),
+                }
+            }
+            if rewritten.is_empty() {
+                // A link to the note it is in, without a heading, can only be written as a path.
+                rewritten = match link.kind {
+                    LinkKind::Wiki => self.wiki_name(to),
+                    LinkKind::Markdown | LinkKind::Image => {
+                        percent_encode(&to.file_name().unwrap_or_default().to_string_lossy())
+                    }
+                };
+            }
+            if let Some((_, alias)) = link.target.split_once('|') {
+                rewritten.push('|');
+                rewritten.push_str(alias);
+            }
+
+            relinked.push_str(&content[end..link.span.start]);
+            relinked.push_str(&rewritten);
+            end = link.span.end;
+        }
+        relinked.push_str(&content[end..]);
+        relinked
+    }
+
+    /// Returns the path, relative to base-dir, that the given link (found in source) points at.
+    /// Note that the returned path need not exist. Returns None if the link does not point at
+    /// anything inside of the vault, e.g. external URLs or anchors into the source note itself.
+    pub fn resolve(&self, source: &Path, link: &Link) -> Option<PathBuf> {
+        if is_external(&link.target) {
+            return None;
+        }
+
+        match link.kind {
+            LinkKind::Wiki => Some(self.resolve_wiki(&link.target)),
+            LinkKind::Markdown | LinkKind::Image => self.resolve_markdown(source, &link.target),
+        }
+    }
+}
+
+#[test]
+fn extract_links() {
+    let content = "See [foo](a/foo.md \"Foo\") and [[bar|Bar]].\n\
+                   ![diagram](<images/my diagram.png>) `[not](a link)`\n\
+                   ```\n\
+                   [[nor this]]\n\
+                   ```\n";
+    let links = extract(content);
+    let targets: Vec<(LinkKind, &str, usize)> = links
+        .iter()
+        .map(|link| (link.kind, link.target.as_str(), link.line))
+        .collect();
+    assert_eq!(
+        targets,
+        vec![
+            (LinkKind::Markdown, "a/foo.md", 1),
+            (LinkKind::Wiki, "bar|Bar", 1),
+            (LinkKind::Image, "images/my diagram.png", 2),
+        ]
+    );
+}
+
+#[test]
+fn resolve_links() {
+    let notes = vec![
+        PathBuf::from("a/foo.md"),
+        PathBuf::from("b/bar.md"),
+        PathBuf::from("z/202405021130-some-title.md"),
+    ];
+    let resolver = Resolver::new(&notes);
+    let source = Path::new("b/source.md");
+    let link = |kind, target: &str| Link {
+        kind,
+        target: target.to_string(),
+        line: 1,
+        span: 0..0,
+    };
+
+    assert_eq!(
+        resolver.resolve(source, &link(LinkKind::Markdown, "../a/foo.md#heading")),
+        Some(PathBuf::from("a/foo.md"))
+    );
+    assert_eq!(
+        resolver.resolve(source, &link(LinkKind::Wiki, "bar#heading")),
+        Some(PathBuf::from("b/bar.md"))
+    );
+    assert_eq!(
+        resolver.resolve(source, &link(LinkKind::Wiki, "202405021130")),
+        Some(PathBuf::from("z/202405021130-some-title.md"))
+    );
+    assert_eq!(
+        resolver.resolve(source, &link(LinkKind::Markdown, "https://example.com")),
+        None
+    );
+}
+
+#[test]
+fn relink_notes() {
+    let notes = vec![
+        PathBuf::from("a.md"),
+        PathBuf::from("b/b.md"),
+        PathBuf::from("c.md"),
+    ];
+    let resolver = Resolver::new(&notes);
+    let moved = |note: &Path, heading: Option<&str>| {
+        (note == Path::new("a.md")).then(|| {
+            (
+                PathBuf::from("c.md"),
+                heading.map(str::to_string).or(Some("A note".to_string())),
+            )
+        })
+    };
+
+    let content = "[x](a.md) [[a#Sub|y]] [z](#top) [w](b/b.md#h) [v](https://a.md)\n";
+    assert_eq!(
+        resolver.relink(content, Path::new("c.md"), Path::new("c.md"), moved),
+        "[x](#a-note) [[#Sub|y]] [z](#top) [w](b/b.md#h) [v](https://a.md)\n"
+    );
+    assert_eq!(
+        resolver.relink(
+            "[x](../a.md) [[a#Sub|y]]\n",
+            Path::new("b/b.md"),
+            Path::new("b/b.md"),
+            moved
+        ),
+        "[x](../c.md#a-note) [[c#Sub|y]]\n"
+    );
+    assert_eq!(
+        resolver.relink(
+            "[w](b.md#h) [[c]]\n",
+            Path::new("b/x.md"),
+            Path::new("x.md"),
+            moved
+        ),
+        "[w](b/b.md#h) [[c]]\n"
+    );
+}
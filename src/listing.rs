@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{cli, frontmatter, json, outline, pins, stats, vault};
+
+/// The git log format that parse_times understands: a NUL-prefixed timestamp line per commit,
+/// followed by the names of the files it touched (with --name-only).
+pub static TIMES_LOG_FORMAT: &str = "--pretty=format:%x00%at";
+
+/// When a note was first and last committed, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Times {
+    pub created: u64,
+    pub modified: u64,
+}
+
+/// Parses the output of git log with TIMES_LOG_FORMAT and --name-only, newest commit first, into
+/// when each file in it was first and last committed.
+pub fn parse_times(log: &str) -> HashMap<PathBuf, Times> {
+    let mut times: HashMap<PathBuf, Times> = HashMap::new();
+    let mut timestamp = None;
+    for line in log.lines() {
+        if let Some(commit_timestamp) = line.strip_prefix('\0') {
+            timestamp = commit_timestamp.trim().parse::<u64>().ok();
+            continue;
+        }
+        let Some(timestamp) = timestamp.filter(|_| !line.is_empty()) else {
+            continue;
+        };
+        times
+            .entry(PathBuf::from(line))
+            .and_modify(|times| times.created = timestamp)
+            .or_insert(Times {
+                created: timestamp,
+                modified: timestamp,
+            });
+    }
+    times
+}
+
+/// The git log format that parse_last_commits understands: a NUL-prefixed line of NUL-separated
+/// fields per commit, followed by the names of the files it touched (with --name-only).
+pub static LAST_COMMIT_LOG_FORMAT: &str = "--pretty=format:%x00%h%x00%at%x00%an%x00%s";
+
+/// The commit that last changed a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastCommit {
+    /// The abbreviated hash of the commit.
+    pub hash: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub author: String,
+    pub subject: String,
+}
+
+/// Parses the output of git log with LAST_COMMIT_LOG_FORMAT and --name-only, newest commit first,
+/// into the commit that last changed each file in it.
+pub fn parse_last_commits(log: &str) -> HashMap<PathBuf, LastCommit> {
+    let mut last_commits = HashMap::new();
+    let mut commit = None;
+    for line in log.lines() {
+        if let Some(fields) = line.strip_prefix('\0') {
+            commit = match fields.splitn(4, '\0').collect::<Vec<&str>>()[..] {
+                [hash, timestamp, author, subject] => {
+                    timestamp.parse().ok().map(|timestamp| LastCommit {
+                        hash: hash.to_string(),
+                        timestamp,
+                        author: author.to_string(),
+                        subject: subject.to_string(),
+                    })
+                }
+                _ => None,
+            };
+            continue;
+        }
+        if let Some(commit) = commit.as_ref().filter(|_| !line.is_empty()) {
+            last_commits
+                .entry(PathBuf::from(line))
+                .or_insert_with(|| commit.clone());
+        }
+    }
+    last_commits
+}
+
+/// A note in a listing, along with what jot knows about it.
+#[derive(Debug)]
+pub struct Note {
+    /// The note's path, relative to base-dir.
+    pub path: PathBuf,
+    pub title: Option<String>,
+    /// The note's size in bytes.
+    pub size: u64,
+    /// The number of words in the note, leaving out its frontmatter.
+    pub words: usize,
+    /// When the note was first committed, or, if it has not been yet, created, as far as the
+    /// filesystem knows, in seconds since the Unix epoch.
+    pub created: Option<u64>,
+    /// When the note was last committed, or, if it has not been yet, last modified, in seconds
+    /// since the Unix epoch.
+    pub modified: Option<u64>,
+    pub tags: Vec<String>,
+    /// Whether the note is pinned (see jot pin).
+    pub pinned: bool,
+    /// The commit that last changed the note, if it was asked for and there is one.
+    pub last_commit: Option<LastCommit>,
+}
+
+impl Note {
+    pub fn to_json(&self) -> json::Value {
+        let timestamp = |timestamp: Option<u64>| json::Value::from(timestamp.map(|t| t as i64));
+        let mut fields = vec![
+            ("path", self.path.display().to_string().into()),
+            ("title", self.title.clone().into()),
+            ("size", (self.size as i64).into()),
+            ("words", (self.words as i64).into()),
+            ("created", timestamp(self.created)),
+            ("modified", timestamp(self.modified)),
+            (
+                "tags",
+                json::Value::Array(self.tags.iter().map(|tag| tag.as_str().into()).collect()),
+            ),
+            ("pinned", self.pinned.into()),
+        ];
+        if let Some(commit) = &self.last_commit {
+            fields.push((
+                "last_commit",
+                json::Value::object([
+                    ("hash", commit.hash.as_str().into()),
+                    ("timestamp", (commit.timestamp as i64).into()),
+                    ("author", commit.author.as_str().into()),
+                    ("message", commit.subject.as_str().into()),
+                ]),
+            ));
+        }
+        json::Value::object(fields)
+    }
+}
+
+/// Returns the notes beneath the given directory (relative to base-dir), with the given times
+/// from git, in sorted order. Given extensions, the files with any of them are returned instead,
+/// whether they are notes or not.
+pub fn notes(
+    base_dir: &Path,
+    subpath: Option<&Path>,
+    extensions: &[String],
+    times: &HashMap<PathBuf, Times>,
+) -> Result<Vec<Note>> {
+    let paths = match extensions.is_empty() {
+        true => vault::notes(base_dir)?,
+        false => vault::files(base_dir)?
+            .into_iter()
+            .filter(|path| {
+                path.extension().is_some_and(|extension| {
+                    extensions
+                        .iter()
+                        .any(|wanted| extension == wanted.trim_start_matches('.'))
+                })
+            })
+            .collect(),
+    };
+
+    let pins = pins::load(base_dir)?;
+    let mut notes = Vec::new();
+    for path in paths {
+        if subpath.is_some_and(|subpath| !path.starts_with(subpath)) {
+            continue;
+        }
+
+        let absolute_path = base_dir.join(&path);
+        let content = match vault::is_note(&path) {
+            true => std::fs::read_to_string(&absolute_path)
+                .context(format!("failed to read note at {}", path.display()))?,
+            false => String::new(),
+        };
+        let metadata = std::fs::metadata(&absolute_path)
+            .context(format!("failed to read metadata of {}", path.display()))?;
+        let seconds = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+        };
+        let committed = times.get(&path);
+        notes.push(Note {
+            title: outline::title(&content),
+            size: metadata.len(),
+            words: stats::words(&content),
+            created: committed
+                .map(|times| times.created)
+                .or_else(|| seconds(metadata.created())),
+            modified: committed
+                .map(|times| times.modified)
+                .or_else(|| seconds(metadata.modified())),
+            tags: frontmatter::tags(&content),
+            pinned: pins.contains(&path),
+            last_commit: None,
+            path,
+        });
+    }
+    Ok(notes)
+}
+
+/// Sorts the given notes by the given key, in its natural order (see cli::SortKey), or the reverse
+/// of that. Ties keep the order they were in.
+pub fn sort(notes: &mut [Note], key: cli::SortKey, reverse: bool) {
+    notes.sort_by(|a, b| {
+        let ordering = match key {
+            cli::SortKey::Mtime => b.modified.cmp(&a.modified),
+            cli::SortKey::Created => b.created.cmp(&a.created),
+            cli::SortKey::Title => {
+                let title = |note: &Note| {
+                    note.title
+                        .clone()
+                        .unwrap_or_else(|| note.path.display().to_string())
+                        .to_lowercase()
+                };
+                title(a).cmp(&title(b))
+            }
+            cli::SortKey::Size => b.size.cmp(&a.size),
+        };
+        match reverse {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    });
+}
+
+/// Moves the pinned notes among the given notes to the front, leaving both the pinned notes and
+/// the others in the order they were in.
+pub fn pinned_first(notes: &mut [Note]) {
+    notes.sort_by_key(|note| !note.pinned);
+}
+
+#[test]
+fn parse_git_times() {
+    let log = "\x00300\na.md\nb.md\n\n\x00200\n\x00100\na.md\n";
+    let times = parse_times(log);
+    assert_eq!(
+        times.get(Path::new("a.md")),
+        Some(&Times {
+            created: 100,
+            modified: 300
+        })
+    );
+    assert_eq!(
+        times.get(Path::new("b.md")),
+        Some(&Times {
+            created: 300,
+            modified: 300
+        })
+    );
+}
+
+#[test]
+fn parse_git_last_commits() {
+    let log =
+        "\x00b2\x00300\x00Ann\x00Edit a\na.md\n\n\x00a1\x00100\x00Bo\x00Add a\x00b\na.md\nb.md\n";
+    let last_commits = parse_last_commits(log);
+    assert_eq!(
+        last_commits
+            .get(Path::new("a.md"))
+            .map(|commit| &commit.subject[..]),
+        Some("Edit a")
+    );
+    assert_eq!(
+        last_commits.get(Path::new("b.md")),
+        Some(&LastCommit {
+            hash: "a1".to_string(),
+            timestamp: 100,
+            author: "Bo".to_string(),
+            subject: "Add a\x00b".to_string(),
+        })
+    );
+}
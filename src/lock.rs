@@ -0,0 +1,101 @@
+use std::{
+    fs::{File, TryLockError},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::git;
+
+/// How often a held lock is checked on while waiting for it.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The lock that jot processes hold while running git operations.
+const SYNC_LOCK: &str = "jot.lock";
+
+/// The lock that a jot daemon holds for as long as it runs.
+const DAEMON_LOCK: &str = "jot-daemon.lock";
+
+/// An exclusive lock on base-dir, held by at most one jot process at a time so that their git
+/// operations cannot interleave and corrupt the index. It is released when dropped, or when the
+/// process holding it exits, however it exits.
+pub struct Lock {
+    _file: File,
+}
+
+impl Lock {
+    /// Opens the given lock's file. It lives in the .git directory, so that it is never synced,
+    /// unless base-dir is not a git repository, in which case it is hidden in base-dir instead.
+    fn open(name: &str) -> Result<(File, PathBuf)> {
+        let path = match git::is_repository()? {
+            true => git::git_path(name)?,
+            false => PathBuf::from(format!(".{}", name)),
+        };
+        let file = File::create(&path).context(format!("failed to open {}", path.display()))?;
+        Ok((file, path))
+    }
+
+    /// Acquires the lock, waiting up to the given time for whichever jot process holds it to
+    /// release it.
+    pub fn acquire(wait: Duration) -> Result<Lock> {
+        let (file, path) = Self::open(SYNC_LOCK)?;
+        let deadline = Instant::now() + wait;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Lock { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(POLL_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => bail!(
+                    "a sync is already in progress, another jot process holds {}, please try \
+                    again once it is done",
+                    path.display()
+                ),
+                Err(TryLockError::Error(err)) => {
+                    return Err(err).context(format!("failed to lock {}", path.display()))
+                }
+            }
+        }
+    }
+
+    /// Acquires the lock if no other jot process holds it, without waiting.
+    pub fn try_acquire() -> Result<Option<Lock>> {
+        let (file, path) = Self::open(SYNC_LOCK)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Lock { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => {
+                Err(err).context(format!("failed to lock {}", path.display()))
+            }
+        }
+    }
+
+    /// Acquires the lock that marks base-dir as synced by a jot daemon, which is held for as long
+    /// as the daemon runs. Fails if another daemon holds it.
+    pub fn acquire_daemon() -> Result<Lock> {
+        let (file, path) = Self::open(DAEMON_LOCK)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Lock { _file: file }),
+            Err(TryLockError::WouldBlock) => bail!(
+                "a jot daemon is already running for base-dir, it holds {}",
+                path.display()
+            ),
+            Err(TryLockError::Error(err)) => {
+                Err(err).context(format!("failed to lock {}", path.display()))
+            }
+        }
+    }
+
+    /// Returns true if a jot daemon is running for base-dir.
+    pub fn is_daemon_running() -> Result<bool> {
+        let (file, path) = Self::open(DAEMON_LOCK)?;
+        match file.try_lock() {
+            Ok(()) => Ok(false),
+            Err(TryLockError::WouldBlock) => Ok(true),
+            Err(TryLockError::Error(err)) => {
+                Err(err).context(format!("failed to lock {}", path.display()))
+            }
+        }
+    }
+}
@@ -1,14 +1,162 @@
-use std::process::{Command, Stdio};
+use std::{
+    process::{Command, ExitCode},
+    time::Duration,
+};
 
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 
+mod assets;
+mod calendar;
 mod cli;
+mod clipboard;
 mod cmd;
+mod compact;
+mod conflict;
+mod crypt;
+mod daemon;
+mod date;
+mod dedupe;
+mod editor;
+mod frontmatter;
+mod git;
+mod graph;
+mod history;
+mod hooks;
+mod html;
+mod ics;
+mod ignore;
+mod json;
+mod last;
+mod lfs;
+mod links;
+mod listing;
+mod lock;
+mod merge;
+mod notify;
+mod object_store;
+mod outline;
+mod output;
+mod periodic;
+mod pins;
+mod progress;
+mod refile;
+mod render;
+mod rsync;
+mod service;
+mod snapshot;
+mod stats;
+mod streak;
+mod style;
+mod sync;
+mod syncthing;
+mod template;
+mod tree;
+#[cfg(feature = "tui")]
+mod tui;
+mod undo;
+mod vault;
+mod watch;
+mod webhook;
+mod wip;
+mod zettel;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let args = cli::Args::parse();
 
+    // Errors are reported the way they would be if main returned them, but in color.
+    let styler = style::Styler::stderr(&args);
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!(
+                "{} {:?}",
+                styler.paint("Error:", &[style::BOLD, style::RED]),
+                err
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: cli::Args) -> Result<()> {
+    // jot enters base-dir before running most commands, and joins paths onto it all over, so a
+    // relative base-dir would be resolved against itself once inside. Resolving it up front keeps
+    // it pointing at the same place from anywhere.
+    args.base_dir = std::path::absolute(&args.base_dir)
+        .context(format!("failed to resolve {}", args.base_dir.display()))?;
+
+    // Every git process jot runs inherits the identity to commit as, whatever it commits. The
+    // native git backend reads the same flags itself.
+    if let Some(name) = &args.git_author_name {
+        std::env::set_var("GIT_AUTHOR_NAME", name);
+        std::env::set_var("GIT_COMMITTER_NAME", name);
+    }
+    if let Some(email) = &args.git_author_email {
+        std::env::set_var("GIT_AUTHOR_EMAIL", email);
+        std::env::set_var("GIT_COMMITTER_EMAIL", email);
+    }
+
+    #[cfg(feature = "native-git")]
+    if let Some(secs) = args.git_timeout_secs {
+        git::set_native_timeout(Duration::from_secs(secs))?;
+    }
+
+    // Creating and cloning a vault create base-dir, so they cannot run from inside it.
+    if let Some(cli::Command::Init {
+        path,
+        remote,
+        encrypted,
+    }) = &args.command
+    {
+        return cmd::init(&args, path.as_deref(), remote.as_deref(), *encrypted);
+    }
+    if let Some(cli::Command::Clone {
+        remote,
+        path,
+        shallow,
+        depth,
+        blobless,
+    }) = &args.command
+    {
+        let depth = depth.or(shallow.then_some(1));
+        return cmd::clone(&args, remote, path.as_deref(), depth, *blobless);
+    }
+
+    if let Some(cli::Command::Backup { action, file }) = &args.command {
+        return match action {
+            Some(cli::BackupAction::Restore { file, path }) => {
+                cmd::restore_backup(&args, file, path)
+            }
+            None => cmd::backup(&args, file.as_deref().expect("file is required")),
+        };
+    }
+
+    // The installed daemon runs from wherever it was installed from, since its flags may be
+    // relative to there.
+    if let Some(cli::Command::Daemon {
+        action: Some(cli::DaemonAction::Install),
+        ..
+    }) = &args.command
+    {
+        return cmd::install_daemon(&args);
+    }
+
+    // Files to attach or read events from are relative to where jot is run from, not to base-dir.
+    if let Some(
+        cli::Command::Attach { file, .. }
+        | cli::Command::New(cli::NewArgs {
+            from_ics: Some(file),
+            ..
+        }),
+    ) = &mut args.command
+    {
+        if file.as_os_str() != "-" {
+            *file = std::path::absolute(&file)
+                .context(format!("failed to resolve {}", file.display()))?;
+        }
+    }
+
     // First, set jot to be into the base_dir, since that is the point from which all our commands
     // should be executing from.
     std::env::set_current_dir(&args.base_dir).context(format!(
@@ -16,40 +164,254 @@ fn main() -> Result<()> {
         args.base_dir.display(),
     ))?;
 
-    // Second, check that base-dir is a git repository:
-    let status = Command::new("git")
-        .arg("rev-parse")
-        .arg("--git-dir")
-        .stdout(Stdio::null())
-        .status()
-        .context("failed to determine if base-dir is a git repository")?;
-    if !status.success() {
+    // Second, check that base-dir is a git repository, unless it is synced some other way:
+    let is_repository = git::is_repository()?;
+    if !is_repository && matches!(args.sync_backend, cli::SyncBackend::Git) {
         bail!(
-            "base-dir ({}) must be a git repository",
+            "base-dir ({}) must be a git repository, unless it is synced with another sync-backend",
             args.base_dir.display()
         )
     }
 
-    // Third, check that the base-dir is clean.
-    let status = Command::new("git")
-        .arg("diff-index")
-        .arg("--quiet")
-        .arg("HEAD")
-        .arg("--")
-        .status()
-        .context("failed to determine if base-dir is clean")?;
-    if !status.success() {
-        bail!(
-            "base-dir ({}) is not clean, please fix the issue and run jot again",
-            args.base_dir.display()
-        )
+    let command = args
+        .command
+        .as_ref()
+        .unwrap_or(&cli::Command::Edit { message: None });
+
+    // Third, check that the base-dir is clean, if it is a git repository. The exceptions are
+    // resolving conflicts and merging files as git's merge driver, which are only ever done on an
+    // unclean base-dir, showing its status or diff, which are most useful on an unclean base-dir,
+    // running git directly, checking what state base-dir is in, and the daemon or watching syncs,
+    // whose job it is to sync any changes.
+    let may_be_unclean = matches!(
+        command,
+        cli::Command::Conflicts { .. }
+            | cli::Command::MergeFile { .. }
+            | cli::Command::Status { .. }
+            | cli::Command::Diff { .. }
+            | cli::Command::Git { .. }
+            | cli::Command::Fsck
+            | cli::Command::Daemon { .. }
+            | cli::Command::Synch(cli::SyncArgs { watch: Some(_), .. })
+            | cli::Command::New(cli::NewArgs { no_open: true, .. })
+    );
+    if is_repository && !may_be_unclean {
+        let status = Command::new("git")
+            .arg("diff-index")
+            .arg("--quiet")
+            .arg("HEAD")
+            .arg("--")
+            .status()
+            .context("failed to determine if base-dir is clean")?;
+        if !status.success() {
+            bail!(
+                "base-dir ({}) is not clean, please fix the issue and run jot again",
+                args.base_dir.display()
+            )
+        }
     }
 
-    match args.command.as_ref().unwrap_or(&cli::Command::Edit) {
-        cli::Command::New { path } => cmd::new(&args, path),
-        cli::Command::Edit => cmd::edit(&args),
-        cli::Command::List { subpath } => cmd::list(&args, subpath.clone()),
-        cli::Command::Synch => cmd::sync(&args),
+    // Fourth, make sure that no other jot process runs git operations in base-dir at the same
+    // time. Commands that only read go without, and so does merging files as git's merge driver,
+    // since that only happens under the lock of the jot process that is syncing. The daemon and
+    // watching syncs take the lock for each of their syncs instead.
+    let _lock = if matches!(
+        command,
+        cli::Command::List { .. }
+            | cli::Command::Backlinks { .. }
+            | cli::Command::Graph { .. }
+            | cli::Command::Orphans { .. }
+            | cli::Command::CheckLinks { .. }
+            | cli::Command::Recent { .. }
+            | cli::Command::Log { .. }
+            | cli::Command::Diff { .. }
+            | cli::Command::Blame { .. }
+            | cli::Command::Cat { .. }
+            | cli::Command::Stats { .. }
+            | cli::Command::Info { .. }
+            | cli::Command::Streak
+            | cli::Command::Cal {
+                interactive: false,
+                ..
+            }
+            | cli::Command::MergeFile { .. }
+            | cli::Command::Fsck
+            | cli::Command::Daemon { .. }
+            | cli::Command::Synch(cli::SyncArgs { watch: Some(_), .. })
+    ) {
+        None
+    } else {
+        Some(lock::Lock::acquire(Duration::from_secs(args.lock_wait))?)
+    };
+
+    match command {
+        cli::Command::New(new_args) => cmd::new(&args, new_args),
+        cli::Command::Quick { text, message } => {
+            cmd::quick(&args, &text.join(" "), message.as_deref())
+        }
+        cli::Command::Clip {
+            path,
+            find,
+            source,
+            message,
+        } => cmd::clip(
+            &args,
+            path.as_ref(),
+            *find,
+            source.as_deref(),
+            message.as_deref(),
+        ),
+        cli::Command::PasteImage {
+            path,
+            print,
+            message,
+        } => cmd::paste_image(&args, path, *print, message.as_deref()),
+        cli::Command::Attach {
+            file,
+            path,
+            per_note,
+            print,
+            message,
+        } => cmd::attach(&args, file, path, *per_note, *print, message.as_deref()),
+        cli::Command::Web { url, path, message } => {
+            cmd::web(&args, url, path.as_deref(), message.as_deref())
+        }
+        cli::Command::Refile { message } => cmd::refile(&args, message.as_deref()),
+        cli::Command::Append {
+            path,
+            text,
+            dated,
+            message,
+        } => cmd::append(
+            &args,
+            path,
+            Some(text.join(" ")).filter(|_| !text.is_empty()).as_deref(),
+            *dated,
+            message.as_deref(),
+        ),
+        cli::Command::Cp {
+            source,
+            dest,
+            fresh_dates,
+            message,
+        } => cmd::cp(&args, source, dest, *fresh_dates, message.as_deref()),
+        cli::Command::Merge {
+            notes,
+            into,
+            message,
+        } => cmd::merge(&args, notes, into, message.as_deref()),
+        cli::Command::Split { note, message } => cmd::split(&args, note, message.as_deref()),
+        cli::Command::Today { message, streak } => {
+            cmd::periodic(&args, cli::Period::Day, 0, message.as_deref())?;
+            match streak {
+                true => cmd::streak(&args),
+                false => Ok(()),
+            }
+        }
+        cli::Command::Streak => cmd::streak(&args),
+        cli::Command::Yesterday { message } => {
+            cmd::periodic(&args, cli::Period::Day, -1, message.as_deref())
+        }
+        cli::Command::Tomorrow { message } => {
+            cmd::periodic(&args, cli::Period::Day, 1, message.as_deref())
+        }
+        cli::Command::Week { message } => {
+            cmd::periodic(&args, cli::Period::Week, 0, message.as_deref())
+        }
+        cli::Command::Month { message } => {
+            cmd::periodic(&args, cli::Period::Month, 0, message.as_deref())
+        }
+        cli::Command::Cal {
+            month,
+            interactive,
+            message,
+        } => cmd::cal(&args, month.as_deref(), *interactive, message.as_deref()),
+        cli::Command::Stats {
+            heatmap,
+            json,
+            weeks,
+        } => cmd::stats(&args, *heatmap, *json, *weeks as usize),
+        cli::Command::Info { note } => cmd::info(&args, note),
+        cli::Command::Edit { message } => cmd::edit(&args, message.as_deref()),
+        cli::Command::Pin { note, message } => cmd::pin(&args, note, true, message.as_deref()),
+        cli::Command::Unpin { note, message } => cmd::pin(&args, note, false, message.as_deref()),
+        cli::Command::Last { message } => cmd::last(&args, message.as_deref()),
+        cli::Command::List(list_args) => cmd::list(&args, list_args),
+        cli::Command::Synch(sync_args) => cmd::sync(&args, sync_args),
+        cli::Command::Backlinks { note, context } => cmd::backlinks(&args, note, *context),
+        cli::Command::Graph { format } => cmd::graph(&args, *format),
+        cli::Command::Orphans { exclude, print0 } => cmd::orphans(&args, exclude, *print0),
+        cli::Command::CheckLinks {
+            external,
+            jobs,
+            timeout,
+        } => cmd::check_links(&args, *external, *jobs, *timeout),
+        cli::Command::Recent { count, print0 } => cmd::recent(&args, *count, *print0),
+        cli::Command::Log { note, count } => cmd::log(&args, note.as_ref(), *count),
+        cli::Command::Diff {
+            note,
+            since,
+            since_snapshot,
+        } => cmd::diff(
+            &args,
+            note.as_ref(),
+            since.as_deref(),
+            since_snapshot.as_deref(),
+        ),
+        cli::Command::Restore {
+            note,
+            at,
+            snapshot,
+            copy,
+        } => cmd::restore(&args, note, at.as_deref(), snapshot.as_deref(), *copy),
+        cli::Command::Undo => cmd::undo(&args),
+        cli::Command::Blame { note } => cmd::blame(&args, note),
+        cli::Command::Cat { note, render } => cmd::cat(&args, note, *render),
+        cli::Command::Tui => cmd::tui(&args),
+        cli::Command::Init { .. }
+        | cli::Command::Clone { .. }
+        | cli::Command::Backup { .. }
+        | cli::Command::Daemon {
+            action: Some(cli::DaemonAction::Install),
+            ..
+        } => unreachable!(
+            "init, clone, backup and daemon install are handled before entering base-dir"
+        ),
+        cli::Command::Git { args: git_args } => cmd::git_passthrough(git_args),
+        cli::Command::Compact {
+            keep_days,
+            period,
+            force_push,
+            branch,
+        } => cmd::compact(&args, *keep_days, *period, *force_push, branch.as_deref()),
+        cli::Command::Gc { aggressive } => cmd::gc(&args, *aggressive),
+        cli::Command::Daemon {
+            action: None,
+            debounce_secs,
+            interval_mins,
+        } => cmd::daemon(&args, *debounce_secs, *interval_mins),
+        cli::Command::Conflicts {
+            list,
+            print0,
+            interactive,
+            finish,
+        } => cmd::conflicts(&args, *list, *print0, *interactive, *finish),
+        cli::Command::MergeFile {
+            base,
+            ours,
+            theirs,
+            union,
+        } => cmd::merge_file(base, ours, theirs, *union),
+        cli::Command::Fsck => cmd::fsck(&args),
+        cli::Command::Doctor => cmd::doctor(&args),
+        cli::Command::Status { fetch } => cmd::status(&args, *fetch),
+        cli::Command::Random {
+            subpath,
+            tag,
+            print,
+            print0,
+        } => cmd::random(&args, subpath.as_ref(), tag.as_deref(), *print, *print0),
+        cli::Command::Dedupe { fuzzy, interactive } => cmd::dedupe(&args, *fuzzy, *interactive),
     }?;
 
     Ok(())
@@ -0,0 +1,362 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// The name jot's merge driver is registered under in git's config, and referred to by in
+/// .gitattributes.
+pub static DRIVER_NAME: &str = "jot";
+
+/// Like DRIVER_NAME, but for the variant of the driver that unions conflicting changes instead of
+/// leaving conflict markers, which suits append-only notes like journals.
+pub static UNION_DRIVER_NAME: &str = "jot-union";
+
+/// The .gitattributes lines that make git use jot's merge driver for notes.
+pub static GITATTRIBUTES_LINES: [&str; 2] = ["*.md merge=jot", "*.markdown merge=jot"];
+
+/// A paragraph, list item, heading or fenced code block: the unit that Markdown merges operate on.
+/// Blank lines belong to the block before them.
+struct Block<'a> {
+    text: &'a str,
+    is_list_item: bool,
+}
+
+impl Block<'_> {
+    /// What blocks are compared by. Differences in surrounding whitespace are not worth a conflict.
+    fn key(&self) -> &str {
+        self.text.trim()
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    if ["- ", "* ", "+ "]
+        .iter()
+        .any(|bullet| line.starts_with(bullet))
+    {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
+
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') && line.trim_start_matches('#').starts_with(' ')
+}
+
+fn split(content: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let (mut start, mut offset) = (0, 0);
+    let (mut in_code_block, mut after_blank) = (false, false);
+    for line in content.split_inclusive('\n') {
+        let starts_block = !in_code_block
+            && offset > start
+            && (after_blank || is_list_item(line) || is_heading(line));
+        if starts_block {
+            blocks.push(&content[start..offset]);
+            start = offset;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        }
+        after_blank = trimmed.is_empty();
+        offset += line.len();
+    }
+    if offset > start {
+        blocks.push(&content[start..offset]);
+    }
+
+    blocks
+        .into_iter()
+        .map(|text| Block {
+            text,
+            is_list_item: is_list_item(text),
+        })
+        .collect()
+}
+
+/// Returns the pairs of indices of the longest common subsequence of the given keys.
+fn lcs(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut matches = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Ours,
+    Theirs,
+}
+
+/// The merged text, built block by block.
+#[derive(Default)]
+struct Output {
+    text: String,
+    last: Option<(Source, bool)>,
+}
+
+impl Output {
+    fn end_line(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn push(&mut self, source: Source, blocks: &[&Block]) {
+        for block in blocks {
+            if let Some((last_source, last_is_list_item)) = self.last {
+                self.end_line();
+                // Blocks that were not next to each other on either side need separating, unless
+                // they are items of the same list.
+                if last_source != source
+                    && !self.text.ends_with("\n\n")
+                    && !(last_is_list_item && block.is_list_item)
+                {
+                    self.text.push('\n');
+                }
+            }
+            self.text.push_str(block.text);
+            self.last = Some((source, block.is_list_item));
+        }
+    }
+
+    fn push_conflict(&mut self, ours: &[&Block], theirs: &[&Block]) {
+        self.end_line();
+        self.text.push_str("<<<<<<< ours\n");
+        ours.iter().for_each(|block| self.text.push_str(block.text));
+        self.end_line();
+        self.text.push_str("=======\n");
+        theirs
+            .iter()
+            .for_each(|block| self.text.push_str(block.text));
+        self.end_line();
+        self.text.push_str(">>>>>>> theirs\n");
+        self.last = None;
+    }
+}
+
+/// Merges two Markdown documents that were both derived from the given base, one paragraph or list
+/// item at a time. Blocks added on both sides at the same spot are all kept (ours first), as are
+/// conflicting changes to the same blocks if union is set. Otherwise, those conflicts are left in
+/// the result between the usual conflict markers. Returns the merged document, and the number of
+/// conflicts in it.
+pub fn merge(base: &str, ours: &str, theirs: &str, union: bool) -> (String, usize) {
+    if ours == theirs {
+        return (ours.to_string(), 0);
+    }
+
+    let (base, ours, theirs) = (split(base), split(ours), split(theirs));
+    fn keys<'a>(blocks: &'a [Block]) -> Vec<&'a str> {
+        blocks.iter().map(Block::key).collect()
+    }
+    let (base_keys, our_keys, their_keys) = (keys(&base), keys(&ours), keys(&theirs));
+    let mut in_ours = vec![None; base.len()];
+    for (base_idx, our_idx) in lcs(&base_keys, &our_keys) {
+        in_ours[base_idx] = Some(our_idx);
+    }
+    let mut in_theirs = vec![None; base.len()];
+    for (base_idx, their_idx) in lcs(&base_keys, &their_keys) {
+        in_theirs[base_idx] = Some(their_idx);
+    }
+
+    // Blocks that are unchanged on both sides split the documents into chunks that can be merged
+    // independently, as in diff3.
+    let stable = (0..base.len())
+        .filter_map(|idx| Some((idx, in_ours[idx]?, in_theirs[idx]?)))
+        .chain(std::iter::once((base.len(), ours.len(), theirs.len())));
+    let mut output = Output::default();
+    let mut conflicts = 0;
+    let (mut base_start, mut our_start, mut their_start) = (0, 0, 0);
+    for (base_end, our_end, their_end) in stable {
+        let base_chunk = base[base_start..base_end].iter().collect::<Vec<&Block>>();
+        let our_chunk = ours[our_start..our_end].iter().collect::<Vec<&Block>>();
+        let their_chunk = theirs[their_start..their_end]
+            .iter()
+            .collect::<Vec<&Block>>();
+        let same = |a: &[&Block], b: &[&Block]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.key() == b.key())
+        };
+
+        if same(&our_chunk, &base_chunk) {
+            output.push(Source::Theirs, &their_chunk);
+        } else if same(&their_chunk, &base_chunk) || same(&our_chunk, &their_chunk) {
+            output.push(Source::Ours, &our_chunk);
+        } else if base_chunk.is_empty() || union {
+            output.push(Source::Ours, &our_chunk);
+            let their_only = their_chunk
+                .into_iter()
+                .filter(|block| !our_chunk.iter().any(|ours| ours.key() == block.key()))
+                .collect::<Vec<&Block>>();
+            output.push(Source::Theirs, &their_only);
+        } else {
+            output.push_conflict(&our_chunk, &their_chunk);
+            conflicts += 1;
+        }
+
+        if base_end < base.len() {
+            // Prefer whichever side changed the block's whitespace, if any.
+            if ours[our_end].text == base[base_end].text {
+                output.push(Source::Theirs, &[&theirs[their_end]]);
+            } else {
+                output.push(Source::Ours, &[&ours[our_end]]);
+            }
+        }
+        (base_start, our_start, their_start) = (base_end + 1, our_end + 1, their_end + 1);
+    }
+
+    (output.text, conflicts)
+}
+
+/// Merges two versions of a Markdown document that have no known base, e.g. a note and the copy of
+/// it that a file syncer kept when both were edited at once. Blocks only one side has are kept,
+/// since without a base there is no telling whether the other side deleted them or never had
+/// them, and blocks that differ between the sides are left between the usual conflict markers.
+/// Returns the merged document, and the number of conflicts in it.
+pub fn merge_without_base(ours: &str, theirs: &str) -> (String, usize) {
+    if ours == theirs {
+        return (ours.to_string(), 0);
+    }
+
+    let (ours, theirs) = (split(ours), split(theirs));
+    let our_keys = ours.iter().map(Block::key).collect::<Vec<&str>>();
+    let their_keys = theirs.iter().map(Block::key).collect::<Vec<&str>>();
+    let common = lcs(&our_keys, &their_keys)
+        .into_iter()
+        .chain(std::iter::once((ours.len(), theirs.len())));
+    let mut output = Output::default();
+    let mut conflicts = 0;
+    let (mut our_start, mut their_start) = (0, 0);
+    for (our_end, their_end) in common {
+        let our_chunk = ours[our_start..our_end].iter().collect::<Vec<&Block>>();
+        let their_chunk = theirs[their_start..their_end]
+            .iter()
+            .collect::<Vec<&Block>>();
+        if their_chunk.is_empty() {
+            output.push(Source::Ours, &our_chunk);
+        } else if our_chunk.is_empty() {
+            output.push(Source::Theirs, &their_chunk);
+        } else {
+            output.push_conflict(&our_chunk, &their_chunk);
+            conflicts += 1;
+        }
+
+        if our_end < ours.len() {
+            output.push(Source::Ours, &[&ours[our_end]]);
+        }
+        (our_start, their_start) = (our_end + 1, their_end + 1);
+    }
+
+    (output.text, conflicts)
+}
+
+/// Returns true if the .gitattributes in the given directory makes git use jot's merge driver for
+/// notes.
+pub fn is_registered_in_gitattributes(base_dir: &Path) -> Result<bool> {
+    let gitattributes_path = base_dir.join(".gitattributes");
+    if !gitattributes_path.exists() {
+        return Ok(false);
+    }
+    let gitattributes = std::fs::read_to_string(&gitattributes_path)
+        .context(format!("failed to read {}", gitattributes_path.display()))?;
+    Ok(GITATTRIBUTES_LINES.iter().all(|line| {
+        gitattributes
+            .lines()
+            .any(|existing| existing.trim() == *line)
+    }))
+}
+
+/// Adds the lines that make git use jot's merge driver for notes to the .gitattributes in the
+/// given directory, creating it if needed.
+pub fn register_in_gitattributes(base_dir: &Path) -> Result<()> {
+    let gitattributes_path = base_dir.join(".gitattributes");
+    let mut gitattributes = if gitattributes_path.exists() {
+        std::fs::read_to_string(&gitattributes_path)
+            .context(format!("failed to read {}", gitattributes_path.display()))?
+    } else {
+        String::new()
+    };
+    for line in GITATTRIBUTES_LINES {
+        if gitattributes
+            .lines()
+            .any(|existing| existing.trim() == line)
+        {
+            continue;
+        }
+        if !gitattributes.is_empty() && !gitattributes.ends_with('\n') {
+            gitattributes.push('\n');
+        }
+        gitattributes.push_str(line);
+        gitattributes.push('\n');
+    }
+    std::fs::write(&gitattributes_path, gitattributes)
+        .context(format!("failed to write {}", gitattributes_path.display()))
+}
+
+#[test]
+fn merge_markdown() {
+    let base = "# Journal\n\n- milk\n- eggs\n\nFirst entry.\n";
+    let ours = "# Journal\n\n- milk\n- eggs\n- bread\n\nFirst entry.\n\nSecond entry.\n";
+    let theirs = "# Journal\n\n- oat milk\n- eggs\n\nFirst entry.\n\nAnother entry.\n";
+    assert_eq!(
+        merge(base, ours, theirs, false),
+        (
+            "# Journal\n\n- oat milk\n- eggs\n- bread\n\nFirst entry.\n\nSecond entry.\n\nAnother entry.\n"
+                .to_string(),
+            0
+        )
+    );
+
+    let ours = "# Journal\n\nFirst entry, edited.\n";
+    let theirs = "# Journal\n\nFirst entry, edited differently.\n";
+    let (merged, conflicts) = merge(base, ours, theirs, false);
+    assert_eq!(conflicts, 1);
+    assert!(merged.contains("<<<<<<< ours\nFirst entry, edited.\n=======\n"));
+    assert_eq!(
+        merge(base, ours, theirs, true),
+        (
+            "# Journal\n\nFirst entry, edited.\n\nFirst entry, edited differently.\n".to_string(),
+            0
+        )
+    );
+}
+
+#[test]
+fn merge_markdown_without_base() {
+    let ours = "# Journal\n\n- milk\n- eggs\n\nFirst entry.\n";
+    let theirs = "# Journal\n\n- milk\n- bread\n\nFirst entry, edited.\n\nSecond entry.\n";
+    let (merged, conflicts) = merge_without_base(ours, theirs);
+    assert_eq!(conflicts, 1);
+    assert_eq!(
+        merged,
+        "# Journal\n\n- milk\n<<<<<<< ours\n- eggs\n\nFirst entry.\n=======\n- bread\n\n\
+        First entry, edited.\n\nSecond entry.\n>>>>>>> theirs\n"
+    );
+    assert_eq!(
+        merge_without_base("A.\n\nB.\n", "A.\n\nC.\n\nB.\n"),
+        ("A.\n\nC.\n\nB.\n".to_string(), 0)
+    );
+}
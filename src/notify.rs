@@ -0,0 +1,66 @@
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::{cli, cmd::exec_cmd};
+
+/// Escapes the given text for a string literal in AppleScript.
+fn applescript_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Shows a desktop notification with the given title and body, via notify-send on Linux and
+/// osascript on macOS.
+fn send(args: &cli::Args, title: &str, body: &str) -> Result<()> {
+    let notify_exec = if cfg!(target_os = "macos") {
+        let mut osascript = Command::new("osascript");
+        osascript.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_quote(body),
+            applescript_quote(title)
+        ));
+        osascript
+    } else {
+        let mut notify_send = Command::new("notify-send");
+        notify_send.args(["--app-name=jot", title, body]);
+        notify_send
+    };
+    exec_cmd(
+        "showing a notification",
+        notify_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    Ok(())
+}
+
+/// Notifies the user of how the sync that had the given result went, if the notify flag asks for
+/// it. Without the flag, only the failures of background syncs are notified of. Failing to notify
+/// is only printed, since there is nothing better to do about it.
+pub fn sync_result(args: &cli::Args, result: &Result<()>, background: bool) {
+    let when = args.notify.unwrap_or(match background {
+        true => cli::Notify::Failures,
+        false => cli::Notify::Never,
+    });
+    let notified = match (when, result) {
+        (cli::Notify::Always, Ok(())) => send(
+            args,
+            "jot synced",
+            &format!("synced {}", args.base_dir.display()),
+        ),
+        (cli::Notify::Always | cli::Notify::Failures, Err(err)) => send(
+            args,
+            "jot failed to sync",
+            &format!(
+                "failed to sync {}: {}",
+                args.base_dir.display(),
+                // Errors of child processes include all of their output, which is far too much.
+                format!("{:#}", err).lines().next().unwrap_or_default()
+            ),
+        ),
+        _ => Ok(()),
+    };
+    if let Err(err) = notified {
+        println!("warning: failed to notify of the sync: {:#}", err);
+    }
+}
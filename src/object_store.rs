@@ -0,0 +1,455 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, cmd::exec_cmd, git::GIT_CMD, ignore::Ignore, progress, sync, vault};
+
+static CURL_CMD: &str = "curl";
+
+/// The object, at the root of the sync-target, that maps each file in the vault to the object
+/// holding its contents.
+static MANIFEST_KEY: &str = "jot-manifest";
+
+/// Where, beneath the sync-target, the contents of files are kept, named by their hash.
+static OBJECTS_KEY: &str = "objects/";
+
+/// The local file the manifest is staged in before uploading it. It is hidden, so it is never
+/// synced itself.
+static MANIFEST_STAGING_PATH: &str = ".jot-manifest";
+
+/// The files that are hashed per git invocation, to stay clear of limits on argument lengths.
+const HASH_CHUNK_SIZE: usize = 500;
+
+/// A version of a file: the hash of its contents, and when it was modified, in seconds since the
+/// epoch.
+#[derive(Clone, PartialEq, Debug)]
+struct Entry {
+    hash: String,
+    modified: u64,
+}
+
+/// The versions of files in the vault, by their path relative to base-dir. A manifest is kept as
+/// one line per file, e.g. `<hash> <modified> projects/foo.md`.
+type Manifest = BTreeMap<String, Entry>;
+
+/// Returns true if the given path, from a manifest, is a plain relative path beneath base-dir.
+fn is_vault_path(path: &Path) -> bool {
+    !path.is_absolute()
+        && !path.has_root()
+        && vault::normalize(path)
+            .is_some_and(|normalized| normalized == path && !normalized.as_os_str().is_empty())
+        && !path.starts_with(".git")
+}
+
+fn parse_manifest(text: &str) -> Result<Manifest> {
+    text.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            match (parts.next(), parts.next().map(str::parse), parts.next()) {
+                // The manifest is whatever the sync-target says it is, so a path in it must not
+                // lead anywhere outside of base-dir, nor into its .git directory.
+                (Some(_), Some(Ok(_)), Some(path)) if !is_vault_path(Path::new(path)) => bail!(
+                    "the sync-target's manifest has a path outside of base-dir: {}",
+                    path
+                ),
+                (Some(hash), Some(Ok(modified)), Some(path)) => Ok((
+                    path.to_string(),
+                    Entry {
+                        hash: hash.to_string(),
+                        modified,
+                    },
+                )),
+                _ => bail!("malformed line in the sync-target's manifest: {}", line),
+            }
+        })
+        .collect()
+}
+
+fn format_manifest(manifest: &Manifest) -> String {
+    manifest
+        .iter()
+        .map(|(path, entry)| format!("{} {} {}\n", entry.hash, entry.modified, path))
+        .collect()
+}
+
+/// Which way each file whose versions differ between the two given manifests should be copied,
+/// as the files to pull and the files to push. The version modified last wins, and the local one
+/// wins ties, since that is the one the user is looking at.
+fn reconcile(local: &Manifest, remote: &Manifest) -> (Vec<String>, Vec<String>) {
+    let mut pulls = Vec::new();
+    let mut pushes = Vec::new();
+    for (path, local_entry) in local {
+        match remote.get(path) {
+            Some(remote_entry) if remote_entry.hash == local_entry.hash => {}
+            Some(remote_entry) if remote_entry.modified > local_entry.modified => {
+                pulls.push(path.clone())
+            }
+            _ => pushes.push(path.clone()),
+        }
+    }
+    pulls.extend(
+        remote
+            .keys()
+            .filter(|path| !local.contains_key(*path))
+            .cloned(),
+    );
+    pulls.sort();
+    (pulls, pushes)
+}
+
+/// Quotes the given value for a curl config file.
+fn curl_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The result of an HTTP request.
+struct Response {
+    status: u16,
+    etag: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Syncs base-dir with S3-compatible or WebDAV storage at the sync-target, via curl, without any
+/// history. The contents of files are uploaded as objects named by their hash, and a manifest
+/// maps each file to its latest version. As with rsync, whichever copy of a file was modified
+/// last wins, and deletions are not synced.
+pub struct ObjectStore<'a> {
+    args: &'a cli::Args,
+    url: String,
+}
+
+impl<'a> ObjectStore<'a> {
+    pub fn new(args: &'a cli::Args) -> Result<ObjectStore<'a>> {
+        let Some(url) = &args.sync_target else {
+            bail!(
+                "the s3 and webdav sync backends need a sync-target, e.g. --sync-target https://example.com/notes/"
+            );
+        };
+        let url = match url.ends_with('/') {
+            true => url.clone(),
+            false => format!("{}/", url),
+        };
+        Ok(ObjectStore { args, url })
+    }
+
+    /// Returns the curl config that authenticates requests. It is passed on stdin rather than as
+    /// arguments, so that the credentials never show up in the list of processes or in errors.
+    fn credentials(&self) -> Result<String> {
+        match self.args.sync_backend {
+            cli::SyncBackend::S3 => {
+                let (Ok(key_id), Ok(secret)) = (
+                    std::env::var("AWS_ACCESS_KEY_ID"),
+                    std::env::var("AWS_SECRET_ACCESS_KEY"),
+                ) else {
+                    bail!("the s3 sync backend needs $AWS_ACCESS_KEY_ID and $AWS_SECRET_ACCESS_KEY to be set");
+                };
+                let region = std::env::var("AWS_REGION").unwrap_or("us-east-1".to_string());
+                let mut config = format!(
+                    "user = {}\naws-sigv4 = {}\n",
+                    curl_quote(&format!("{}:{}", key_id, secret)),
+                    curl_quote(&format!("aws:amz:{}:s3", region)),
+                );
+                if let Ok(token) = std::env::var("AWS_SESSION_TOKEN") {
+                    config.push_str(&format!(
+                        "header = {}\n",
+                        curl_quote(&format!("x-amz-security-token: {}", token))
+                    ));
+                }
+                Ok(config)
+            }
+            _ => match (
+                std::env::var("JOT_SYNC_USER"),
+                std::env::var("JOT_SYNC_PASSWORD"),
+            ) {
+                (Ok(user), Ok(password)) => Ok(format!(
+                    "user = {}\n",
+                    curl_quote(&format!("{}:{}", user, password))
+                )),
+                _ => Ok("netrc-optional\n".to_string()),
+            },
+        }
+    }
+
+    /// Sends a request for the given key beneath the sync-target, uploading the given file as
+    /// its body, if any.
+    fn request(
+        &self,
+        method: &str,
+        key: &str,
+        headers: &[String],
+        upload: Option<&Path>,
+    ) -> Result<Response> {
+        let url = format!("{}{}", self.url, key);
+        let mut curl_exec = Command::new(CURL_CMD);
+        curl_exec.args([
+            "--config",
+            "-",
+            "--silent",
+            "--show-error",
+            "--request",
+            method,
+        ]);
+        // The status and ETag go to stderr, after any error, so that stdout is only the body.
+        curl_exec.args(["--write-out", "%{stderr}\n%{http_code} %header{etag}"]);
+        for header in headers {
+            curl_exec.args(["--header", header]);
+        }
+        if let Some(path) = upload {
+            curl_exec.arg("--upload-file").arg(path);
+        }
+        curl_exec.arg(&url);
+        let invocation = format!(
+            "{} {}",
+            CURL_CMD,
+            curl_exec
+                .get_args()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        let mut child = curl_exec
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(format!("failed to execute curl: `{}`", invocation))?;
+        // The unwrap is fine, since stdin was piped just above.
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(self.credentials()?.as_bytes())
+            .context("failed to pass credentials to curl")?;
+        let output = child
+            .wait_with_output()
+            .context(format!("failed to execute curl: `{}`", invocation))?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let (error, write_out) = stderr.rsplit_once('\n').unwrap_or(("", &stderr));
+        if !output.status.success() {
+            bail!("failed to {} {}: {}", method, url, error.trim());
+        }
+        let (status, etag) = write_out.split_once(' ').unwrap_or((write_out, ""));
+        Ok(Response {
+            status: status.parse().context(format!(
+                "curl reported no HTTP status for {} {}",
+                method, url
+            ))?,
+            etag: Some(etag.trim().to_string()).filter(|etag| !etag.is_empty()),
+            body: output.stdout,
+        })
+    }
+
+    /// Returns the given response to a request for the given key, unless it is unsuccessful.
+    fn check(&self, method: &str, key: &str, response: Response) -> Result<Response> {
+        if !(200..300).contains(&response.status) {
+            bail!(
+                "{} {}{} failed with HTTP status {}: {}",
+                method,
+                self.url,
+                key,
+                response.status,
+                String::from_utf8_lossy(&response.body).trim()
+            );
+        }
+        Ok(response)
+    }
+
+    /// Returns the sync-target's manifest, and its ETag, if it has one yet.
+    fn fetch_manifest(&self) -> Result<(Manifest, Option<String>)> {
+        let response = self.request("GET", MANIFEST_KEY, &[], None)?;
+        if response.status == 404 {
+            return Ok((Manifest::new(), None));
+        }
+        let response = self.check("GET", MANIFEST_KEY, response)?;
+        let manifest = parse_manifest(&String::from_utf8_lossy(&response.body))?;
+        Ok((manifest, response.etag))
+    }
+
+    /// Uploads the given manifest, unless the sync-target's manifest changed since it was fetched
+    /// with the given ETag, e.g. because another device synced in the meantime.
+    fn upload_manifest(&self, manifest: &Manifest, etag: Option<&str>) -> Result<()> {
+        std::fs::write(MANIFEST_STAGING_PATH, format_manifest(manifest))
+            .context("failed to write the manifest")?;
+        let condition = match etag {
+            Some(etag) => format!("If-Match: {}", etag),
+            None => "If-None-Match: *".to_string(),
+        };
+        let response = self.request(
+            "PUT",
+            MANIFEST_KEY,
+            &[condition],
+            Some(Path::new(MANIFEST_STAGING_PATH)),
+        );
+        std::fs::remove_file(MANIFEST_STAGING_PATH).context("failed to remove the manifest")?;
+        let response = response?;
+        if response.status == 412 {
+            bail!(
+                "{} changed while syncing, probably because another device synced, please sync again",
+                self.url
+            );
+        }
+        self.check("PUT", MANIFEST_KEY, response)?;
+        Ok(())
+    }
+
+    /// Returns the versions of every file in base-dir that is not ignored.
+    fn local_manifest(&self) -> Result<Manifest> {
+        let ignore = Ignore::load(&self.args.base_dir, &self.args.ignore)?;
+        let files = vault::files(Path::new("."))?
+            .into_iter()
+            .filter(|path| !ignore.is_ignored(path))
+            .collect::<Vec<PathBuf>>();
+
+        let mut manifest = Manifest::new();
+        for chunk in files.chunks(HASH_CHUNK_SIZE) {
+            let mut git_exec = Command::new(GIT_CMD);
+            git_exec.args(["hash-object", "--"]).args(chunk);
+            let (hashes, _) = exec_cmd("hash files", git_exec, true, self.args.quiet_on_ctrl_c)?;
+            for (path, hash) in chunk.iter().zip(hashes.lines()) {
+                let modified = std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .context(format!("failed to stat {}", path.display()))?;
+                manifest.insert(
+                    path.to_string_lossy().to_string(),
+                    Entry {
+                        hash: hash.to_string(),
+                        modified: modified.duration_since(UNIX_EPOCH)?.as_secs(),
+                    },
+                );
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Downloads the given version of the file at the given path, keeping its modification time.
+    fn download(&self, path: &str, entry: &Entry) -> Result<()> {
+        let key = format!("{}{}", OBJECTS_KEY, entry.hash);
+        let response = self.request("GET", &key, &[], None)?;
+        let response = self.check("GET", &key, response)?;
+        let path = Path::new(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context(format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(path, response.body)
+            .context(format!("failed to write {}", path.display()))?;
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(UNIX_EPOCH + Duration::from_secs(entry.modified)))
+            .context(format!(
+                "failed to set the modification time of {}",
+                path.display()
+            ))
+    }
+
+    /// Uploads the contents of the file at the given path, as the object of the given version.
+    fn upload(&self, path: &str, entry: &Entry) -> Result<()> {
+        let key = format!("{}{}", OBJECTS_KEY, entry.hash);
+        let mut response = self.request("PUT", &key, &[], Some(Path::new(path)))?;
+        // WebDAV servers refuse to create objects in collections that do not exist yet.
+        if response.status == 409 && matches!(self.args.sync_backend, cli::SyncBackend::Webdav) {
+            let created = self.request("MKCOL", OBJECTS_KEY, &[], None)?;
+            // 405 means that the collection exists after all.
+            if created.status != 405 {
+                self.check("MKCOL", OBJECTS_KEY, created)?;
+            }
+            response = self.request("PUT", &key, &[], Some(Path::new(path)))?;
+        }
+        self.check("PUT", &key, response)?;
+        Ok(())
+    }
+
+    fn run(&self, sync_args: &cli::SyncArgs, dry_run: bool) -> Result<()> {
+        let (pulls, _, pushes) = sync::phases(sync_args);
+        if !pulls && !pushes {
+            return Ok(());
+        }
+
+        let local = self.local_manifest()?;
+        let (mut remote, etag) = self.fetch_manifest()?;
+        let ignore = Ignore::load(&self.args.base_dir, &self.args.ignore)?;
+        let (to_pull, to_push) = reconcile(&local, &remote);
+        let to_pull = to_pull
+            .into_iter()
+            .filter(|path| !ignore.is_ignored(Path::new(path)))
+            .collect::<Vec<String>>();
+
+        if pulls {
+            if !dry_run {
+                progress::step(self.args, "pulling", || {
+                    to_pull
+                        .iter()
+                        .try_for_each(|path| self.download(path, &remote[path]))
+                })?;
+            }
+            sync::print_copied(&to_pull, true, &self.url, dry_run);
+        }
+        if pushes {
+            if !dry_run && !to_push.is_empty() {
+                progress::step(self.args, "pushing", || {
+                    for path in &to_push {
+                        self.upload(path, &local[path])?;
+                        remote.insert(path.clone(), local[path].clone());
+                    }
+                    self.upload_manifest(&remote, etag.as_deref())
+                })?;
+            }
+            sync::print_copied(&to_push, false, &self.url, dry_run);
+        }
+        Ok(())
+    }
+}
+
+impl sync::Backend for ObjectStore<'_> {
+    fn sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        self.run(sync_args, false)
+    }
+
+    fn print_sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        self.run(sync_args, true)
+    }
+}
+
+#[test]
+fn reconcile_manifests() {
+    let entry = |hash: &str, modified| Entry {
+        hash: hash.to_string(),
+        modified,
+    };
+    let local =
+        parse_manifest("a 10 same.md\nb 20 newer here.md\nc 10 older.md\nd 5 new.md\n").unwrap();
+    let remote = Manifest::from([
+        ("same.md".to_string(), entry("a", 30)),
+        ("newer here.md".to_string(), entry("x", 10)),
+        ("older.md".to_string(), entry("y", 20)),
+        ("remote.md".to_string(), entry("z", 1)),
+    ]);
+    assert_eq!(local["newer here.md"], entry("b", 20));
+    for path in [
+        "../../.bashrc",
+        "/etc/passwd",
+        "a/../../b.md",
+        ".git/hooks/pre-commit",
+    ] {
+        assert!(parse_manifest(&format!("a 10 {}\n", path)).is_err());
+    }
+    assert_eq!(parse_manifest(&format_manifest(&remote)).unwrap(), remote);
+    assert_eq!(
+        reconcile(&local, &remote),
+        (
+            vec!["older.md".to_string(), "remote.md".to_string()],
+            vec!["new.md".to_string(), "newer here.md".to_string()]
+        )
+    );
+}
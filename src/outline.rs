@@ -0,0 +1,173 @@
+use crate::frontmatter;
+
+/// Returns the level of the Markdown heading on the given line, if it is one.
+pub fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (level > 0 && level <= 6 && line[level..].starts_with([' ', '\t', '\n', '\r'])).then_some(level)
+}
+
+/// Returns the text of the Markdown heading on the given line, e.g. `Ideas` for `## Ideas`.
+fn heading_text(line: &str) -> &str {
+    line.trim_start_matches('#').trim()
+}
+
+/// Returns the lines of the given note contents, along with whether each is a heading, i.e. not
+/// inside of a code block.
+fn lines(content: &str) -> Vec<(&str, Option<usize>)> {
+    let mut in_code_block = false;
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code_block = !in_code_block;
+                return (line, None);
+            }
+            match in_code_block {
+                true => (line, None),
+                false => (line, heading_level(line)),
+            }
+        })
+        .collect()
+}
+
+/// Returns the given note contents with the levels of all of its headings changed by the given
+/// amount, e.g. `### a` for `## a` by 1. Levels stop at 1 and 6.
+pub fn shift_headings(content: &str, by: isize) -> String {
+    lines(content)
+        .into_iter()
+        .map(|(line, level)| match level {
+            Some(level) => {
+                let shifted = level.saturating_add_signed(by).clamp(1, 6);
+                format!("{}{}", "#".repeat(shifted), &line[level..])
+            }
+            None => line.to_string(),
+        })
+        .collect()
+}
+
+/// Splits the given note body (i.e. without its frontmatter) into its title, which is the level 1
+/// heading it starts with, if any, and the rest of it.
+pub fn split_title(body: &str) -> (Option<&str>, &str) {
+    let start = body.len() - body.trim_start().len();
+    let first_line = body[start..]
+        .split_inclusive('\n')
+        .next()
+        .unwrap_or_default();
+    match heading_level(first_line) {
+        Some(1) => (
+            Some(heading_text(first_line)),
+            &body[start + first_line.len()..],
+        ),
+        _ => (None, body),
+    }
+}
+
+/// Returns the title of the note with the given contents, i.e. the title in its frontmatter, or
+/// else its first level 1 heading, if it has either.
+pub fn title(content: &str) -> Option<String> {
+    let (frontmatter, body) = frontmatter::split(content);
+    if let Some(frontmatter::Value::Scalar(title)) = frontmatter
+        .as_ref()
+        .and_then(|frontmatter| frontmatter.get("title"))
+    {
+        return Some(title.clone());
+    }
+    lines(body)
+        .into_iter()
+        .find(|(_, level)| *level == Some(1))
+        .map(|(line, _)| heading_text(line).to_string())
+}
+
+/// Returns the given notes, as pairs of their titles and bodies without them, merged into one note
+/// with the given title, in which each is a level 2 section with its headings a level lower.
+pub fn merge(title: &str, notes: &[(&str, &str)]) -> String {
+    let mut merged = format!("# {}\n", title);
+    for (title, body) in notes {
+        merged.push_str(&format!("\n## {}\n", title));
+        let body = shift_headings(body.trim(), 1);
+        if !body.is_empty() {
+            merged.push_str(&format!("\n{}\n", body));
+        }
+    }
+    merged
+}
+
+/// A top-level section of a note, as split off by [`split`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Section {
+    pub heading: String,
+    /// The section's content, with its heading as a level 1 heading, and its other headings raised
+    /// to match.
+    pub content: String,
+}
+
+/// Splits the given note contents into its top-level sections. Those are its level 1 headings, or,
+/// if the note has a title (see [`split_title`]), its level 2 headings, or whichever is the
+/// highest level it has otherwise. Returns everything before the first section, including the
+/// note's frontmatter and title, along with the sections.
+pub fn split(content: &str) -> (&str, Vec<Section>) {
+    let (_, body) = frontmatter::split(content);
+    let (title, rest) = split_title(body);
+    let offset = content.len() - rest.len();
+
+    let lines = lines(rest);
+    let Some(top) = lines
+        .iter()
+        .filter_map(|(_, level)| *level)
+        .filter(|level| title.is_none() || *level > 1)
+        .min()
+    else {
+        return (content, Vec::new());
+    };
+
+    let mut preamble_len = offset;
+    let mut sections: Vec<(String, String)> = Vec::new();
+    for (line, level) in lines {
+        match (level, sections.last_mut()) {
+            (Some(level), _) if level <= top => {
+                sections.push((heading_text(line).to_string(), line.to_string()))
+            }
+            (_, Some((_, section))) => section.push_str(line),
+            (_, None) => preamble_len += line.len(),
+        }
+    }
+    let sections = sections
+        .into_iter()
+        .map(|(heading, section)| Section {
+            heading,
+            content: shift_headings(&section, 1 - top as isize),
+        })
+        .collect();
+    (&content[..preamble_len], sections)
+}
+
+#[test]
+fn merge_and_split_notes() {
+    assert_eq!(
+        shift_headings("# a\n```\n# b\n```\n##c\n", 1),
+        "## a\n```\n# b\n```\n##c\n"
+    );
+    assert_eq!(split_title("\n# Title\nbody\n"), (Some("Title"), "body\n"));
+    assert_eq!(title("```\n# a\n```\n# b\n").as_deref(), Some("b"));
+
+    let merged = merge("All", &[("A", "text\n## Sub\n"), ("B", "")]);
+    assert_eq!(merged, "# All\n\n## A\n\ntext\n### Sub\n\n## B\n");
+
+    let content = "---\ntags: [a]\n---\n# Big\nintro\n## One\nx\n### Deep\n## Two\ny\n";
+    let (preamble, sections) = split(content);
+    assert_eq!(preamble, "---\ntags: [a]\n---\n# Big\nintro\n");
+    assert_eq!(
+        sections,
+        vec![
+            Section {
+                heading: "One".to_string(),
+                content: "# One\nx\n## Deep\n".to_string(),
+            },
+            Section {
+                heading: "Two".to_string(),
+                content: "# Two\ny\n".to_string(),
+            },
+        ]
+    );
+}
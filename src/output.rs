@@ -0,0 +1,117 @@
+use std::{
+    io::{ErrorKind, IsTerminal, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    cli,
+    cmd::{get_env_var, SHELL_ENV_VARNAME},
+};
+
+/// The pager to use when $PAGER is not set.
+static DEFAULT_PAGER: &str = "less";
+
+/// Prints the given output, through $PAGER if stdout is a terminal and the no-pager flag is not
+/// set, like git does. Unless $LESS says otherwise, less passes colors through and exits straight
+/// away if the output fits on one screen.
+pub fn page(args: &cli::Args, output: &str) -> Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    if args.no_pager || !std::io::stdout().is_terminal() || pager.is_empty() || pager == "cat" {
+        print!("{}", output);
+        return Ok(());
+    }
+
+    let mut pager_exec = Command::new(get_env_var(SHELL_ENV_VARNAME)?);
+    pager_exec
+        .arg(&args.shell_cmd_flag)
+        .arg(&pager)
+        .stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        pager_exec.env("LESS", "FRX");
+    }
+    if std::env::var_os("LV").is_none() {
+        pager_exec.env("LV", "-c");
+    }
+    let mut pager_child = pager_exec
+        .spawn()
+        .context(format!("failed to execute pager: `{}`", pager))?;
+
+    // Quitting the pager before it has read everything closes its end of the pipe, which is no
+    // reason to fail.
+    if let Some(mut stdin) = pager_child.stdin.take() {
+        match stdin.write_all(output.as_bytes()) {
+            Err(err) if err.kind() != ErrorKind::BrokenPipe => {
+                return Err(err).context(format!("failed to write to pager: `{}`", pager));
+            }
+            _ => {}
+        }
+    }
+    pager_child
+        .wait()
+        .context(format!("failed to wait for pager: `{}`", pager))?;
+    Ok(())
+}
+
+/// Formats the given rows as left-aligned columns, two spaces apart, one row per line. The last
+/// column is not padded, and empty trailing cells are left out.
+pub fn columns(rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(idx) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    let mut formatted = String::new();
+    for row in rows {
+        let len = row.len() - row.iter().rev().take_while(|cell| cell.is_empty()).count();
+        let line = row[..len]
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| match idx == len - 1 {
+                true => cell.clone(),
+                false => format!("{:<width$}", cell, width = widths[idx]),
+            })
+            .collect::<Vec<String>>()
+            .join("  ");
+        formatted.push_str(&line);
+        formatted.push('\n');
+    }
+    formatted
+}
+
+/// Formats the given paths one per line, or, with print0, each followed by a NUL instead, so that
+/// paths with spaces or newlines in them survive xargs -0 or fzf --read0.
+pub fn paths<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>, print0: bool) -> String {
+    let terminator = if print0 { '\0' } else { '\n' };
+    paths
+        .into_iter()
+        .map(|path| format!("{}{}", path.as_ref().display(), terminator))
+        .collect()
+}
+
+#[test]
+fn format_columns() {
+    let row = |cells: &[&str]| {
+        cells
+            .iter()
+            .map(|cell| cell.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        columns(&[
+            row(&["a.md", "A"]),
+            row(&["journal/ü.md", ""]),
+            row(&["b.md", "B"])
+        ]),
+        "a.md          A\njournal/ü.md\nb.md          B\n"
+    );
+    assert_eq!(paths(["a.md", "my note.md"], true), "a.md\0my note.md\0");
+}
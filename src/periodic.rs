@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{cli, date::Date};
+
+/// Returns the first date of the given period the given date is in, e.g. the Monday of its week.
+pub fn start(period: cli::Period, date: Date) -> Date {
+    match period {
+        cli::Period::Day => date,
+        cli::Period::Week => date.add_days(1 - date.weekday()),
+        cli::Period::Month => Date { day: 1, ..date },
+    }
+}
+
+/// Returns the first date of the given period the given number of periods after the one the given
+/// date is in (or before, if negative).
+pub fn shift(period: cli::Period, date: Date, periods: i64) -> Date {
+    match period {
+        cli::Period::Day => date.add_days(periods),
+        cli::Period::Week => start(period, date).add_days(7 * periods),
+        cli::Period::Month => {
+            let months = date.year * 12 + i64::from(date.month) - 1 + periods;
+            Date {
+                year: months.div_euclid(12),
+                month: months.rem_euclid(12) as u32 + 1,
+                day: 1,
+            }
+        }
+    }
+}
+
+/// Returns the path, relative to base-dir, of the note of the given period the given date is in.
+pub fn path(args: &cli::Args, period: cli::Period, date: Date) -> Result<PathBuf> {
+    let pattern = match period {
+        cli::Period::Day => &args.daily_path,
+        cli::Period::Week => &args.weekly_path,
+        cli::Period::Month => &args.monthly_path,
+    };
+    Ok(PathBuf::from(start(period, date).format(pattern)?))
+}
+
+/// Returns the name of the template to create the notes of the given period from, if any.
+pub fn template(args: &cli::Args, period: cli::Period) -> Option<&str> {
+    match period {
+        cli::Period::Day => args.daily_template.as_deref(),
+        cli::Period::Week => args.weekly_template.as_deref(),
+        cli::Period::Month => args.monthly_template.as_deref(),
+    }
+}
+
+/// Returns the unfinished tasks in the given note, i.e. its `- [ ]` list items, as they are.
+pub fn unfinished_tasks(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .filter(|line| {
+            let item = line.trim_start();
+            ["- [ ] ", "* [ ] ", "+ [ ] "]
+                .iter()
+                .any(|prefix| item.starts_with(prefix))
+        })
+        .collect()
+}
+
+#[test]
+fn periods() {
+    // 2024-05-15, a Wednesday.
+    let date = Date::from_days(19_858);
+    assert_eq!(start(cli::Period::Week, date).to_string(), "2024-05-13");
+    assert_eq!(shift(cli::Period::Week, date, -1).to_string(), "2024-05-06");
+    assert_eq!(
+        shift(cli::Period::Month, date, -5).to_string(),
+        "2023-12-01"
+    );
+    assert_eq!(
+        unfinished_tasks("- [ ] a\n- [x] b\n  * [ ] c\n-[ ] d\n"),
+        vec!["- [ ] a", "  * [ ] c"]
+    );
+}
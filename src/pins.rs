@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// The name of the file, at the root of base-dir, that lists the pinned notes. It is synced like
+/// any other file, so that pins follow the vault from machine to machine.
+pub static PINS_FILENAME: &str = ".jotpins";
+
+/// Returns the pinned notes, relative to base-dir, in the order they were pinned. Blank lines and
+/// `#` comments in the pins file are skipped.
+pub fn load(base_dir: &Path) -> Result<Vec<PathBuf>> {
+    let path = base_dir.join(PINS_FILENAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        std::fs::read_to_string(&path).context(format!("failed to read {}", PINS_FILENAME))?;
+    Ok(parse(&content))
+}
+
+fn parse(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Writes the given pinned notes to the pins file, or deletes it if there are none.
+pub fn save(base_dir: &Path, pins: &[PathBuf]) -> Result<()> {
+    let path = base_dir.join(PINS_FILENAME);
+    if pins.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).context(format!("failed to delete {}", PINS_FILENAME))?;
+        }
+        return Ok(());
+    }
+    let content = pins
+        .iter()
+        .map(|pin| format!("{}\n", pin.display()))
+        .collect::<String>();
+    std::fs::write(&path, content).context(format!("failed to write {}", PINS_FILENAME))
+}
+
+#[test]
+fn parse_pins() {
+    let pins = parse("# pinned notes\nb.md\n\n  a/c.md\n");
+    assert_eq!(pins, [PathBuf::from("b.md"), PathBuf::from("a/c.md")]);
+}
@@ -0,0 +1,81 @@
+use std::{
+    io::IsTerminal,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::cli;
+
+/// The frames of the spinner that shows a step is still running.
+static SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+
+/// How often the spinner is redrawn.
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs the given step, e.g. `pulling`, and reports on stderr that it is in progress and how long
+/// it took, unless the quiet flag is set. On a terminal, a spinner with the time taken so far is
+/// redrawn while the step runs. Elsewhere, e.g. in the daemon's log, there is a line for when the
+/// step starts, and one for when it ends.
+pub fn step<T>(args: &cli::Args, label: &str, op: impl FnOnce() -> Result<T>) -> Result<T> {
+    run(args, label, std::io::stderr().is_terminal(), op)
+}
+
+/// Runs the given step like step does, but without a spinner, for steps that may hand the
+/// terminal over to a child process, e.g. $EDITOR for a commit message, hooks printing to stderr,
+/// or ssh asking for a passphrase, which the spinner would draw over.
+pub fn terminal_step<T>(
+    args: &cli::Args,
+    label: &str,
+    op: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    run(args, label, false, op)
+}
+
+fn run<T>(
+    args: &cli::Args,
+    label: &str,
+    animate: bool,
+    op: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    if args.quiet {
+        return op();
+    }
+
+    let start = Instant::now();
+    if !animate {
+        eprintln!("{}...", label);
+    }
+    let (done_sender, done_receiver) = mpsc::channel::<()>();
+    let result = std::thread::scope(|scope| {
+        if animate {
+            scope.spawn(move || {
+                let mut frames = SPINNER_FRAMES.iter().cycle();
+                while let Err(RecvTimeoutError::Timeout) =
+                    done_receiver.recv_timeout(FRAME_INTERVAL)
+                {
+                    // The unwrap is fine, since the frames cycle forever.
+                    eprint!(
+                        "\r\x1b[K{} {}... {:.1}s",
+                        frames.next().unwrap(),
+                        label,
+                        start.elapsed().as_secs_f64()
+                    );
+                }
+            });
+        }
+        let result = op();
+        drop(done_sender);
+        result
+    });
+
+    eprintln!(
+        "{}{}... {} ({:.1}s)",
+        if animate { "\r\x1b[K" } else { "" },
+        label,
+        if result.is_ok() { "done" } else { "failed" },
+        start.elapsed().as_secs_f64()
+    );
+    result
+}
@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+use crate::{frontmatter, outline::heading_level};
+
+/// The kind of item a note's content can be refiled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A top-level list item, along with its indented sub-items and continuation lines.
+    Bullet,
+    /// A heading of the given level (2 or more), along with everything beneath it until the next
+    /// heading of the same or a higher level.
+    Section(usize),
+}
+
+/// Returns whether the given line starts a top-level list item, e.g. `- call dentist`.
+fn is_bullet(line: &str) -> bool {
+    let numbered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    ["- ", "* ", "+ "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+        || (numbered.len() < line.len() && numbered.starts_with(". "))
+}
+
+/// Returns the byte ranges of the items in the given note that can be refiled one by one, i.e. its
+/// top-level list items and its sections (but not the note's title, i.e. a level 1 heading), in
+/// order. Frontmatter and anything else between the items is left out.
+pub fn items(content: &str) -> Vec<Range<usize>> {
+    let (_, body) = frontmatter::split(content);
+    let mut offset = content.len() - body.len();
+
+    let mut items = Vec::new();
+    let mut current: Option<(usize, Kind)> = None;
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let continues = match (current, heading_level(line)) {
+            (Some((_, Kind::Section(level))), Some(heading_level)) => heading_level > level,
+            (Some((_, Kind::Section(_))), None) => true,
+            (Some((_, Kind::Bullet)), None) => {
+                line.starts_with([' ', '\t']) && !line.trim().is_empty()
+            }
+            (_, _) => false,
+        };
+        if continues {
+            continue;
+        }
+
+        if let Some((item_start, _)) = current.take() {
+            items.push(item_start..start);
+        }
+        current = match heading_level(line) {
+            Some(level) if level > 1 => Some((start, Kind::Section(level))),
+            None if is_bullet(line) => Some((start, Kind::Bullet)),
+            _ => None,
+        };
+    }
+    if let Some((item_start, _)) = current {
+        items.push(item_start..content.len());
+    }
+    items
+}
+
+#[test]
+fn refile_items() {
+    let content = "---\ntags: [inbox]\n---\n# Inbox\n\n- call dentist\n  - tomorrow\n\
+                   * buy milk\n\nsome text\n## Idea\nbody\n### More\n- in section\n## Other\n";
+    let items: Vec<&str> = items(content)
+        .into_iter()
+        .map(|range| &content[range])
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            "- call dentist\n  - tomorrow\n",
+            "* buy milk\n",
+            "## Idea\nbody\n### More\n- in section\n",
+            "## Other\n",
+        ]
+    );
+}
@@ -0,0 +1,343 @@
+use crate::{
+    frontmatter, outline,
+    style::{Styler, BLUE, BOLD, CYAN, DIM, GREEN, ITALIC, MAGENTA, UNDERLINE, YELLOW},
+};
+
+/// Returns the keywords of the given language, for highlighting code blocks in it, or None if we
+/// do not know it.
+fn keywords(language: &str) -> Option<&'static [&'static str]> {
+    Some(match language.to_lowercase().as_str() {
+        "rust" | "rs" => &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+            "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
+            "type", "unsafe", "use", "where", "while",
+        ],
+        "python" | "py" => &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "False", "finally", "for", "from", "if", "import", "in",
+            "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True", "try", "while",
+            "with", "yield",
+        ],
+        "javascript" | "js" | "typescript" | "ts" | "jsx" | "tsx" => &[
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "else",
+            "export",
+            "extends",
+            "false",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "null",
+            "of",
+            "return",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "typeof",
+            "undefined",
+            "var",
+            "while",
+            "yield",
+        ],
+        "go" => &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "false",
+            "for",
+            "func",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "nil",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "true",
+            "type",
+            "var",
+        ],
+        "c" | "cpp" | "c++" | "h" | "java" | "cs" | "csharp" => &[
+            "break",
+            "case",
+            "char",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "do",
+            "double",
+            "else",
+            "enum",
+            "false",
+            "float",
+            "for",
+            "if",
+            "int",
+            "long",
+            "namespace",
+            "new",
+            "null",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "struct",
+            "switch",
+            "this",
+            "true",
+            "typedef",
+            "void",
+            "while",
+        ],
+        "sh" | "bash" | "zsh" | "shell" | "console" => &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "while",
+        ],
+        _ => return None,
+    })
+}
+
+/// Returns the prefix that starts line comments in the given language.
+fn comment_prefix(language: &str) -> &'static str {
+    match language.to_lowercase().as_str() {
+        "python" | "py" | "sh" | "bash" | "zsh" | "shell" | "console" | "yaml" | "toml" => "#",
+        "sql" | "lua" | "haskell" => "--",
+        _ => "//",
+    }
+}
+
+/// Highlights the given line of code in the given language: its keywords, strings, numbers and
+/// comments.
+fn highlight(line: &str, language: &str, styler: Styler) -> String {
+    let Some(keywords) = keywords(language) else {
+        return line.to_string();
+    };
+    let comment = comment_prefix(language);
+
+    let mut highlighted = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(comment) {
+            highlighted.push_str(&styler.paint(rest, &[DIM]));
+            break;
+        }
+        let len = if c == '"' || c == '\'' {
+            let end = rest[1..]
+                .char_indices()
+                .scan(false, |escaped, (idx, next)| {
+                    let is_end = next == c && !*escaped;
+                    *escaped = next == '\\' && !*escaped;
+                    Some((idx, is_end))
+                })
+                .find(|(_, is_end)| *is_end)
+                .map_or(rest.len(), |(idx, _)| idx + 2);
+            highlighted.push_str(&styler.paint(&rest[..end], &[GREEN]));
+            end
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match (keywords.contains(&word), c.is_ascii_digit()) {
+                (true, _) => highlighted.push_str(&styler.paint(word, &[MAGENTA])),
+                (false, true) => highlighted.push_str(&styler.paint(word, &[YELLOW])),
+                (false, false) => highlighted.push_str(word),
+            }
+            end
+        } else {
+            highlighted.push(c);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    highlighted
+}
+
+/// Renders the inline Markdown in the given text: strong and emphasized text, code spans and
+/// links.
+fn inline(text: &str, styler: Styler) -> String {
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        // Underscores within words, e.g. in snake_case, do not emphasize anything.
+        let in_word = text[..text.len() - rest.len()]
+            .chars()
+            .last()
+            .is_some_and(char::is_alphanumeric);
+        let delimited = |delimiter: &str| {
+            if in_word && delimiter.starts_with('_') {
+                return None;
+            }
+            let after = rest.strip_prefix(delimiter)?;
+            let end = after.find(delimiter).filter(|end| *end > 0)?;
+            Some((&after[..end], delimiter.len() * 2 + end))
+        };
+
+        if let Some((code, len)) = delimited("`") {
+            rendered.push_str(&styler.paint(code, &[CYAN]));
+            rest = &rest[len..];
+        } else if let Some((strong, len)) = delimited("**").or_else(|| delimited("__")) {
+            rendered.push_str(&styler.paint(&inline(strong, styler), &[BOLD]));
+            rest = &rest[len..];
+        } else if let Some((emphasized, len)) = delimited("*").or_else(|| delimited("_")) {
+            rendered.push_str(&styler.paint(&inline(emphasized, styler), &[ITALIC]));
+            rest = &rest[len..];
+        } else if let Some((text, target, len)) = link(rest) {
+            rendered.push_str(&styler.paint(text, &[UNDERLINE, BLUE]));
+            rendered.push_str(&styler.paint(&format!(" ({})", target), &[DIM]));
+            rest = &rest[len..];
+        } else {
+            rendered.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    rendered
+}
+
+/// Parses the Markdown link at the start of the given text, e.g. `[text](target)`, into its text,
+/// its target and its length.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let after = text.strip_prefix('[')?;
+    let close = after.find("](")?;
+    let target_len = after[close + 2..].find(')')?;
+    Some((
+        &after[..close],
+        &after[close + 2..close + 2 + target_len],
+        close + target_len + 4,
+    ))
+}
+
+/// Renders the given note for a terminal, with ANSI escape codes for its headings, emphasis, code
+/// and so on.
+pub fn render(content: &str, styler: Styler) -> String {
+    let (_, body) = frontmatter::split(content);
+    let mut rendered = String::new();
+    let yaml = &content[..content.len() - body.len()];
+    if !yaml.is_empty() {
+        rendered.push_str(&styler.paint(yaml.trim_end(), &[DIM]));
+        rendered.push('\n');
+    }
+
+    let mut code_language: Option<String> = None;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_language = match code_language {
+                Some(_) => None,
+                None => Some(trimmed[3..].trim().to_string()),
+            };
+            continue;
+        }
+        if let Some(language) = &code_language {
+            rendered.push_str(&styler.paint("│ ", &[DIM]));
+            rendered.push_str(&highlight(line, language, styler));
+            rendered.push('\n');
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        let rendered_line = if let Some(level) = outline::heading_level(line) {
+            let text = inline(line[level..].trim(), styler);
+            match level {
+                1 => styler.paint(&text, &[BOLD, UNDERLINE, CYAN]),
+                2 => styler.paint(&text, &[BOLD, CYAN]),
+                _ => styler.paint(&text, &[BOLD]),
+            }
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            format!(
+                "{}{}",
+                styler.paint("│ ", &[DIM]),
+                styler.paint(&inline(quote.trim(), styler), &[ITALIC])
+            )
+        } else if !trimmed.is_empty() && trimmed.chars().all(|c| matches!(c, '-' | '*' | '_')) {
+            if trimmed.len() >= 3 {
+                styler.paint(&"─".repeat(40), &[DIM])
+            } else {
+                inline(trimmed, styler)
+            }
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            let (marker, item) = match item {
+                item if item.starts_with("[ ] ") => (styler.paint("☐", &[YELLOW]), &item[4..]),
+                item if item.starts_with("[x] ") || item.starts_with("[X] ") => {
+                    (styler.paint("☑", &[GREEN]), &item[4..])
+                }
+                item => (styler.paint("•", &[CYAN]), item),
+            };
+            format!("{}{} {}", indent, marker, inline(item, styler))
+        } else if let Some((number, item)) = trimmed
+            .split_once(". ")
+            .filter(|(number, _)| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+        {
+            format!(
+                "{}{}. {}",
+                indent,
+                styler.paint(number, &[CYAN]),
+                inline(item, styler)
+            )
+        } else {
+            format!("{}{}", indent, inline(trimmed, styler))
+        };
+        rendered.push_str(&rendered_line);
+        rendered.push('\n');
+    }
+    rendered
+}
+
+#[test]
+fn render_markdown() {
+    use crate::{cli::ColorMode, style::RESET};
+
+    let styler = Styler::new(ColorMode::Always, false);
+    assert_eq!(
+        inline("a **b** `c` [d](e.md) snake_case_name", styler),
+        format!(
+            "a {BOLD}b{RESET} {CYAN}c{RESET} {UNDERLINE}{BLUE}d{RESET}{DIM} (e.md){RESET} \
+             snake_case_name"
+        )
+    );
+    assert_eq!(
+        highlight("let s = \"a\\\"b\"; // c", "rust", styler),
+        format!("{MAGENTA}let{RESET} s = {GREEN}\"a\\\"b\"{RESET}; {DIM}// c{RESET}")
+    );
+    assert_eq!(
+        render("# T\n- [ ] x\n", styler),
+        format!("{BOLD}{UNDERLINE}{CYAN}T{RESET}\n{YELLOW}☐{RESET} x\n")
+    );
+    let styler = Styler::new(ColorMode::Never, true);
+    assert_eq!(render("# T\n- [x] **x**\n", styler), "T\n☑ x\n");
+}
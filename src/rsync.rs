@@ -0,0 +1,94 @@
+use std::process::Command;
+
+use anyhow::{bail, Result};
+
+use crate::{cli, cmd::exec_cmd, ignore::Ignore, progress, sync};
+
+static RSYNC_CMD: &str = "rsync";
+
+/// base-dir as rsync refers to it, since jot runs from it.
+static BASE_DIR: &str = "./";
+
+/// Syncs base-dir by mirroring it to and from the sync-target with rsync, without any history.
+/// Files are only ever copied over older versions of themselves, so the most recent edit of a
+/// file wins. Deleting a file does not delete it from the other side.
+pub struct Rsync<'a> {
+    args: &'a cli::Args,
+    target: String,
+}
+
+impl<'a> Rsync<'a> {
+    pub fn new(args: &'a cli::Args) -> Result<Rsync<'a>> {
+        let Some(target) = &args.sync_target else {
+            bail!(
+                "the rsync sync backend needs a sync-target, e.g. --sync-target user@host:notes/"
+            );
+        };
+        // Trailing slashes have rsync copy the contents of directories, rather than the
+        // directories themselves.
+        let target = match target.ends_with('/') {
+            true => target.clone(),
+            false => format!("{}/", target),
+        };
+        Ok(Rsync { args, target })
+    }
+
+    /// Copies whatever is newer on one side than on the other over, from the sync-target if pulling
+    /// and to it otherwise, and prints what was (or with dry_run, would be) copied.
+    fn copy(&self, pulling: bool, dry_run: bool) -> Result<()> {
+        let (from, to, label, verb) = match pulling {
+            true => (self.target.as_str(), BASE_DIR, "pull from", "pulling"),
+            false => (BASE_DIR, self.target.as_str(), "push to", "pushing"),
+        };
+        let ignore = Ignore::load(&self.args.base_dir, &self.args.ignore)?;
+        let mut rsync_exec = Command::new(RSYNC_CMD);
+        rsync_exec.args(["--archive", "--update", "--out-format=%n"]);
+        if dry_run {
+            rsync_exec.arg("--dry-run");
+        }
+        // jot's own files (e.g. its lock) and any git repository are never mirrored.
+        rsync_exec.args(["--exclude=/.git/", "--exclude=/.jot*.lock"]);
+        for filter in ignore.rsync_filters() {
+            rsync_exec.arg(format!("--filter={}", filter));
+        }
+        rsync_exec.args([from, to]);
+        // rsync may ask for an ssh passphrase, so it gets the terminal to itself.
+        let (copied, _) = progress::terminal_step(self.args, verb, || {
+            exec_cmd(
+                &format!("rsync {} {}", label, self.target),
+                rsync_exec,
+                true,
+                self.args.quiet_on_ctrl_c,
+            )
+        })?;
+
+        let files = copied
+            .lines()
+            .filter(|line| !line.ends_with('/'))
+            .collect::<Vec<&str>>();
+        sync::print_copied(&files, pulling, &self.target, dry_run);
+        Ok(())
+    }
+
+    fn run(&self, sync_args: &cli::SyncArgs, dry_run: bool) -> Result<()> {
+        // There is nothing to commit, so local syncs have nothing to do.
+        let (pulls, _, pushes) = sync::phases(sync_args);
+        if pulls {
+            self.copy(true, dry_run)?;
+        }
+        if pushes {
+            self.copy(false, dry_run)?;
+        }
+        Ok(())
+    }
+}
+
+impl sync::Backend for Rsync<'_> {
+    fn sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        self.run(sync_args, false)
+    }
+
+    fn print_sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        self.run(sync_args, true)
+    }
+}
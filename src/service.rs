@@ -0,0 +1,215 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, cmd::exec_cmd};
+
+/// Returns the name to give base-dir's daemon service, e.g. `jot-daemon-home-me-notes` for
+/// /home/me/notes, so that every vault can have its own.
+fn service_name(base_dir: &Path) -> String {
+    let escaped = base_dir
+        .to_string_lossy()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    format!("jot-daemon-{}", escaped)
+}
+
+/// Quotes the given argument for a systemd unit's ExecStart, which expands specifiers (`%`) and
+/// environment variables (`$`) even inside quotes.
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{}\"", escaped)
+}
+
+/// Escapes the given text for an XML document, i.e. a launchd property list.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn systemd_unit(base_dir: &Path, command: &[String], working_dir: &Path, path: &str) -> String {
+    format!(
+        "[Unit]\n\
+        Description=jot daemon for {}\n\
+        After=network-online.target\n\
+        \n\
+        [Service]\n\
+        ExecStart={}\n\
+        WorkingDirectory={}\n\
+        Environment={}\n\
+        Restart=on-failure\n\
+        RestartSec=30\n\
+        \n\
+        [Install]\n\
+        WantedBy=default.target\n",
+        base_dir.display(),
+        command
+            .iter()
+            .map(|arg| systemd_quote(arg))
+            .collect::<Vec<String>>()
+            .join(" "),
+        working_dir.to_string_lossy().replace('%', "%%"),
+        systemd_quote(&format!("PATH={}", path)),
+    )
+}
+
+fn launchd_plist(
+    label: &str,
+    command: &[String],
+    working_dir: &Path,
+    path: &str,
+    log_path: &Path,
+) -> String {
+    let string = |text: &str| format!("<string>{}</string>", xml_escape(text));
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+        \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+        <plist version=\"1.0\">\n\
+        <dict>\n\
+        \t<key>Label</key>\n\t{}\n\
+        \t<key>ProgramArguments</key>\n\t<array>\n{}\t</array>\n\
+        \t<key>WorkingDirectory</key>\n\t{}\n\
+        \t<key>EnvironmentVariables</key>\n\t<dict>\n\t\t<key>PATH</key>\n\t\t{}\n\t</dict>\n\
+        \t<key>StandardOutPath</key>\n\t{}\n\
+        \t<key>StandardErrorPath</key>\n\t{}\n\
+        \t<key>RunAtLoad</key>\n\t<true/>\n\
+        \t<key>KeepAlive</key>\n\t<true/>\n\
+        </dict>\n\
+        </plist>\n",
+        string(label),
+        command
+            .iter()
+            .map(|arg| format!("\t\t{}\n", string(arg)))
+            .collect::<String>(),
+        string(&working_dir.to_string_lossy()),
+        string(path),
+        string(&log_path.to_string_lossy()),
+        string(&log_path.to_string_lossy()),
+    )
+}
+
+/// Returns the command line that the service runs: this very invocation of jot, with the same
+/// flags, minus the trailing install subcommand.
+fn daemon_command() -> Result<Vec<String>> {
+    let exe = std::env::current_exe().context("failed to locate the jot executable")?;
+    let mut command = vec![exe.to_string_lossy().into_owned()];
+    let mut args = std::env::args_os().skip(1).collect::<Vec<OsString>>();
+    if args.last().is_some_and(|arg| arg == "install") {
+        args.pop();
+    }
+    command.extend(args.iter().map(|arg| arg.to_string_lossy().into_owned()));
+    Ok(command)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .context(format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, content).context(format!("failed to write {}", path.display()))
+}
+
+fn run(args: &cli::Args, label: &str, program: &str, program_args: &[&str]) -> Result<()> {
+    let mut exec = Command::new(program);
+    exec.args(program_args);
+    exec_cmd(label, exec, true, args.quiet_on_ctrl_c)?;
+    Ok(())
+}
+
+/// Installs and starts a service that runs the daemon for base-dir whenever the user is logged in:
+/// a systemd user unit on Linux, or a launchd agent on macOS.
+pub fn install(args: &cli::Args) -> Result<()> {
+    let command = daemon_command()?;
+    // Relative paths among the flags (e.g. base-dir) are resolved against the working directory.
+    let working_dir =
+        std::env::current_dir().context("failed to determine the current directory")?;
+    let path = std::env::var("PATH").unwrap_or_default();
+    let home = PathBuf::from(std::env::var("HOME").context("$HOME is not set")?);
+    let base_dir = std::path::absolute(&args.base_dir)
+        .context(format!("failed to resolve {}", args.base_dir.display()))?;
+    let name = service_name(&base_dir);
+
+    if cfg!(target_os = "macos") {
+        let label = format!("com.github.utagai.{}", name);
+        let plist_path = home
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label));
+        let log_path = home.join("Library/Logs").join(format!("{}.log", name));
+        write_file(
+            &plist_path,
+            &launchd_plist(&label, &command, &working_dir, &path, &log_path),
+        )?;
+        println!("wrote {}", plist_path.display());
+        run(
+            args,
+            "loading the launchd agent",
+            "launchctl",
+            &["load", "-w", &plist_path.to_string_lossy()],
+        )?;
+        println!(
+            "started {}, its output goes to {}, stop it with launchctl unload -w {}",
+            label,
+            log_path.display(),
+            plist_path.display()
+        );
+    } else if cfg!(target_os = "linux") {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config"));
+        let unit_path = config_dir
+            .join("systemd/user")
+            .join(format!("{}.service", name));
+        write_file(
+            &unit_path,
+            &systemd_unit(&base_dir, &command, &working_dir, &path),
+        )?;
+        println!("wrote {}", unit_path.display());
+        run(
+            args,
+            "reloading systemd",
+            "systemctl",
+            &["--user", "daemon-reload"],
+        )?;
+        run(
+            args,
+            "enabling the systemd unit",
+            "systemctl",
+            &["--user", "enable", "--now", &name],
+        )?;
+        println!(
+            "started {}, see its output with journalctl --user -u {}, stop it with systemctl \
+            --user disable --now {}",
+            name, name, name
+        );
+    } else {
+        bail!("installing the daemon as a service is only supported on Linux and macOS");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn service_files() {
+    assert_eq!(
+        service_name(Path::new("/home/me/my notes")),
+        "jot-daemon-home-me-my-notes"
+    );
+    assert_eq!(
+        systemd_quote("100% \"$HOME\""),
+        "\"100%% \\\"$$HOME\\\"\"".to_string()
+    );
+    assert_eq!(xml_escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+}
@@ -0,0 +1,127 @@
+use std::{
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    cli,
+    cmd::{exec_cmd, exec_cmd_with_timeout},
+    compact,
+    git::GIT_CMD,
+};
+
+/// Returns what the names of the snapshot tags for the given period start with.
+pub fn prefix(period: cli::Period) -> &'static str {
+    match period {
+        cli::Period::Day => "daily/",
+        cli::Period::Week => "weekly/",
+        cli::Period::Month => "monthly/",
+    }
+}
+
+/// Returns the name of the snapshot tag for the (UTC) period that the given timestamp falls in,
+/// e.g. `daily/2024-05-01`, or `weekly/2024-04-29` for weeks, which are named after their Monday.
+pub fn tag_name(timestamp: u64, period: cli::Period) -> String {
+    let period_name = compact::period(timestamp, period);
+    format!(
+        "{}{}",
+        prefix(period),
+        period_name.trim_start_matches("week of ")
+    )
+}
+
+/// Returns true if there is a tag with the given name.
+fn exists(name: &str) -> Result<bool> {
+    Ok(Command::new(GIT_CMD)
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/tags/{}", name),
+        ])
+        .stdout(Stdio::null())
+        .status()
+        .context(format!("failed to look up tag {}", name))?
+        .success())
+}
+
+/// Tags HEAD with the given name, unless there is a tag with that name already. Returns true if
+/// HEAD was tagged.
+pub fn create(args: &cli::Args, name: &str) -> Result<bool> {
+    if exists(name)? {
+        return Ok(false);
+    }
+    let mut git_tag_exec = Command::new(GIT_CMD);
+    git_tag_exec.args(["tag", name, "HEAD"]);
+    exec_cmd("tagging", git_tag_exec, true, args.quiet_on_ctrl_c)?;
+    Ok(true)
+}
+
+/// Pushes every snapshot tag for the given period to the given remote. Tags that the remote
+/// already has are left as they are.
+pub fn push(args: &cli::Args, remote: &str, period: cli::Period) -> Result<()> {
+    let refspec = format!("refs/tags/{0}*:refs/tags/{0}*", prefix(period));
+    let mut git_push_exec = Command::new(GIT_CMD);
+    git_push_exec.args(["push", "--no-verify", remote, &refspec]);
+    exec_cmd_with_timeout(
+        "pushing snapshots",
+        git_push_exec,
+        true,
+        args.quiet_on_ctrl_c,
+        args.git_timeout_secs.map(Duration::from_secs),
+    )?;
+    Ok(())
+}
+
+/// Resolves the given snapshot to its tag's name. The snapshot is either the name of a tag, e.g.
+/// `daily/2024-05-01`, or one of `daily`, `weekly` and `monthly` for the latest such snapshot.
+pub fn resolve(args: &cli::Args, snapshot: &str) -> Result<String> {
+    let period = match snapshot {
+        "daily" => Some(cli::Period::Day),
+        "weekly" => Some(cli::Period::Week),
+        "monthly" => Some(cli::Period::Month),
+        _ => None,
+    };
+    let Some(period) = period else {
+        if !exists(snapshot)? {
+            bail!(
+                "there is no snapshot named {}, list them with jot git tag --list",
+                snapshot
+            );
+        }
+        return Ok(snapshot.to_string());
+    };
+
+    // Snapshots are named after the dates they start on, so the latest sorts last.
+    let mut git_tag_exec = Command::new(GIT_CMD);
+    git_tag_exec.args([
+        "tag",
+        "--list",
+        "--sort=-refname",
+        &format!("{}*", prefix(period)),
+    ]);
+    let (tags, _) = exec_cmd(
+        "listing snapshots",
+        git_tag_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+    match tags.lines().next() {
+        Some(latest) => Ok(latest.to_string()),
+        None => bail!(
+            "there are no {} snapshots yet, see the snapshot-tags flag",
+            snapshot
+        ),
+    }
+}
+
+#[test]
+fn snapshot_tag_names() {
+    // 2024-01-31T12:00:00Z, a Wednesday.
+    let timestamp = 1_706_702_400;
+    assert_eq!(tag_name(timestamp, cli::Period::Day), "daily/2024-01-31");
+    assert_eq!(tag_name(timestamp, cli::Period::Week), "weekly/2024-01-29");
+    assert_eq!(tag_name(timestamp, cli::Period::Month), "monthly/2024-01");
+}
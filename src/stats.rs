@@ -0,0 +1,362 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{date::Date, frontmatter, graph::Graph, json, links, listing, output, vault};
+
+/// How many notes the largest and oldest note rankings have.
+const RANKING_LEN: usize = 5;
+
+/// How many words a minute reading times are estimated at.
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Returns the number of words in the given note, leaving out its frontmatter. Anything between
+/// whitespace with a letter or digit in it is a word, so list bullets and heading markers are not.
+pub fn words(content: &str) -> usize {
+    let (_, body) = frontmatter::split(content);
+    body.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Returns roughly how many minutes it takes to read the given number of words, which is at least
+/// a minute for anything but nothing at all.
+pub fn reading_time(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+/// Formats the given number of bytes with whichever unit suits it best, e.g. `3.2 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        1_048_576..=1_073_741_823 => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+        _ => format!("{:.1} GiB", bytes as f64 / 1_073_741_824.0),
+    }
+}
+
+/// Statistics about the notes in a vault, and the attachments alongside them.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub notes: usize,
+    pub words: usize,
+    /// The number of words in the notes in each top-level directory, or `.` for those that are
+    /// not in one.
+    pub words_by_dir: BTreeMap<String, usize>,
+    /// The number of files that are not notes, and their total size in bytes.
+    pub attachments: usize,
+    pub attachment_size: u64,
+    /// The number of notes created in each month, e.g. `2024-05`.
+    pub created_by_month: BTreeMap<String, usize>,
+    /// The largest notes, largest first, with their sizes in bytes.
+    pub largest: Vec<(PathBuf, u64)>,
+    /// The oldest notes, oldest first, with when they were created.
+    pub oldest: Vec<(PathBuf, Date)>,
+    /// The number of days on which notes were edited, according to git, and the number of weeks
+    /// up to and including this one that they were counted over.
+    pub edit_days: Option<(usize, usize)>,
+}
+
+impl Stats {
+    /// Gathers the statistics of the given notes (see listing::notes) in the given vault, dating
+    /// them in the timezone the given offset (in seconds) from UTC.
+    pub fn gather(base_dir: &Path, notes: &[listing::Note], utc_offset: i64) -> Result<Stats> {
+        let date =
+            |timestamp: u64| Date::from_days((timestamp as i64 + utc_offset).div_euclid(86_400));
+        let mut stats = Stats {
+            notes: notes.len(),
+            ..Default::default()
+        };
+        for note in notes {
+            stats.words += note.words;
+            let dir = match note.path.components().count() {
+                1 => ".".to_string(),
+                _ => note.path.components().next().map_or(String::new(), |dir| {
+                    dir.as_os_str().to_string_lossy().to_string()
+                }),
+            };
+            *stats.words_by_dir.entry(dir).or_insert(0) += note.words;
+            if let Some(created) = note.created.map(date) {
+                let month = format!("{:04}-{:02}", created.year, created.month);
+                *stats.created_by_month.entry(month).or_insert(0) += 1;
+            }
+        }
+
+        let mut largest = notes
+            .iter()
+            .map(|note| (note.path.clone(), note.size))
+            .collect::<Vec<_>>();
+        largest.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then(a_path.cmp(b_path)));
+        largest.truncate(RANKING_LEN);
+        stats.largest = largest;
+
+        let mut oldest = notes
+            .iter()
+            .filter_map(|note| Some((note.path.clone(), note.created?)))
+            .collect::<Vec<_>>();
+        oldest.sort_by(|(a_path, a), (b_path, b)| a.cmp(b).then(a_path.cmp(b_path)));
+        stats.oldest = oldest
+            .into_iter()
+            .take(RANKING_LEN)
+            .map(|(path, created)| (path, date(created)))
+            .collect();
+
+        for file in vault::files(base_dir)? {
+            if vault::is_note(&file) {
+                continue;
+            }
+            let metadata = std::fs::metadata(base_dir.join(&file))
+                .context(format!("failed to read metadata of {}", file.display()))?;
+            stats.attachments += 1;
+            stats.attachment_size += metadata.len();
+        }
+        Ok(stats)
+    }
+
+    pub fn to_json(&self) -> json::Value {
+        let counts = |counts: &BTreeMap<String, usize>| {
+            json::Value::object(
+                counts
+                    .iter()
+                    .map(|(key, count)| (key.as_str(), (*count as i64).into())),
+            )
+        };
+        json::Value::object([
+            ("notes", (self.notes as i64).into()),
+            ("words", (self.words as i64).into()),
+            ("words_by_dir", counts(&self.words_by_dir)),
+            ("attachments", (self.attachments as i64).into()),
+            ("attachment_size", (self.attachment_size as i64).into()),
+            ("created_by_month", counts(&self.created_by_month)),
+            (
+                "largest",
+                json::Value::Array(
+                    self.largest
+                        .iter()
+                        .map(|(path, size)| {
+                            json::Value::object([
+                                ("path", path.display().to_string().into()),
+                                ("size", (*size as i64).into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "oldest",
+                json::Value::Array(
+                    self.oldest
+                        .iter()
+                        .map(|(path, created)| {
+                            json::Value::object([
+                                ("path", path.display().to_string().into()),
+                                ("created", created.to_string().into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "edit_days",
+                self.edit_days.map_or(json::Value::Null, |(days, weeks)| {
+                    json::Value::object([
+                        ("days", (days as i64).into()),
+                        ("weeks", (weeks as i64).into()),
+                    ])
+                }),
+            ),
+        ])
+    }
+
+    /// Formats the statistics as a few aligned tables, one per statistic.
+    pub fn render(&self) -> String {
+        let mut overview = vec![
+            vec!["Notes".to_string(), self.notes.to_string()],
+            vec!["Words".to_string(), self.words.to_string()],
+            vec![
+                "Attachments".to_string(),
+                format!(
+                    "{} ({})",
+                    self.attachments,
+                    format_size(self.attachment_size)
+                ),
+            ],
+        ];
+        if let Some((days, weeks)) = self.edit_days {
+            overview.push(vec![
+                format!("Days edited in the last {} weeks", weeks),
+                days.to_string(),
+            ]);
+        }
+        let mut rendered = output::columns(&overview);
+
+        let mut section = |title: &str, rows: Vec<Vec<String>>| {
+            if rows.is_empty() {
+                return;
+            }
+            let rows = rows
+                .into_iter()
+                .map(|mut row| {
+                    row[0].insert_str(0, "  ");
+                    row
+                })
+                .collect::<Vec<_>>();
+            rendered.push_str(&format!("\n{}\n{}", title, output::columns(&rows)));
+        };
+        section(
+            "Words per directory",
+            self.words_by_dir
+                .iter()
+                .map(|(dir, words)| vec![dir.clone(), words.to_string()])
+                .collect(),
+        );
+        section(
+            "Notes created per month",
+            self.created_by_month
+                .iter()
+                .map(|(month, count)| vec![month.clone(), count.to_string()])
+                .collect(),
+        );
+        section(
+            "Largest notes",
+            self.largest
+                .iter()
+                .map(|(path, size)| vec![path.display().to_string(), format_size(*size)])
+                .collect(),
+        );
+        section(
+            "Oldest notes",
+            self.oldest
+                .iter()
+                .map(|(path, created)| vec![path.display().to_string(), created.to_string()])
+                .collect(),
+        );
+        rendered
+    }
+}
+
+/// What jot knows about a single note, for jot info.
+#[derive(Debug)]
+pub struct NoteInfo {
+    pub note: listing::Note,
+    pub created: Option<Date>,
+    pub modified: Option<Date>,
+    /// The number of other notes that link to the note, and that the note links to.
+    pub inbound: usize,
+    pub outbound: usize,
+    /// The files that are not notes that the note links to or embeds, relative to base-dir, and
+    /// whether they exist.
+    pub attachments: Vec<(PathBuf, bool)>,
+}
+
+impl NoteInfo {
+    /// Gathers what there is to know about the given note (see listing::notes), with its links
+    /// counted in the given graph, and its dates in the timezone the given offset (in seconds)
+    /// from UTC.
+    pub fn gather(
+        base_dir: &Path,
+        note: listing::Note,
+        graph: &Graph,
+        utc_offset: i64,
+    ) -> Result<NoteInfo> {
+        let date =
+            |timestamp: u64| Date::from_days((timestamp as i64 + utc_offset).div_euclid(86_400));
+        let content = std::fs::read_to_string(base_dir.join(&note.path))
+            .context(format!("failed to read note at {}", note.path.display()))?;
+        let notes = vault::notes(base_dir)?;
+        let resolver = links::Resolver::new(&notes);
+        let mut attachments = links::extract(&content)
+            .iter()
+            .filter_map(|link| resolver.resolve(&note.path, link))
+            .filter(|target| !vault::is_note(target))
+            .collect::<Vec<PathBuf>>();
+        attachments.sort();
+        attachments.dedup();
+
+        Ok(NoteInfo {
+            created: note.created.map(date),
+            modified: note.modified.map(date),
+            inbound: graph
+                .edges
+                .iter()
+                .filter(|(_, to)| *to == note.path)
+                .count(),
+            outbound: graph
+                .edges
+                .iter()
+                .filter(|(from, _)| *from == note.path)
+                .count(),
+            attachments: attachments
+                .into_iter()
+                .map(|attachment| {
+                    let exists = base_dir.join(&attachment).is_file();
+                    (attachment, exists)
+                })
+                .collect(),
+            note,
+        })
+    }
+
+    /// Formats what there is to know about the note as an aligned table.
+    pub fn render(&self) -> String {
+        let date = |date: Option<Date>| date.map_or("unknown".to_string(), |date| date.to_string());
+        let mut rows = vec![vec![
+            "Path".to_string(),
+            self.note.path.display().to_string(),
+        ]];
+        if let Some(title) = &self.note.title {
+            rows.push(vec!["Title".to_string(), title.clone()]);
+        }
+        rows.extend([
+            vec![
+                "Words".to_string(),
+                format!(
+                    "{} (about {} min to read)",
+                    self.note.words,
+                    reading_time(self.note.words)
+                ),
+            ],
+            vec!["Size".to_string(), format_size(self.note.size)],
+            vec!["Created".to_string(), date(self.created)],
+            vec!["Modified".to_string(), date(self.modified)],
+            vec![
+                "Tags".to_string(),
+                match self.note.tags.is_empty() {
+                    true => "none".to_string(),
+                    false => self.note.tags.join(", "),
+                },
+            ],
+            vec![
+                "Links".to_string(),
+                format!("{} in, {} out", self.inbound, self.outbound),
+            ],
+            vec![
+                "Attachments".to_string(),
+                self.attachments.len().to_string(),
+            ],
+        ]);
+        let mut rendered = output::columns(&rows);
+        for (attachment, exists) in &self.attachments {
+            let missing = if *exists { "" } else { " (missing)" };
+            rendered.push_str(&format!("  {}{}\n", attachment.display(), missing));
+        }
+        rendered
+    }
+}
+
+#[test]
+fn count_words() {
+    assert_eq!(
+        words("---\ntitle: a b\n---\n# A title\n\n- one, two\n- 3 — four\n"),
+        6
+    );
+    assert_eq!(
+        (reading_time(0), reading_time(1), reading_time(401)),
+        (0, 1, 3)
+    );
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(3 * 1024 * 1024 + 200 * 1024), "3.2 MiB");
+}
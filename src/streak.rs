@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use crate::date::Date;
+
+/// A run of consecutive days with a daily note, from its first day to its last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streak {
+    pub start: Date,
+    pub end: Date,
+}
+
+impl Streak {
+    /// Returns the number of days in the streak.
+    pub fn days(&self) -> i64 {
+        self.end.days() - self.start.days() + 1
+    }
+}
+
+/// Returns the runs of consecutive days in the given dates, oldest first, leaving out those after
+/// the given date, since a note written ahead of time does not make for a streak.
+pub fn streaks(dates: &BTreeSet<Date>, today: Date) -> Vec<Streak> {
+    let mut streaks: Vec<Streak> = Vec::new();
+    for date in dates.range(..=today) {
+        match streaks.last_mut() {
+            Some(streak) if streak.end.add_days(1) == *date => streak.end = *date,
+            _ => streaks.push(Streak {
+                start: *date,
+                end: *date,
+            }),
+        }
+    }
+    streaks
+}
+
+/// Returns the streak that is still going, i.e. that ends today, or yesterday, since today's note
+/// may not have been written yet, if any.
+pub fn current(streaks: &[Streak], today: Date) -> Option<Streak> {
+    streaks
+        .last()
+        .filter(|streak| streak.end >= today.add_days(-1))
+        .copied()
+}
+
+/// Returns the longest of the given streaks, or the latest of them if there is a tie.
+pub fn longest(streaks: &[Streak]) -> Option<Streak> {
+    streaks.iter().max_by_key(|streak| streak.days()).copied()
+}
+
+/// Describes the current and longest streaks in the given dates, e.g. for jot streak.
+pub fn render(dates: &BTreeSet<Date>, today: Date) -> String {
+    let plural = |days: i64| match days {
+        1 => "1 day".to_string(),
+        days => format!("{} days", days),
+    };
+    let streaks = streaks(dates, today);
+    let mut rendered = match current(&streaks, today) {
+        Some(streak) => format!(
+            "Current streak: {} (since {})\n",
+            plural(streak.days()),
+            streak.start
+        ),
+        None => "Current streak: 0 days\n".to_string(),
+    };
+    if let Some(streak) = longest(&streaks) {
+        rendered.push_str(&format!(
+            "Longest streak: {} ({} to {})\n",
+            plural(streak.days()),
+            streak.start,
+            streak.end
+        ));
+    }
+    rendered
+}
+
+#[test]
+fn count_streaks() {
+    let today = Date::from_days(100);
+    let dates = [90, 91, 92, 95, 96, 99, 100, 101]
+        .into_iter()
+        .map(Date::from_days)
+        .collect::<BTreeSet<Date>>();
+    let found = streaks(&dates, today);
+    assert_eq!(
+        found.iter().map(Streak::days).collect::<Vec<i64>>(),
+        [3, 2, 2]
+    );
+    assert_eq!(current(&found, today), found.last().copied());
+    assert_eq!(current(&found, today.add_days(2)), None);
+    assert_eq!(
+        longest(&found).map(|streak| streak.start),
+        Some(Date::from_days(90))
+    );
+    assert_eq!(render(&BTreeSet::new(), today), "Current streak: 0 days\n");
+}
@@ -0,0 +1,91 @@
+use std::{io::IsTerminal, ops::Range};
+
+use crate::cli;
+
+pub const BOLD: &str = "\x1b[1m";
+pub const DIM: &str = "\x1b[2m";
+pub const ITALIC: &str = "\x1b[3m";
+pub const UNDERLINE: &str = "\x1b[4m";
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const BLUE: &str = "\x1b[34m";
+pub const MAGENTA: &str = "\x1b[35m";
+pub const CYAN: &str = "\x1b[36m";
+pub const RESET: &str = "\x1b[0m";
+
+/// Styles text with ANSI escape codes, or leaves it as is, depending on whether the output it is
+/// for should be colored.
+#[derive(Debug, Clone, Copy)]
+pub struct Styler {
+    enabled: bool,
+}
+
+impl Styler {
+    /// Returns the styler for output to a stream, which is a terminal or not, per the given color
+    /// mode. In auto mode, output is colored if it goes to a terminal, unless $NO_COLOR is set to
+    /// a non-empty value (see https://no-color.org).
+    pub fn new(color: cli::ColorMode, is_terminal: bool) -> Styler {
+        let enabled = match color {
+            cli::ColorMode::Always => true,
+            cli::ColorMode::Never => false,
+            cli::ColorMode::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        };
+        Styler { enabled }
+    }
+
+    /// Returns the styler for jot's stdout.
+    pub fn stdout(args: &cli::Args) -> Styler {
+        Styler::new(args.color, std::io::stdout().is_terminal())
+    }
+
+    /// Returns the styler for jot's stderr.
+    pub fn stderr(args: &cli::Args) -> Styler {
+        Styler::new(args.color, std::io::stderr().is_terminal())
+    }
+
+    /// Whether output is colored.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Wraps the given text in the given escape codes, e.g. `&[BOLD, RED]`, if output is colored.
+    pub fn paint(&self, text: &str, codes: &[&str]) -> String {
+        match self.enabled && !text.is_empty() {
+            true => format!("{}{}{}", codes.concat(), text, RESET),
+            false => text.to_string(),
+        }
+    }
+
+    /// Paints the given byte ranges of the given text, e.g. matches in a line, with the given
+    /// escape codes, and leaves the rest as is. The ranges must be in order and not overlap.
+    pub fn paint_ranges(&self, text: &str, ranges: &[Range<usize>], codes: &[&str]) -> String {
+        let mut painted = String::new();
+        let mut end = 0;
+        for range in ranges {
+            if range.start < end {
+                continue;
+            }
+            painted.push_str(&text[end..range.start]);
+            painted.push_str(&self.paint(&text[range.clone()], codes));
+            end = range.end;
+        }
+        painted.push_str(&text[end..]);
+        painted
+    }
+}
+
+#[test]
+fn paint_text() {
+    let styler = Styler::new(cli::ColorMode::Always, false);
+    assert_eq!(styler.paint("a", &[BOLD, RED]), "\x1b[1m\x1b[31ma\x1b[0m");
+    assert_eq!(styler.paint("", &[BOLD]), "");
+    assert_eq!(
+        styler.paint_ranges("a [[b]] c [[d]]", &[4..5, 12..13], &[RED]),
+        "a [[\x1b[31mb\x1b[0m]] c [[\x1b[31md\x1b[0m]]"
+    );
+    let styler = Styler::new(cli::ColorMode::Never, true);
+    assert_eq!(styler.paint("a", &[BOLD, RED]), "a");
+}
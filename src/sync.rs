@@ -0,0 +1,699 @@
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use humantime::format_rfc3339_seconds;
+
+use crate::{
+    cli, cmd::exec_cmd, crypt, git, ignore::Ignore, lfs, object_store, progress, rsync, snapshot,
+    syncthing, undo, vault,
+};
+
+/// What the names of the branches that devices commit to start with, when each has its own.
+const DEVICE_BRANCH_PREFIX: &str = "devices/";
+
+/// The most files a summary lists individually.
+const SUMMARY_MAX_FILES: usize = 5;
+
+/// Summarizes the given changes, e.g. `edit projects/foo.md (+42 -3); add images/diagram.png`.
+fn summarize(diffs: &[git::FileDiff]) -> String {
+    let mut parts = diffs
+        .iter()
+        .take(SUMMARY_MAX_FILES)
+        .map(|diff| {
+            let verb = match diff.kind {
+                git::ChangeKind::Add => "add",
+                git::ChangeKind::Edit => "edit",
+                git::ChangeKind::Delete => "delete",
+            };
+            match (diff.kind, diff.lines) {
+                (git::ChangeKind::Add, Some((insertions, _))) => {
+                    format!("{} {} (+{})", verb, diff.path.display(), insertions)
+                }
+                (git::ChangeKind::Edit, Some((insertions, deletions))) => format!(
+                    "{} {} (+{} -{})",
+                    verb,
+                    diff.path.display(),
+                    insertions,
+                    deletions
+                ),
+                _ => format!("{} {}", verb, diff.path.display()),
+            }
+        })
+        .collect::<Vec<String>>();
+    if diffs.len() > SUMMARY_MAX_FILES {
+        parts.push(format!("and {} more", diffs.len() - SUMMARY_MAX_FILES));
+    }
+    parts.join("; ")
+}
+
+/// Replaces each `{placeholder}` in the given template with the value the given function returns
+/// for it.
+fn render_template(
+    template: &str,
+    mut value: impl FnMut(&str) -> Result<String>,
+) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            bail!(
+                "unterminated placeholder in the commit template: {}",
+                template
+            );
+        };
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(&value(&rest[start + 1..start + len])?);
+        rest = &rest[start + len + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// A way of syncing base-dir with wherever else the vault is kept.
+pub trait Backend {
+    /// Syncs base-dir, doing only the steps that the given arguments ask for.
+    fn sync(&self, sync_args: &cli::SyncArgs) -> Result<()>;
+
+    /// Describes what syncing with the given arguments would do, without doing any of it.
+    fn print_sync(&self, sync_args: &cli::SyncArgs) -> Result<()>;
+}
+
+/// Returns the sync backend that was asked for via the sync-backend flag.
+pub fn backend(args: &cli::Args) -> Result<Box<dyn Backend + '_>> {
+    Ok(match args.sync_backend {
+        cli::SyncBackend::Git => Box::new(Steps::new(args)?),
+        cli::SyncBackend::Rsync => Box::new(rsync::Rsync::new(args)?),
+        cli::SyncBackend::S3 | cli::SyncBackend::Webdav => {
+            Box::new(object_store::ObjectStore::new(args)?)
+        }
+        cli::SyncBackend::Syncthing => Box::new(syncthing::Syncthing),
+    })
+}
+
+/// Prints the given files that were (or with dry_run, would be) pulled from or pushed to the
+/// given target.
+pub fn print_copied(files: &[impl AsRef<str>], pulled: bool, target: &str, dry_run: bool) {
+    let would = if dry_run { "would have " } else { "" };
+    let (verb, direction) = if pulled {
+        ("pulled", "from")
+    } else {
+        ("pushed", "to")
+    };
+    if files.is_empty() {
+        println!("{}{} nothing {} {}", would, verb, direction, target);
+    } else {
+        println!(
+            "{}{} {} {}:\n\t{}",
+            would,
+            verb,
+            direction,
+            target,
+            files
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<&str>>()
+                .join("\n\t")
+        );
+    }
+}
+
+/// Returns which of pulling, committing and pushing a sync with the given arguments does.
+/// Pull-only, push-only and local syncs skip everything but their one step, and flushes skip
+/// committing.
+pub fn phases(sync_args: &cli::SyncArgs) -> (bool, bool, bool) {
+    (
+        !sync_args.push && !sync_args.local,
+        !sync_args.pull && !sync_args.push && !sync_args.flush,
+        !sync_args.pull && !sync_args.local,
+    )
+}
+
+/// The individual steps that make up a sync. Each step can be run on its own, or "printed", which
+/// describes what the step would do without doing it.
+pub struct Steps<'a> {
+    args: &'a cli::Args,
+    git: Box<dyn git::Backend + 'a>,
+    /// Set once upstream turns out to be unreachable, after which the sync carries on locally.
+    offline: Cell<bool>,
+}
+
+impl<'a> Steps<'a> {
+    pub fn new(args: &'a cli::Args) -> Result<Steps<'a>> {
+        // Device branches are pushed as they are, so rebasing them would get their pushes
+        // rejected, and fast-forwarding alone could never integrate the other devices' changes.
+        if args.device_branch.is_some() && !matches!(args.pull_strategy, cli::PullStrategy::Merge) {
+            bail!("device branches can only be synced with the merge pull strategy");
+        }
+        Ok(Steps {
+            args,
+            git: git::backend(args)?,
+            offline: Cell::new(false),
+        })
+    }
+
+    pub fn git(&self) -> &dyn git::Backend {
+        self.git.as_ref()
+    }
+
+    /// Returns the branch this device commits to, if it has its own.
+    fn device_branch(&self) -> Option<String> {
+        self.args
+            .device_branch
+            .as_ref()
+            .map(|name| format!("{}{}", DEVICE_BRANCH_PREFIX, name))
+    }
+
+    /// Returns the local branch that syncing commits to.
+    pub fn branch(&self) -> String {
+        self.device_branch()
+            .unwrap_or_else(|| self.args.git_upstream_branch.clone())
+    }
+
+    /// Checks out the branch this device commits to, if it has its own, creating it if needed.
+    pub fn switch_to_device_branch(&self) -> Result<()> {
+        match self.device_branch() {
+            Some(branch) => git::switch_branch(self.args, &branch),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if there are any local changes that a commit would include.
+    pub fn has_changes(&self) -> Result<bool> {
+        Ok(!self.files_to_stage()?.is_empty())
+    }
+
+    /// Returns the message for committing the current local changes, rendered from the
+    /// commit-template flag.
+    pub fn default_message(&self) -> Result<String> {
+        let files = self.files_to_stage()?;
+        render_template(&self.args.commit_template, |placeholder| {
+            Ok(match placeholder {
+                "timestamp" => format_rfc3339_seconds(SystemTime::now()).to_string(),
+                "hostname" => {
+                    exec_cmd(
+                        "getting hostname",
+                        Command::new("hostname"),
+                        true,
+                        self.args.quiet_on_ctrl_c,
+                    )?
+                    .0
+                }
+                "files_changed" => files.len().to_string(),
+                "summary" => summarize(&self.git.diff_stats(&files)?),
+                "first_changed_note" => files
+                    .iter()
+                    .find(|path| vault::is_note(path))
+                    .map_or_else(String::new, |path| path.display().to_string()),
+                _ => bail!(
+                    "unknown placeholder in the commit template: {{{}}}",
+                    placeholder
+                ),
+            })
+        })
+    }
+
+    /// Returns the message to commit with when syncing: the one given to the sync, if any, or else
+    /// the rendered commit template, unless the user asked to be prompted for a message by git
+    /// instead.
+    fn commit_message(&self, sync_args: &cli::SyncArgs) -> Result<Option<String>> {
+        if let Some(message) = &sync_args.message {
+            Ok(Some(message.clone()))
+        } else if self.args.git_custom_commit_msg {
+            Ok(None)
+        } else {
+            Ok(Some(self.default_message()?))
+        }
+    }
+
+    fn files_to_stage(&self) -> Result<Vec<PathBuf>> {
+        let ignore = Ignore::load(&self.args.base_dir, &self.args.ignore)?;
+        Ok(self
+            .git
+            .changed_files()?
+            .into_iter()
+            .filter(|path| !ignore.is_ignored(path))
+            .collect())
+    }
+
+    /// Runs the given operation against a remote, retrying it with exponential backoff for as long
+    /// as it fails because the remote could not be reached, up to the number of attempts the
+    /// sync-attempts flag allows.
+    fn with_retries(&self, label: &str, mut operation: impl FnMut() -> Result<()>) -> Result<()> {
+        let mut delay = Duration::from_secs(1);
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(err) if attempt < self.args.sync_attempts && git::is_network_error(&err) => {
+                    println!(
+                        "{} failed (attempt {} of {}), retrying in {}s",
+                        label,
+                        attempt,
+                        self.args.sync_attempts,
+                        delay.as_secs()
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Integrates upstream changes, and if this device has its own branch, those on every other
+    /// device's branch.
+    fn pull_branches(&self) -> Result<()> {
+        let remote = &self.args.git_remote_name;
+        self.git.pull(
+            remote,
+            &self.args.git_upstream_branch,
+            self.args.pull_strategy,
+        )?;
+        let Some(device_branch) = self.device_branch() else {
+            return Ok(());
+        };
+
+        self.git.fetch(
+            remote,
+            &format!(
+                "+refs/heads/{0}*:refs/remotes/{1}/{0}*",
+                DEVICE_BRANCH_PREFIX, remote
+            ),
+        )?;
+        for branch in git::remote_branches(remote, DEVICE_BRANCH_PREFIX)? {
+            if branch != device_branch {
+                self.git.pull(remote, &branch, self.args.pull_strategy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches and integrates upstream changes. Uncommitted local changes are stashed for the
+    /// duration of the pull, so that they do not get in its way. If upstream cannot be reached,
+    /// e.g. because there is no network, the pull is skipped, and so is the push that follows.
+    pub fn pull(&self) -> Result<()> {
+        // Vaults that are only kept locally have nothing to pull from.
+        if !git::has_remote(&self.args.git_remote_name)? {
+            println!(
+                "there is no remote named {}, skipping pull",
+                self.args.git_remote_name
+            );
+            return Ok(());
+        }
+
+        let stashed = self.git.stash()?;
+
+        // If we encounter an issue, namely a merge conflict, this will propagate an error and we
+        // will abort on trying to merge our recent changes.
+        let pulled = match self.with_retries("pulling", || self.pull_branches()) {
+            Err(err) if git::is_network_error(&err) => {
+                println!(
+                    "could not reach {} after {} attempt(s), syncing offline, i.e. only \
+                    committing locally",
+                    self.args.git_remote_name, self.args.sync_attempts
+                );
+                self.offline.set(true);
+                Ok(())
+            }
+            pulled => pulled.context(
+                "failed to pull upstream changes, please fix the issue and run jot sync, or if \
+                there are conflicts, resolve them with jot conflicts",
+            ),
+        };
+
+        // Even if the pull failed, we should do our best to put the user's changes back where
+        // they were. Whatever happens though, they are never lost, since git keeps them stashed
+        // if they cannot be restored.
+        if stashed {
+            let unstashed = self.git.unstash().context(
+                "failed to restore local changes after pulling, they are still saved in git's \
+                stash (see git stash list), if they conflict with upstream changes, resolve them \
+                with jot conflicts",
+            );
+            if let (Ok(_), Err(err)) = (&pulled, unstashed) {
+                return Err(err);
+            }
+        }
+
+        pulled
+    }
+
+    pub fn print_pull(&self) -> Result<()> {
+        if !git::has_remote(&self.args.git_remote_name)? {
+            println!(
+                "would skip pulling, there is no remote named {}",
+                self.args.git_remote_name
+            );
+            return Ok(());
+        }
+        println!(
+            "would pull {} from {} ({}), stashing local changes for the duration",
+            self.args.git_upstream_branch,
+            self.args.git_remote_name,
+            match self.args.pull_strategy {
+                cli::PullStrategy::Merge => "merge",
+                cli::PullStrategy::Rebase => "rebase",
+                cli::PullStrategy::FfOnly => "fast-forward only",
+            }
+        );
+        if let Some(device_branch) = self.device_branch() {
+            println!(
+                "would merge every other device's branch into {}",
+                device_branch
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the given files that are larger than the lfs-threshold-kib flag allows, and not
+    /// stored with Git LFS yet, along with the pattern to track each of them with.
+    fn large_files(&self, paths: &[PathBuf]) -> Result<Vec<(PathBuf, String)>> {
+        let Some(threshold) = self.args.lfs_threshold_kib else {
+            return Ok(Vec::new());
+        };
+        let mut large_files: Vec<(PathBuf, String)> = Vec::new();
+        for path in paths {
+            // Deleted files have no size, and notes are never worth storing elsewhere. Paths are
+            // relative to base-dir, which jot is in.
+            let metadata = match std::fs::metadata(path) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(err)
+                        .context(format!("failed to read metadata of {}", path.display()))
+                }
+            };
+            if vault::is_note(path) || metadata.len() <= threshold * 1024 || lfs::is_tracked(path)?
+            {
+                continue;
+            }
+            let pattern = lfs::pattern(path);
+            if !large_files.iter().any(|(_, tracked)| *tracked == pattern) {
+                large_files.push((path.clone(), pattern));
+            }
+        }
+        Ok(large_files)
+    }
+
+    /// Stages local changes, save for anything the user asked us to ignore, and commits them with
+    /// the given message. If no message is given, git prompts the user for one. Attachments that
+    /// are larger than the lfs-threshold-kib flag allows are stored with Git LFS.
+    pub fn commit(&self, message: Option<&str>) -> Result<()> {
+        // Without git-crypt's key, whatever is staged is committed as it is, i.e. unencrypted.
+        if crypt::is_encrypted(Path::new("."))? && !crypt::is_unlocked()? {
+            bail!(
+                "base-dir is encrypted with git-crypt, but locked, unlock it with git-crypt unlock \
+                before syncing"
+            );
+        }
+
+        let mut to_stage = self.files_to_stage()?;
+        let large_files = self.large_files(&to_stage)?;
+        for (path, pattern) in &large_files {
+            println!(
+                "tracking {} with Git LFS, since {} is larger than {} KiB",
+                pattern,
+                path.display(),
+                self.args.lfs_threshold_kib.unwrap_or_default()
+            );
+            lfs::track(self.args, pattern)?;
+        }
+        let gitattributes_path = PathBuf::from(".gitattributes");
+        if !large_files.is_empty() && !to_stage.contains(&gitattributes_path) {
+            to_stage.push(gitattributes_path);
+        }
+        progress::step(self.args, "staging", || self.git.stage(&to_stage))?;
+        progress::terminal_step(self.args, "committing", || self.git.commit(message))?;
+        undo::record(self.args)
+    }
+
+    pub fn print_commit(&self, message: Option<&str>) -> Result<()> {
+        let to_stage = self.files_to_stage()?;
+        if to_stage.is_empty() {
+            println!("would stage nothing");
+        } else {
+            println!("would stage:");
+            for path in &to_stage {
+                println!("\t{}", path.display());
+            }
+        }
+        for (path, pattern) in self.large_files(&to_stage)? {
+            println!(
+                "would track {} with Git LFS, since {} is too large",
+                pattern,
+                path.display()
+            );
+        }
+
+        match message {
+            Some(message) => println!("would commit with message: {}", message),
+            None => println!("would commit with a message prompted for by git"),
+        }
+        if self.args.sign_commits || self.args.signing_key.is_some() {
+            println!("would sign the commit");
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the snapshot tag for the current period, if the snapshot-tags flag asks
+    /// for them.
+    fn snapshot_name(&self) -> Option<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.args
+            .snapshot_tags
+            .map(|period| snapshot::tag_name(now.as_secs(), period))
+    }
+
+    /// Tags the vault as the current period's snapshot, unless it already has one.
+    pub fn snapshot(&self) -> Result<()> {
+        if let Some(name) = self.snapshot_name() {
+            if snapshot::create(self.args, &name)? {
+                println!("tagged snapshot {}", name);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn print_snapshot(&self) {
+        if let Some(name) = self.snapshot_name() {
+            println!("would tag snapshot {}, unless it already exists", name);
+        }
+    }
+
+    /// Returns every remote that is pushed to (upstream first, then any mirrors), split into those
+    /// that are configured, and those that are not.
+    fn push_remotes(&self) -> Result<(Vec<&String>, Vec<&String>)> {
+        let (mut configured, mut missing) = (Vec::new(), Vec::new());
+        for remote in
+            std::iter::once(&self.args.git_remote_name).chain(&self.args.git_mirror_remote)
+        {
+            if git::has_remote(remote)? {
+                configured.push(remote);
+            } else {
+                missing.push(remote);
+            }
+        }
+        Ok((configured, missing))
+    }
+
+    /// Pushes committed changes to upstream and every mirror. As long as any of them could be
+    /// pushed to, failing to push to the others (e.g. because they are unreachable) is only
+    /// reported, since they catch up on the next sync anyway. Remotes that are not configured at
+    /// all are skipped.
+    pub fn push(&self) -> Result<()> {
+        if self.offline.get() {
+            return self.print_pending_push();
+        }
+
+        let (remotes, missing) = self.push_remotes()?;
+        for remote in missing {
+            println!("there is no remote named {}, skipping push to it", remote);
+        }
+        let mut failures = Vec::new();
+        for remote in &remotes {
+            if let Err(err) = self.push_to(remote) {
+                failures.push((remote, err));
+            }
+        }
+
+        if failures.len() < remotes.len() || remotes.is_empty() {
+            for (remote, err) in failures {
+                println!(
+                    "warning: failed to push to {}, skipping it: {:#}",
+                    remote, err
+                );
+            }
+            return Ok(());
+        }
+        if failures.iter().all(|(_, err)| git::is_network_error(err)) {
+            println!(
+                "could not reach any remote to push to after {} attempt(s)",
+                self.args.sync_attempts
+            );
+            return self.print_pending_push();
+        }
+        if remotes.len() == 1 {
+            let (_, err) = failures.remove(0);
+            return Err(err)
+                .context("failed to push to upstream, please fix the issue and run jot sync");
+        }
+        bail!(
+            "failed to push to any remote, please fix the issue and run jot sync:\n\t! {}",
+            failures
+                .iter()
+                .map(|(remote, err)| format!("{}: {:#}", remote, err))
+                .collect::<Vec<String>>()
+                .join("\n\t! ")
+        )
+    }
+
+    /// Pushes committed changes, and then any snapshot tags, to the given remote. The snapshots
+    /// are only a convenience, so failing to push them is only reported.
+    fn push_to(&self, remote: &str) -> Result<()> {
+        self.push_branches_to(remote)?;
+        if let Some(period) = self.args.snapshot_tags {
+            let label = format!("pushing snapshots to {}", remote);
+            if let Err(err) =
+                self.with_retries(&label, || snapshot::push(self.args, remote, period))
+            {
+                println!("warning: failed to push snapshots to {}: {:#}", remote, err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes committed changes to the given remote. If this device has its own branch, it is
+    /// pushed first, which is never rejected, since no other device pushes to it. Upstream is
+    /// then fast-forwarded to it, unless another device got there first, in which case this
+    /// device's changes reach upstream with its next sync.
+    fn push_branches_to(&self, remote: &str) -> Result<()> {
+        let label = format!("pushing to {}", remote);
+        let upstream = &self.args.git_upstream_branch;
+        let Some(device_branch) = self.device_branch() else {
+            return self.with_retries(&label, || self.git.push(remote, upstream));
+        };
+
+        self.with_retries(&label, || self.git.push(remote, &device_branch))?;
+        match self.with_retries(&label, || self.git.push(remote, upstream)) {
+            Err(err) if !git::is_network_error(&err) => {
+                println!(
+                    "warning: could not update {}/{} ({:#}), it gets {} merged into it on the \
+                    next sync",
+                    remote, upstream, err, device_branch
+                );
+                Ok(())
+            }
+            pushed => pushed,
+        }
+    }
+
+    /// Tells the user how many commits are waiting to be pushed. Git itself keeps track of them,
+    /// as the commits that upstream's remote-tracking branch is behind on.
+    fn print_pending_push(&self) -> Result<()> {
+        let pending = match self
+            .git
+            .ahead_behind(&self.args.git_remote_name, &self.args.git_upstream_branch)?
+        {
+            Some((ahead, _)) => format!("{} commit(s) are", ahead),
+            None => "commits are".to_string(),
+        };
+        println!(
+            "{} waiting to be pushed, run jot sync --flush once online",
+            pending
+        );
+        Ok(())
+    }
+
+    pub fn print_push(&self) -> Result<()> {
+        let (remotes, missing) = self.push_remotes()?;
+        if !remotes.is_empty() {
+            println!(
+                "would push {} to {}",
+                self.device_branch().map_or_else(
+                    || self.args.git_upstream_branch.clone(),
+                    |device_branch| format!(
+                        "{} and then {}",
+                        device_branch, self.args.git_upstream_branch
+                    )
+                ),
+                remotes
+                    .iter()
+                    .map(|remote| remote.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            );
+        }
+        if !remotes.is_empty() && self.args.snapshot_tags.is_some() {
+            println!("would push snapshot tags along with it");
+        }
+        for remote in missing {
+            println!("would skip pushing to {}, there is no such remote", remote);
+        }
+        Ok(())
+    }
+}
+
+impl Backend for Steps<'_> {
+    fn sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        let (pulls, commits, pushes) = phases(sync_args);
+        lfs::check(&self.args.base_dir)?;
+        self.switch_to_device_branch()?;
+        if pulls {
+            progress::terminal_step(self.args, "pulling", || self.pull())?;
+        }
+        if commits {
+            self.commit(self.commit_message(sync_args)?.as_deref())?;
+        }
+        self.snapshot()?;
+        if pushes {
+            progress::terminal_step(self.args, "pushing", || self.push())?;
+        }
+        Ok(())
+    }
+
+    fn print_sync(&self, sync_args: &cli::SyncArgs) -> Result<()> {
+        let (pulls, commits, pushes) = phases(sync_args);
+        if pulls {
+            self.print_pull()?;
+        }
+        if commits {
+            self.print_commit(self.commit_message(sync_args)?.as_deref())?;
+        }
+        self.print_snapshot();
+        if pushes {
+            self.print_push()?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn commit_template() {
+    let rendered = render_template("{a} and {b}!", |placeholder| Ok(placeholder.to_uppercase()));
+    assert_eq!(rendered.unwrap(), "A and B!");
+    assert!(render_template("{a", |_| Ok(String::new())).is_err());
+}
+
+#[test]
+fn summary() {
+    let diff = |path: &str, kind, lines| git::FileDiff {
+        path: PathBuf::from(path),
+        kind,
+        lines,
+    };
+    assert_eq!(
+        summarize(&[
+            diff("projects/foo.md", git::ChangeKind::Edit, Some((42, 3))),
+            diff("images/diagram.png", git::ChangeKind::Add, None),
+            diff("old.md", git::ChangeKind::Delete, Some((0, 7))),
+        ]),
+        "edit projects/foo.md (+42 -3); add images/diagram.png; delete old.md"
+    );
+}
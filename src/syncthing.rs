@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::{cli, merge, sync, vault};
+
+/// What Syncthing puts in the names of the copies it keeps of files that were changed on two
+/// devices at once, e.g. `note.sync-conflict-20240101-120000-ABCDEFG.md` for `note.md`.
+static CONFLICT_COPY_INFIX: &str = ".sync-conflict-";
+
+/// Returns the path of the file that the file at the given path is a conflict copy of, or None
+/// if it is not a conflict copy.
+pub fn original_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let start = name.find(CONFLICT_COPY_INFIX)?;
+    let rest = &name[start + CONFLICT_COPY_INFIX.len()..];
+    let extension = rest.find('.').map_or("", |idx| &rest[idx..]);
+    Some(path.with_file_name(format!("{}{}", &name[..start], extension)))
+}
+
+/// Returns every conflict copy in the vault, relative to base-dir, along with the path of the file
+/// it is a copy of.
+pub fn conflict_copies() -> Result<Vec<(PathBuf, PathBuf)>> {
+    Ok(vault::files(Path::new("."))?
+        .into_iter()
+        .filter_map(|path| Some((original_path(&path)?, path)))
+        .collect())
+}
+
+/// Merges the given conflict copy into the given file, returning the merged contents and the
+/// number of conflicts in them, or None if the file is not a note and so cannot be merged. A copy
+/// of a file that no longer exists is merged as is.
+pub fn merge(original: &Path, copy: &Path) -> Result<Option<(String, usize)>> {
+    if !vault::is_note(original) {
+        return Ok(None);
+    }
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).context(format!("failed to read note at {}", path.display()))
+    };
+    let theirs = read(copy)?;
+    if !original.exists() {
+        return Ok(Some((theirs, 0)));
+    }
+    Ok(Some(merge::merge_without_base(&read(original)?, &theirs)))
+}
+
+/// Writes the given merged contents to the given file, and removes the conflict copy that was
+/// merged into it.
+pub fn resolve(original: &Path, copy: &Path, merged: &str) -> Result<()> {
+    std::fs::write(original, merged)
+        .context(format!("failed to write note at {}", original.display()))?;
+    std::fs::remove_file(copy).context(format!("failed to remove {}", copy.display()))
+}
+
+/// Leaves syncing base-dir to Syncthing, and only merges the conflict copies it kept into the
+/// notes they are copies of. Copies that cannot be merged cleanly are left for jot conflicts.
+pub struct Syncthing;
+
+impl Syncthing {
+    fn run(&self, dry_run: bool) -> Result<()> {
+        let mut unmerged = Vec::new();
+        for (original, copy) in conflict_copies()? {
+            match merge(&original, &copy)? {
+                Some((merged, 0)) => {
+                    if !dry_run {
+                        resolve(&original, &copy, &merged)?;
+                    }
+                    println!(
+                        "{}merged {} into {}",
+                        if dry_run { "would have " } else { "" },
+                        copy.display(),
+                        original.display()
+                    );
+                }
+                _ => unmerged.push(copy.display().to_string()),
+            }
+        }
+
+        if !unmerged.is_empty() {
+            println!(
+                "warning: the following Syncthing conflict copies could not be merged cleanly, \
+                resolve them with jot conflicts:\n\t! {}",
+                unmerged.join("\n\t! ")
+            );
+        }
+        Ok(())
+    }
+}
+
+impl sync::Backend for Syncthing {
+    fn sync(&self, _: &cli::SyncArgs) -> Result<()> {
+        self.run(false)
+    }
+
+    fn print_sync(&self, _: &cli::SyncArgs) -> Result<()> {
+        self.run(true)
+    }
+}
+
+#[test]
+fn conflict_copy_paths() {
+    assert_eq!(
+        original_path(Path::new("a/note.sync-conflict-20240101-120000-ABCDEFG.md")),
+        Some(PathBuf::from("a/note.md"))
+    );
+    assert_eq!(
+        original_path(Path::new("TODO.sync-conflict-20240101-120000-ABCDEFG")),
+        Some(PathBuf::from("TODO"))
+    );
+    assert_eq!(original_path(Path::new("a/note.md")), None);
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, cmd, date, editor, git::GIT_CMD};
+
+/// Returns the path of the template with the given name, i.e. the file in the templates directory
+/// with that name, with or without a `.md` extension, e.g. `templates/meeting.md` for `meeting`.
+pub fn path(args: &cli::Args, name: &str) -> Result<PathBuf> {
+    let templates_dir = args.base_dir.join(&args.templates_dir);
+    let candidates = [
+        templates_dir.join(name),
+        templates_dir.join(format!("{}.md", name)),
+    ];
+    match candidates.into_iter().find(|candidate| candidate.is_file()) {
+        Some(path) => Ok(path),
+        None => bail!(
+            "there is no template named {} in {}",
+            name,
+            templates_dir.display()
+        ),
+    }
+}
+
+/// The name of the file that declares the default template for the notes created in the directory
+/// it is in, and in the directories beneath it, e.g. `meetings/.jot-template` containing
+/// `meeting`.
+pub static DEFAULT_TEMPLATE_FILENAME: &str = ".jot-template";
+
+/// Returns the name of the default template for the given new note (relative to base-dir), as
+/// declared by the closest of the directories it is in, if any.
+pub fn default_for(args: &cli::Args, note: &Path) -> Result<Option<String>> {
+    for dir in note.ancestors().skip(1) {
+        let path = args.base_dir.join(dir).join(DEFAULT_TEMPLATE_FILENAME);
+        if !path.is_file() {
+            continue;
+        }
+        let name =
+            std::fs::read_to_string(&path).context(format!("failed to read {}", path.display()))?;
+        return Ok(Some(name.trim().to_string()).filter(|name| !name.is_empty()));
+    }
+    Ok(None)
+}
+
+/// Returns the title of the note at the given path, derived from its file name, e.g. `Weekly sync`
+/// for `meetings/weekly-sync.md`.
+pub fn title(note: &Path) -> String {
+    let stem = note
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace(['-', '_'], " ");
+    let mut chars = stem.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => stem,
+    }
+}
+
+/// The characters that may not be in file names on some filesystem or other.
+static UNSAFE_FILENAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Returns the given title as a file name without an extension, with the given separator between
+/// its words. Punctuation is stripped, unless asked to be kept, in which case only what is unsafe
+/// in file names is, e.g. `quarterly-planning-q3` for `Quarterly Planning: Q3` by default.
+fn slug_with(title: &str, separator: &str, lowercase: bool, keep_punctuation: bool) -> String {
+    let is_word_char = |c: char| match keep_punctuation {
+        true => !c.is_whitespace() && !c.is_control() && !UNSAFE_FILENAME_CHARS.contains(&c),
+        false => c.is_alphanumeric(),
+    };
+    let slug = title
+        .split(|c: char| !is_word_char(c))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator);
+    match lowercase {
+        true => slug.to_lowercase(),
+        false => slug,
+    }
+}
+
+/// Returns the file name, without an extension, for a note with the given title, as the slug
+/// flags say it should be.
+pub fn slug(args: &cli::Args, title: &str) -> String {
+    slug_with(
+        title,
+        &args.slug_separator,
+        !args.slug_keep_case,
+        args.slug_keep_punctuation,
+    )
+}
+
+/// Returns who is writing notes, i.e. whoever jot commits as.
+fn author(args: &cli::Args) -> Result<String> {
+    if let Some(name) = &args.git_author_name {
+        return Ok(name.clone());
+    }
+    let output = Command::new(GIT_CMD)
+        .args(["config", "user.name"])
+        .output()
+        .context("failed to read git's user.name")?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(match name.is_empty() {
+        true => std::env::var("USER").unwrap_or_default(),
+        false => name,
+    })
+}
+
+/// Replaces each `{{variable}}` in the given template with the value the given function returns
+/// for it. Whitespace around variables' names is ignored.
+fn render(template: &str, mut value: impl FnMut(&str) -> Result<String>) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            bail!("unterminated variable in template: {}", &rest[start..]);
+        };
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(&value(rest[start + 2..start + len].trim())?);
+        rest = &rest[start + len + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Marks where a template's `{{cursor}}` was in a note created from it, until [`take_cursor`]
+/// takes it out again.
+const CURSOR_MARKER: &str = "\u{0}cursor\u{0}";
+
+/// Returns the given note contents without the place where the cursor should start, as marked by
+/// its template's `{{cursor}}`, along with that place, if any.
+pub fn take_cursor(content: &str) -> (String, Option<editor::Cursor>) {
+    let cursor = content.find(CURSOR_MARKER).map(|idx| {
+        let before = &content[..idx];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        editor::Cursor {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    });
+    (content.replace(CURSOR_MARKER, ""), cursor)
+}
+
+/// Returns the contents of a new note with the given title, created from the template with the
+/// given name for the given date. Templates may contain `{{date}}` (e.g. 2024-05-01), `{{time}}`
+/// (e.g. 09:30), `{{title}}`, `{{author}}` and `{{prompt:<question>}}` variables, the latter of
+/// which jot asks for the values of, and a `{{cursor}}` where writing should begin. Each question
+/// is only asked once, however often it is in the template. The given variables take precedence
+/// over these. Among them, `stdin` is the given input, e.g. what was piped into jot new
+/// --from-stdin, which is appended to the template if the template has no `{{stdin}}` variables.
+pub fn instantiate(
+    args: &cli::Args,
+    name: &str,
+    title: &str,
+    date: date::Date,
+    variables: &[(&str, &str)],
+) -> Result<String> {
+    let path = path(args, name)?;
+    let template = std::fs::read_to_string(&path)
+        .context(format!("failed to read template {}", path.display()))?;
+
+    let mut answers: HashMap<String, String> = HashMap::new();
+    let mut is_stdin_used = false;
+    let mut rendered = render(&template, |variable| {
+        if let Some((_, value)) = variables.iter().find(|(name, _)| *name == variable) {
+            is_stdin_used |= variable == "stdin";
+            return Ok(value.to_string());
+        }
+        Ok(match variable {
+            "stdin" => bail!(
+                "template {} can only be used with --from-stdin",
+                path.display()
+            ),
+            "date" => date.to_string(),
+            "time" => date::local_time()?,
+            "title" => title.to_string(),
+            "author" => author(args)?,
+            "cursor" => CURSOR_MARKER.to_string(),
+            _ => match variable.strip_prefix("prompt:") {
+                Some(question) => match answers.get(question) {
+                    Some(answer) => answer.clone(),
+                    None => {
+                        let answer = cmd::prompt(&format!("{}: ", question.trim()))?;
+                        answers.insert(question.to_string(), answer.clone());
+                        answer
+                    }
+                },
+                None => bail!(
+                    "unknown variable {{{{{}}}}} in template {}",
+                    variable,
+                    path.display()
+                ),
+            },
+        })
+    })?;
+    let stdin = variables.iter().find(|(name, _)| *name == "stdin");
+    if let Some((_, stdin)) = stdin.filter(|_| !is_stdin_used) {
+        if !rendered.is_empty() && !rendered.ends_with('\n') {
+            rendered.push('\n');
+        }
+        rendered.push_str(stdin);
+    }
+    Ok(rendered)
+}
+
+#[test]
+fn render_templates() {
+    let rendered = render(
+        "# {{ title }}\n\n{{prompt:Who}} and {{prompt:Who}}\n",
+        |variable| Ok(variable.to_uppercase()),
+    );
+    assert_eq!(rendered.unwrap(), "# TITLE\n\nPROMPT:WHO and PROMPT:WHO\n");
+    assert!(render("{{date", |_| Ok(String::new())).is_err());
+    assert_eq!(
+        take_cursor(&format!("# a\nü {}b\n", CURSOR_MARKER)),
+        (
+            "# a\nü b\n".to_string(),
+            Some(editor::Cursor { line: 2, column: 3 })
+        )
+    );
+    assert_eq!(title(Path::new("meetings/weekly-sync.md")), "Weekly sync");
+    assert_eq!(
+        slug_with("Quarterly Planning: Q3 (draft)", "-", true, false),
+        "quarterly-planning-q3-draft"
+    );
+    assert_eq!(
+        slug_with("Quarterly Planning: Q3 (draft)", "_", false, true),
+        "Quarterly_Planning_Q3_(draft)"
+    );
+}
@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    outline,
+    style::{Styler, BLUE, BOLD, DIM},
+    vault,
+};
+
+/// A file or directory in a tree listing.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    /// The note's title, if this is a note that has one.
+    pub title: Option<String>,
+    /// The entries in the directory, or None if this is a file.
+    pub children: Option<Vec<Entry>>,
+}
+
+/// Returns the entries in the given directory, directories first and each in alphabetical order,
+/// with those in directories down to the given depth, if any, beneath it. Hidden entries are left
+/// out, unless all are asked for, but .git always is. Notes' titles are read if asked for.
+pub fn read(dir: &Path, depth: Option<usize>, all: bool, titles: bool) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let dir_entries =
+        std::fs::read_dir(dir).context(format!("failed to read directory {}", dir.display()))?;
+    for dir_entry in dir_entries {
+        let path = dir_entry
+            .context(format!(
+                "failed to read entry in directory {}",
+                dir.display()
+            ))?
+            .path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if name == ".git" || (name.starts_with('.') && !all) {
+            continue;
+        }
+
+        let children = match (path.is_dir(), depth) {
+            (false, _) => None,
+            (true, Some(0 | 1)) => Some(Vec::new()),
+            (true, depth) => Some(read(&path, depth.map(|depth| depth - 1), all, titles)?),
+        };
+        // Notes that are not text, e.g. encrypted ones, just have no title.
+        let title = match titles && children.is_none() && vault::is_note(&path) {
+            true => std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| outline::title(&content)),
+            false => None,
+        };
+        entries.push(Entry {
+            name,
+            title,
+            children,
+        });
+    }
+    entries.sort_by_cached_key(|entry| (entry.children.is_none(), entry.name.to_lowercase()));
+    Ok(entries)
+}
+
+/// Leaves out the files among the given entries, and in the directories among them, leaving just
+/// the directories, like tree -d.
+pub fn retain_dirs(entries: &mut Vec<Entry>) {
+    entries.retain(|entry| entry.children.is_some());
+    for entry in entries.iter_mut() {
+        if let Some(children) = entry.children.as_mut() {
+            retain_dirs(children);
+        }
+    }
+}
+
+/// Draws the given entries beneath the given root, like tree does, followed by a count of the
+/// directories and files in them. Directories stand out in bold blue, and notes' titles follow
+/// their names.
+pub fn render(root: &str, entries: &[Entry], styler: Styler) -> String {
+    fn render_entries(
+        entries: &[Entry],
+        prefix: &str,
+        styler: Styler,
+        rendered: &mut String,
+        counts: &mut (usize, usize),
+    ) {
+        for (idx, entry) in entries.iter().enumerate() {
+            let is_last = idx == entries.len() - 1;
+            let (branch, indent) = match is_last {
+                true => ("└── ", "    "),
+                false => ("├── ", "│   "),
+            };
+            let name = match (&entry.children, &entry.title) {
+                (Some(_), _) => styler.paint(&entry.name, &[BOLD, BLUE]),
+                (None, Some(title)) => format!("{}  {}", entry.name, styler.paint(title, &[DIM])),
+                (None, None) => entry.name.clone(),
+            };
+            rendered.push_str(&format!("{}{}{}\n", prefix, branch, name));
+            match &entry.children {
+                Some(children) => {
+                    counts.0 += 1;
+                    let prefix = format!("{}{}", prefix, indent);
+                    render_entries(children, &prefix, styler, rendered, counts);
+                }
+                None => counts.1 += 1,
+            }
+        }
+    }
+
+    let mut rendered = format!("{}\n", styler.paint(root, &[BOLD, BLUE]));
+    let mut counts = (0, 0);
+    render_entries(entries, "", styler, &mut rendered, &mut counts);
+    let plural = |count: usize, singular: &str, plural: &str| match count {
+        1 => format!("1 {}", singular),
+        _ => format!("{} {}", count, plural),
+    };
+    rendered.push_str(&format!(
+        "\n{}, {}",
+        plural(counts.0, "directory", "directories"),
+        plural(counts.1, "file", "files")
+    ));
+    rendered
+}
+
+#[test]
+fn render_tree() {
+    let file = |name: &str| Entry {
+        name: name.to_string(),
+        title: None,
+        children: None,
+    };
+    let entries = vec![
+        Entry {
+            name: "journal".to_string(),
+            title: None,
+            children: Some(vec![file("a.md"), file("b.md")]),
+        },
+        Entry {
+            title: Some("Inbox".to_string()),
+            ..file("inbox.md")
+        },
+    ];
+    assert_eq!(
+        render(
+            ".",
+            &entries,
+            Styler::new(crate::cli::ColorMode::Never, true)
+        ),
+        ".\n├── journal\n│   ├── a.md\n│   └── b.md\n└── inbox.md  Inbox\n\n1 directory, 3 files"
+    );
+    let mut entries = entries;
+    retain_dirs(&mut entries);
+    assert_eq!(
+        render(
+            ".",
+            &entries,
+            Styler::new(crate::cli::ColorMode::Never, true)
+        ),
+        ".\n└── journal\n\n1 directory, 0 files"
+    );
+}
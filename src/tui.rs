@@ -0,0 +1,387 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{cli, listing, render, style::Styler};
+
+/// What the user asked for when they left the browser. Everything but quitting is done outside of
+/// it, and the browser is then shown again.
+pub enum Action {
+    Quit,
+    /// Open the note in $EDITOR.
+    Open(PathBuf),
+    /// Create the note, which may not have its extension yet, and open it in $EDITOR.
+    New(PathBuf),
+    Rename(PathBuf, PathBuf),
+    Delete(PathBuf),
+}
+
+/// What the notes are narrowed down to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    All,
+    Dir(PathBuf),
+    Tag(String),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    #[default]
+    Filters,
+    Notes,
+}
+
+#[derive(Debug, Default)]
+enum Mode {
+    #[default]
+    Browse,
+    /// Typing the path of a new note.
+    New(String),
+    /// Typing the path to move the selected note to.
+    Rename(String),
+    ConfirmDelete,
+}
+
+/// The state of the browser, which is kept while an action is done outside of it, so that the
+/// user ends up where they were.
+#[derive(Debug, Default)]
+pub struct State {
+    focus: Pane,
+    mode: Mode,
+    filter: usize,
+    note: usize,
+    scroll: u16,
+    /// A message for the status line, e.g. why the last action failed.
+    pub status: Option<String>,
+}
+
+/// Returns the filters for the given notes: all of them, each directory they are in, indented as
+/// in a tree, and each of their tags.
+fn filters(notes: &[listing::Note]) -> Vec<(String, Filter)> {
+    let dirs = notes
+        .iter()
+        .flat_map(|note| note.path.ancestors().skip(1))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect::<BTreeSet<&Path>>();
+    let tags = notes
+        .iter()
+        .flat_map(|note| &note.tags)
+        .collect::<BTreeSet<&String>>();
+
+    let mut filters = vec![("All notes".to_string(), Filter::All)];
+    for dir in dirs {
+        let label = format!(
+            "{}{}/",
+            "  ".repeat(dir.components().count() - 1),
+            dir.file_name().unwrap_or_default().to_string_lossy()
+        );
+        filters.push((label, Filter::Dir(dir.to_path_buf())));
+    }
+    for tag in tags {
+        filters.push((format!("#{}", tag), Filter::Tag(tag.clone())));
+    }
+    filters
+}
+
+/// Converts the ANSI escape codes that render::render styles notes with into the terminal UI's
+/// styles.
+fn ansi_to_text(rendered: &str) -> Text<'static> {
+    let mut lines = Vec::new();
+    for line in rendered.lines() {
+        let mut spans = Vec::new();
+        let mut style = Style::default();
+        let mut rest = line;
+        while !rest.is_empty() {
+            let Some(escape) = rest.strip_prefix("\x1b[") else {
+                let end = rest.find("\x1b[").unwrap_or(rest.len());
+                spans.push(Span::styled(rest[..end].to_string(), style));
+                rest = &rest[end..];
+                continue;
+            };
+            let Some(end) = escape.find('m') else {
+                break;
+            };
+            for code in escape[..end].split(';') {
+                style = match code {
+                    "0" => Style::default(),
+                    "1" => style.add_modifier(Modifier::BOLD),
+                    "2" => style.add_modifier(Modifier::DIM),
+                    "3" => style.add_modifier(Modifier::ITALIC),
+                    "4" => style.add_modifier(Modifier::UNDERLINED),
+                    "31" => style.fg(Color::Red),
+                    "32" => style.fg(Color::Green),
+                    "33" => style.fg(Color::Yellow),
+                    "34" => style.fg(Color::Blue),
+                    "35" => style.fg(Color::Magenta),
+                    "36" => style.fg(Color::Cyan),
+                    _ => style,
+                };
+            }
+            rest = &escape[end + 1..];
+        }
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
+/// Moves the given index by the given amount, within the given length.
+fn step(idx: usize, by: isize, len: usize) -> usize {
+    idx.saturating_add_signed(by).min(len.saturating_sub(1))
+}
+
+/// Shows the browser: a pane of directories and tags, one of the notes in the selected one, and a
+/// preview of the selected note, until the user asks for an action.
+pub fn browse(args: &cli::Args, state: &mut State) -> Result<Action> {
+    let mut notes = listing::notes(&args.base_dir, None, &[], &HashMap::new())?;
+    listing::pinned_first(&mut notes);
+    let filters = filters(&notes);
+
+    let mut terminal = ratatui::init();
+    let action = loop {
+        state.filter = step(state.filter, 0, filters.len());
+        let shown = notes
+            .iter()
+            .filter(|note| match &filters[state.filter].1 {
+                Filter::All => true,
+                Filter::Dir(dir) => note.path.starts_with(dir),
+                Filter::Tag(tag) => note.tags.contains(tag),
+            })
+            .collect::<Vec<&listing::Note>>();
+        state.note = step(state.note, 0, shown.len());
+        let selected = shown.get(state.note).map(|note| note.path.clone());
+        let preview = match &selected {
+            Some(note) => std::fs::read_to_string(args.base_dir.join(note))
+                .map(|content| render::render(&content, Styler::new(args.color, true)))
+                .unwrap_or_else(|err| format!("failed to read {}: {}", note.display(), err)),
+            None => String::new(),
+        };
+
+        let drawn = terminal.draw(|frame| draw(frame, state, &filters, &shown, &preview));
+        if let Err(err) = drawn {
+            break Err(err).context("failed to draw the browser");
+        }
+        let event = match event::read() {
+            Ok(event) => event,
+            Err(err) => break Err(err).context("failed to read input"),
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        state.status = None;
+        match (&mut state.mode, key.code) {
+            (Mode::New(input) | Mode::Rename(input), KeyCode::Char(c)) => input.push(c),
+            (Mode::New(input) | Mode::Rename(input), KeyCode::Backspace) => {
+                input.pop();
+            }
+            (Mode::New(input), KeyCode::Enter) if !input.is_empty() => {
+                let note = PathBuf::from(std::mem::take(input));
+                state.mode = Mode::Browse;
+                break Ok(Action::New(note));
+            }
+            (Mode::Rename(input), KeyCode::Enter) if !input.is_empty() => {
+                let to = PathBuf::from(std::mem::take(input));
+                state.mode = Mode::Browse;
+                if let Some(from) = selected {
+                    break Ok(Action::Rename(from, to));
+                }
+            }
+            (Mode::ConfirmDelete, KeyCode::Char('y')) => {
+                state.mode = Mode::Browse;
+                if let Some(note) = selected {
+                    break Ok(Action::Delete(note));
+                }
+            }
+            (Mode::New(_) | Mode::Rename(_) | Mode::ConfirmDelete, KeyCode::Enter) => {}
+            (Mode::New(_) | Mode::Rename(_) | Mode::ConfirmDelete, _) => state.mode = Mode::Browse,
+            (Mode::Browse, KeyCode::Char('q') | KeyCode::Esc) => break Ok(Action::Quit),
+            (Mode::Browse, KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right) => {
+                state.focus = match state.focus {
+                    Pane::Filters => Pane::Notes,
+                    Pane::Notes => Pane::Filters,
+                };
+            }
+            (Mode::Browse, KeyCode::Down | KeyCode::Up | KeyCode::Char('j' | 'k')) => {
+                let by = match key.code {
+                    KeyCode::Down | KeyCode::Char('j') => 1,
+                    _ => -1,
+                };
+                match state.focus {
+                    Pane::Filters => {
+                        state.filter = step(state.filter, by, filters.len());
+                        state.note = 0;
+                    }
+                    Pane::Notes => state.note = step(state.note, by, shown.len()),
+                }
+                state.scroll = 0;
+            }
+            (Mode::Browse, KeyCode::PageDown) => state.scroll = state.scroll.saturating_add(10),
+            (Mode::Browse, KeyCode::PageUp) => state.scroll = state.scroll.saturating_sub(10),
+            (Mode::Browse, KeyCode::Enter | KeyCode::Char('e')) => match (state.focus, selected) {
+                (Pane::Filters, _) => state.focus = Pane::Notes,
+                (Pane::Notes, Some(note)) => break Ok(Action::Open(note)),
+                (Pane::Notes, None) => {}
+            },
+            (Mode::Browse, KeyCode::Char('n')) => {
+                // New notes go in the selected directory, if any.
+                let dir = match &filters[state.filter].1 {
+                    Filter::Dir(dir) => format!("{}/", dir.display()),
+                    _ => String::new(),
+                };
+                state.mode = Mode::New(dir);
+            }
+            (Mode::Browse, KeyCode::Char('r')) => {
+                if let Some(note) = &selected {
+                    state.mode = Mode::Rename(note.display().to_string());
+                }
+            }
+            (Mode::Browse, KeyCode::Char('d')) if selected.is_some() => {
+                state.mode = Mode::ConfirmDelete;
+            }
+            _ => {}
+        }
+    };
+    ratatui::restore();
+    action
+}
+
+fn draw(
+    frame: &mut Frame,
+    state: &State,
+    filters: &[(String, Filter)],
+    notes: &[&listing::Note],
+    preview: &str,
+) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [filters_area, notes_area, preview_area] = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+        Constraint::Percentage(50),
+    ])
+    .areas(main);
+
+    let block = |title: String, focused: bool| {
+        let border_style = match focused {
+            true => Style::default().fg(Color::Cyan),
+            false => Style::default().add_modifier(Modifier::DIM),
+        };
+        Block::bordered().title(title).border_style(border_style)
+    };
+    let highlight_style = Style::default().add_modifier(Modifier::REVERSED);
+
+    let filter_items = filters
+        .iter()
+        .map(|(label, _)| ListItem::new(label.as_str()))
+        .collect::<Vec<ListItem>>();
+    frame.render_stateful_widget(
+        List::new(filter_items)
+            .block(block(
+                " Directories & tags ".to_string(),
+                state.focus == Pane::Filters,
+            ))
+            .highlight_style(highlight_style),
+        filters_area,
+        &mut ListState::default().with_selected(Some(state.filter)),
+    );
+
+    let note_items = notes
+        .iter()
+        .map(|note| {
+            let name = note
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let mut spans = match &note.title {
+                Some(title) => vec![
+                    Span::raw(title.clone()),
+                    Span::styled(
+                        format!("  {}", name),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ],
+                None => vec![Span::raw(name)],
+            };
+            if note.pinned {
+                spans.insert(0, Span::styled("★ ", Style::default().fg(Color::Yellow)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect::<Vec<ListItem>>();
+    frame.render_stateful_widget(
+        List::new(note_items)
+            .block(block(
+                format!(" Notes ({}) ", notes.len()),
+                state.focus == Pane::Notes,
+            ))
+            .highlight_style(highlight_style),
+        notes_area,
+        &mut ListState::default().with_selected(Some(state.note).filter(|_| !notes.is_empty())),
+    );
+
+    let preview_title = notes
+        .get(state.note)
+        .map_or(" Preview ".to_string(), |note| {
+            format!(" {} ", note.path.display())
+        });
+    frame.render_widget(
+        Paragraph::new(ansi_to_text(preview))
+            .block(block(preview_title, false))
+            .wrap(Wrap { trim: false })
+            .scroll((state.scroll, 0)),
+        preview_area,
+    );
+
+    let status_line = match (&state.mode, &state.status) {
+        (Mode::New(input), _) => format!("New note (Enter to create, Esc to cancel): {}█", input),
+        (Mode::Rename(input), _) => format!("Rename to (Enter to move, Esc to cancel): {}█", input),
+        (Mode::ConfirmDelete, _) => format!(
+            "Delete {}? (y/n)",
+            notes
+                .get(state.note)
+                .map_or(String::new(), |note| note.path.display().to_string())
+        ),
+        (Mode::Browse, Some(status)) => status.clone(),
+        (Mode::Browse, None) => "↑/↓ move  Tab switch pane  Enter/e edit  n new  r rename  \
+                                 d delete  PgUp/PgDn scroll  q quit"
+            .to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(status_line).style(Style::default().add_modifier(Modifier::DIM)),
+        status,
+    );
+}
+
+#[test]
+fn convert_ansi() {
+    let text = ansi_to_text("a \x1b[1m\x1b[36mb\x1b[0m c");
+    assert_eq!(
+        text.lines[0].spans,
+        vec![
+            Span::raw("a "),
+            Span::styled(
+                "b",
+                Style::default()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Cyan)
+            ),
+            Span::raw(" c"),
+        ]
+    );
+}
@@ -0,0 +1,60 @@
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    cli,
+    cmd::exec_cmd,
+    git::{self, GIT_CMD},
+};
+
+/// The file that the commits jot made are recorded in, so that jot undo only ever undoes those.
+const JOT_COMMITS: &str = "jot-commits";
+
+/// How many of the commits jot made are remembered, i.e. how many times in a row jot undo can undo
+/// the last one.
+const MAX_RECORDED: usize = 100;
+
+fn head(args: &cli::Args) -> Result<String> {
+    let mut git_rev_parse_exec = Command::new(GIT_CMD);
+    git_rev_parse_exec.args(["rev-parse", "HEAD"]);
+    Ok(exec_cmd(
+        "reading HEAD",
+        git_rev_parse_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?
+    .0)
+}
+
+fn recorded() -> Result<Vec<String>> {
+    let path = git::git_path(JOT_COMMITS)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        std::fs::read_to_string(&path).context(format!("failed to read {}", path.display()))?;
+    Ok(content.lines().map(str::to_string).collect())
+}
+
+/// Records the commit that was just made, i.e. HEAD, as one that jot made. Like the lock, the
+/// record lives in the .git directory, so that it is never synced.
+pub fn record(args: &cli::Args) -> Result<()> {
+    let mut commits = recorded()?;
+    commits.push(head(args)?);
+    let skipped = commits.len().saturating_sub(MAX_RECORDED);
+    let path = git::git_path(JOT_COMMITS)?;
+    std::fs::write(
+        &path,
+        commits[skipped..]
+            .iter()
+            .map(|commit| format!("{}\n", commit))
+            .collect::<String>(),
+    )
+    .context(format!("failed to write {}", path.display()))
+}
+
+/// Returns true if jot made the commit with the given hash.
+pub fn is_recorded(hash: &str) -> Result<bool> {
+    Ok(recorded()?.iter().any(|commit| commit == hash))
+}
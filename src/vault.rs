@@ -0,0 +1,105 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// File extensions that we consider to be notes. Everything else in the vault (images, PDFs,
+/// etc) is considered an attachment.
+static NOTE_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+pub fn is_note(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| NOTE_EXTENSIONS.iter().any(|note_ext| ext == *note_ext))
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+fn walk(base_dir: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).context(format!("failed to read directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .context(format!(
+                "failed to read entry in directory {}",
+                dir.display()
+            ))?
+            .path();
+        // Hidden entries are skipped wholesale. Most notably this skips .git, but it also skips
+        // things like editor swap files and any dotfiles jot itself may keep around.
+        if is_hidden(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk(base_dir, &path, files)?;
+        } else {
+            // This unwrap is fine, since everything we walk is, by construction, beneath base_dir.
+            files.push(path.strip_prefix(base_dir).unwrap().to_path_buf());
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns every non-hidden file in the vault, relative to base_dir, in sorted order.
+pub fn files(base_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(base_dir, base_dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Returns every note in the vault, relative to base_dir, in sorted order.
+pub fn notes(base_dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(files(base_dir)?
+        .into_iter()
+        .filter(|path| is_note(path))
+        .collect())
+}
+
+/// Lexically normalizes the given relative path, resolving any `.` and `..` components. Returns
+/// None if the path tries to escape above its root, since for our purposes that means it points
+/// outside of the vault.
+pub fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(part) => normalized.push(part),
+            Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+
+    Some(normalized)
+}
+
+/// Returns the given path (relative to base-dir) relative to the given directory (also relative to
+/// base-dir), e.g. `../assets/a.png` for `assets/a.png` from `notes`.
+pub fn relative_to(dir: &Path, path: &Path) -> PathBuf {
+    let dir: Vec<Component> = dir.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    let common = dir
+        .iter()
+        .zip(&path)
+        .take_while(|(dir, path)| dir == path)
+        .count();
+    std::iter::repeat_n(Component::ParentDir, dir.len() - common)
+        .chain(path[common..].iter().copied())
+        .collect()
+}
+
+#[test]
+fn normalize_paths() {
+    assert_eq!(
+        normalize(Path::new("a/./b/../c.md")),
+        Some(PathBuf::from("a/c.md"))
+    );
+    assert_eq!(normalize(Path::new("a/../../c.md")), None);
+}
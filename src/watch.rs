@@ -0,0 +1,85 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+use crate::git::GIT_CMD;
+
+/// How often base-dir is checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returns a fingerprint of base-dir's state: its HEAD, along with the name, size and modification
+/// time of every file that differs from it. Any change to what a sync would do changes the
+/// fingerprint, be it an edit, a new file or a commit made outside of jot.
+pub fn fingerprint() -> Result<u64> {
+    let head = Command::new(GIT_CMD)
+        .args(["rev-parse", "--quiet", "--verify", "HEAD"])
+        .output()
+        .context("failed to read HEAD")?;
+    let status = Command::new(GIT_CMD)
+        .args(["status", "--porcelain", "-z", "--untracked-files=all"])
+        .output()
+        .context("failed to list changed files")?;
+
+    let mut hasher = DefaultHasher::new();
+    head.stdout.hash(&mut hasher);
+    status.stdout.hash(&mut hasher);
+    // Entries are `XY path`, where the path is relative to the repository's root. Renames are
+    // followed by their original path, which no longer exists, and so has no metadata to hash.
+    for entry in String::from_utf8_lossy(&status.stdout).split('\0') {
+        let path = entry.get(3..).unwrap_or(entry);
+        if let Ok(metadata) = std::fs::metadata(Path::new(path)) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// Tracks how long a fingerprint has been unchanged, so that bursts of changes, e.g. an editor
+/// writing a swap file and then the file itself, can be waited out before acting on them.
+pub struct Debouncer {
+    fingerprint: u64,
+    changed_at: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(fingerprint: u64) -> Debouncer {
+        Debouncer {
+            fingerprint,
+            changed_at: None,
+        }
+    }
+
+    /// Records the given fingerprint. Returns true if it has differed from the one given on
+    /// creation (or the last reset) and then stayed the same for at least the given time.
+    pub fn settled(&mut self, fingerprint: u64, quiet_period: Duration) -> bool {
+        if fingerprint != self.fingerprint {
+            self.fingerprint = fingerprint;
+            self.changed_at = Some(Instant::now());
+        }
+        self.changed_at
+            .is_some_and(|changed_at| changed_at.elapsed() >= quiet_period)
+    }
+
+    /// Forgets about any change so far, taking the given fingerprint as the new baseline.
+    pub fn reset(&mut self, fingerprint: u64) {
+        self.fingerprint = fingerprint;
+        self.changed_at = None;
+    }
+}
+
+#[test]
+fn debouncer() {
+    let mut debouncer = Debouncer::new(1);
+    assert!(!debouncer.settled(1, Duration::ZERO));
+    assert!(debouncer.settled(2, Duration::ZERO));
+    assert!(!debouncer.settled(3, Duration::from_secs(60)));
+    debouncer.reset(3);
+    assert!(!debouncer.settled(3, Duration::ZERO));
+}
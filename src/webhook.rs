@@ -0,0 +1,107 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{cli, cmd::exec_cmd, git::GIT_CMD, json::Value};
+
+static CURL_CMD: &str = "curl";
+
+/// How long the webhook may take to respond before it is given up on.
+const TIMEOUT_SECS: &str = "10";
+
+/// Returns the commit HEAD points to, or None if there is none yet.
+pub fn head() -> Result<Option<String>> {
+    let output = Command::new(GIT_CMD)
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .output()
+        .context("failed to resolve HEAD")?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Returns the JSON payload that describes the sync that moved HEAD from the given commit to the
+/// given one: the files that changed between them, the new HEAD and its message.
+fn payload(args: &cli::Args, before: Option<&str>, after: &str) -> Result<Value> {
+    let mut git_exec = Command::new(GIT_CMD);
+    match before {
+        Some(before) => git_exec.args(["diff", "--name-only", before, after]),
+        None => git_exec.args(["ls-tree", "-r", "--name-only", after]),
+    };
+    let (files, _) = exec_cmd(
+        "listing changed files",
+        git_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+
+    let mut git_exec = Command::new(GIT_CMD);
+    git_exec.args(["log", "-1", "--format=%B", after]);
+    let (message, _) = exec_cmd(
+        "reading the commit message",
+        git_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+
+    Ok(Value::object([
+        ("base_dir", args.base_dir.display().to_string().into()),
+        ("commit", after.into()),
+        ("message", message.into()),
+        (
+            "files",
+            Value::Array(files.lines().map(Value::from).collect()),
+        ),
+    ]))
+}
+
+/// POSTs a JSON payload describing the sync that moved HEAD from the given commit to the
+/// webhook-url, if the sync moved HEAD at all.
+pub fn post(args: &cli::Args, url: &str, before: Option<&str>) -> Result<()> {
+    let Some(after) = head()? else {
+        return Ok(());
+    };
+    if before == Some(after.as_str()) {
+        return Ok(());
+    }
+
+    let payload = payload(args, before, &after)?;
+    // The payload is passed on stdin, since it can be too long to be an argument.
+    let mut child = Command::new(CURL_CMD)
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            TIMEOUT_SECS,
+        ])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to execute curl to post to the webhook")?;
+    // The unwrap is fine, since stdin was piped just above.
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(payload.to_string().as_bytes())
+        .context("failed to pass the webhook's payload to curl")?;
+    let output = child
+        .wait_with_output()
+        .context("failed to execute curl to post to the webhook")?;
+    if !output.status.success() {
+        bail!(
+            "failed to post to the webhook at {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::{
+    path::Path,
+    process::Command,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{cli, cmd::exec_cmd, git::GIT_CMD, watch};
+
+/// The message that work-in-progress commits are made with.
+const WIP_MESSAGE: &str = "jot: work in progress";
+
+/// Returns what identifies the given file's current content for our purposes, i.e. its size and
+/// modification time, or None if it does not exist (yet).
+fn file_state(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.len(), metadata.modified().ok()))
+}
+
+/// Commits the given note on its own, as a work-in-progress commit. Hooks are skipped, since the
+/// commit is squashed away again once editing is done.
+fn commit(args: &cli::Args, note: &Path) -> Result<()> {
+    let mut git_add_exec = Command::new(GIT_CMD);
+    git_add_exec.args(["add", "--"]).arg(note);
+    exec_cmd("staging", git_add_exec, true, args.quiet_on_ctrl_c)?;
+    let mut git_commit_exec = Command::new(GIT_CMD);
+    git_commit_exec
+        .args(["commit", "--quiet", "--no-verify", "-m", WIP_MESSAGE, "--"])
+        .arg(note);
+    exec_cmd("committing", git_commit_exec, true, args.quiet_on_ctrl_c)?;
+    Ok(())
+}
+
+/// Runs the given operation, i.e. editing the given note, while committing the note every given
+/// interval for as long as it keeps changing, so that a crash never loses more than that much
+/// writing. Once the operation is done, the work-in-progress commits are undone again, leaving
+/// their changes staged for the sync that follows to commit as one. Failing to make them is only
+/// reported afterwards, since printing anything would garble the editor's screen.
+pub fn commit_while<T>(
+    args: &cli::Args,
+    note: &Path,
+    interval: Duration,
+    operation: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let mut git_rev_parse_exec = Command::new(GIT_CMD);
+    git_rev_parse_exec.args(["rev-parse", "HEAD"]);
+    let (head, _) = exec_cmd(
+        "reading HEAD",
+        git_rev_parse_exec,
+        true,
+        args.quiet_on_ctrl_c,
+    )?;
+
+    let done = AtomicBool::new(false);
+    let (result, (committed, failure)) = std::thread::scope(|scope| {
+        let committer = scope.spawn(|| {
+            let mut committed_state = file_state(note);
+            let mut committed_at = Instant::now();
+            let mut committed = false;
+            while !done.load(Ordering::SeqCst) {
+                std::thread::sleep(watch::POLL_INTERVAL);
+                let state = file_state(note);
+                if state.is_none() || state == committed_state || committed_at.elapsed() < interval
+                {
+                    continue;
+                }
+                if let Err(err) = commit(args, note) {
+                    return (committed, Some(err));
+                }
+                (committed_state, committed_at, committed) = (state, Instant::now(), true);
+            }
+            (committed, None)
+        });
+        let result = operation();
+        done.store(true, Ordering::SeqCst);
+        (
+            result,
+            committer
+                .join()
+                .expect("work-in-progress committer panicked"),
+        )
+    });
+
+    if let Some(err) = failure {
+        println!(
+            "warning: failed to make a work-in-progress commit of {}: {:#}",
+            note.display(),
+            err
+        );
+    }
+    if committed {
+        let mut git_reset_exec = Command::new(GIT_CMD);
+        git_reset_exec.args(["reset", "--soft", &head]);
+        exec_cmd(
+            "squashing work-in-progress commits",
+            git_reset_exec,
+            true,
+            args.quiet_on_ctrl_c,
+        )
+        .context(format!(
+            "failed to squash the work-in-progress commits of {}, they are still there, undo \
+            them with jot git reset --soft {}",
+            note.display(),
+            head
+        ))?;
+    }
+    result
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::date::{self, Date};
+
+/// How many digits Zettelkasten IDs have, i.e. YYYYMMDDHHMM.
+const ID_LEN: usize = 12;
+
+/// Returns the Zettelkasten ID of the given local time, in seconds since the Unix epoch, e.g.
+/// 202405021130 for 2024-05-02 11:30.
+fn id(local_time: i64) -> String {
+    let date = Date::from_days(local_time.div_euclid(24 * 60 * 60));
+    let seconds_of_day = local_time.rem_euclid(24 * 60 * 60);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}",
+        date.year,
+        date.month,
+        date.day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    )
+}
+
+/// Returns whether the given name is a Zettelkasten ID.
+pub fn is_id(name: &str) -> bool {
+    name.len() == ID_LEN && name.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns the ID of the given note, if it is named after one, e.g. 202405021130 for
+/// `202405021130-some-title.md`, whatever the separator after the ID is.
+pub fn note_id(note: &std::path::Path) -> Option<&str> {
+    let stem = note.file_stem()?.to_str()?;
+    let id = stem.get(..ID_LEN)?;
+    let rest = &stem[ID_LEN..];
+    let is_separated = rest.chars().next().is_none_or(|c| !c.is_ascii_digit());
+    (is_id(id) && is_separated).then_some(id)
+}
+
+/// Returns a Zettelkasten ID for a new note, i.e. the current minute, unless one of the given notes
+/// has that ID already, in which case it is the first free minute after it.
+pub fn new_id(notes: &[PathBuf]) -> Result<String> {
+    let mut local_time = date::local_now()?;
+    loop {
+        let id = id(local_time);
+        if !notes.iter().any(|note| note_id(note) == Some(id.as_str())) {
+            return Ok(id);
+        }
+        local_time += 60;
+    }
+}
+
+#[test]
+fn zettel_ids() {
+    // 2024-05-02 11:30:59.
+    assert_eq!(
+        id(19_845 * 24 * 60 * 60 + 11 * 3600 + 30 * 60 + 59),
+        "202405021130"
+    );
+    assert_eq!(
+        note_id(std::path::Path::new("z/202405021130-some-title.md")),
+        Some("202405021130")
+    );
+    assert_eq!(note_id(std::path::Path::new("2024050211301.md")), None);
+}
@@ -0,0 +1,59 @@
+use std::{path::PathBuf, process::Command};
+
+/// Creates an empty directory to run jot in, named after the given test.
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jot-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn git(dir: &PathBuf, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=jot", "-c", "user.email=jot@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn relative_base_dir_refuses_to_sync_locked_vault() {
+    let dir = scratch_dir("locked-vault");
+    let vault = dir.join("vault");
+    std::fs::create_dir(&vault).unwrap();
+    git(&vault, &["init", "--quiet"]);
+    std::fs::write(
+        vault.join(".gitattributes"),
+        "* filter=git-crypt diff=git-crypt\n.gitattributes !filter !diff\n",
+    )
+    .unwrap();
+    git(&vault, &["add", ".gitattributes"]);
+    git(&vault, &["commit", "--quiet", "-m", "init"]);
+    std::fs::write(vault.join("secret.md"), "# Secret\n").unwrap();
+
+    // Without git-crypt's key the vault is locked, so syncing it would commit the note as it is.
+    let output = Command::new(env!("CARGO_BIN_EXE_jot"))
+        .args([
+            "-b", "vault", "--finder", "true", "sync", "--local", "-m", "x",
+        ])
+        .current_dir(&dir)
+        .env("GIT_AUTHOR_NAME", "jot")
+        .env("GIT_AUTHOR_EMAIL", "jot@example.com")
+        .env("GIT_COMMITTER_NAME", "jot")
+        .env("GIT_COMMITTER_EMAIL", "jot@example.com")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("locked"), "unexpected error: {}", stderr);
+
+    let log = Command::new("git")
+        .args(["log", "--oneline"])
+        .current_dir(&vault)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&log.stdout).lines().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}